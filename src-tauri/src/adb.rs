@@ -19,7 +19,7 @@ pub fn adb_devices() -> Result<String, String> {
 #[tauri::command]
 pub fn adb_connect(address: String) -> Result<String, String> {
     let mut cmd = Command::new("adb");
-    cmd.args(&["connect", &address]);
+    cmd.args(["connect", &address]);
     #[cfg(target_os = "windows")]
     const CREATE_NO_WINDOW: u32 = 0x08000000;
     #[cfg(target_os = "windows")]
@@ -34,7 +34,7 @@ pub fn adb_connect(address: String) -> Result<String, String> {
 #[tauri::command]
 pub fn adb_disconnect(device: String) -> Result<String, String> {
     let mut cmd = Command::new("adb");
-    cmd.args(&["disconnect", &device]);
+    cmd.args(["disconnect", &device]);
     #[cfg(target_os = "windows")]
     const CREATE_NO_WINDOW: u32 = 0x08000000;
     #[cfg(target_os = "windows")]
@@ -49,7 +49,7 @@ pub fn adb_disconnect(device: String) -> Result<String, String> {
 #[tauri::command]
 pub fn emulator_list_avds() -> Result<String, String> {
     let mut cmd = Command::new("emulator");
-    cmd.args(&["-list-avds"]);
+    cmd.args(["-list-avds"]);
     #[cfg(target_os = "windows")]
     const CREATE_NO_WINDOW: u32 = 0x08000000;
     #[cfg(target_os = "windows")]
@@ -64,7 +64,7 @@ pub fn emulator_list_avds() -> Result<String, String> {
 #[tauri::command]
 pub fn emulator_start(avd_name: String) -> Result<String, String> {
     let mut cmd = Command::new("emulator");
-    cmd.args(&["-avd", &avd_name]);
+    cmd.args(["-avd", &avd_name]);
     #[cfg(target_os = "windows")]
     const CREATE_NO_WINDOW: u32 = 0x08000000;
     #[cfg(target_os = "windows")]
@@ -0,0 +1,143 @@
+use crate::error::FsError;
+use crate::filesystem::{content_hash, write_private};
+use crate::models::FileVersion;
+use crate::storage;
+use crate::workspace;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+lazy_static::lazy_static! {
+    static ref LAST_BACKUP: Mutex<HashMap<PathBuf, String>> = Mutex::new(HashMap::new());
+}
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BufferBackup {
+    pub path: String,
+    pub content: String,
+    pub base_version: Option<FileVersion>,
+    pub saved_at: i64,
+}
+#[derive(Serialize, Debug, Clone)]
+pub struct RecoverableBuffer {
+    #[serde(flatten)]
+    pub backup: BufferBackup,
+    pub disk_changed: bool,
+}
+fn workspace_dir(root: &Path) -> PathBuf {
+    storage::data_dir()
+        .join("backups")
+        .join(content_hash(root.to_string_lossy().as_bytes()))
+}
+fn backup_path(resolved: &Path) -> Option<PathBuf> {
+    let root = workspace::root_for(resolved)?;
+    let name = format!(
+        "{}.json",
+        content_hash(resolved.to_string_lossy().as_bytes())
+    );
+    Some(workspace_dir(&root).join(name))
+}
+pub fn clear(resolved: &Path) {
+    LAST_BACKUP.lock().unwrap().remove(resolved);
+    if let Some(path) = backup_path(resolved) {
+        let _ = fs::remove_file(path);
+    }
+}
+fn disk_changed(backup: &BufferBackup) -> bool {
+    match (fs::read(&backup.path), &backup.base_version) {
+        (Ok(bytes), Some(base)) => content_hash(&bytes) != base.hash,
+        (Ok(_), None) => true,
+        (Err(_), base) => base.is_some(),
+    }
+}
+#[tauri::command]
+pub fn backup_buffer(
+    path: String,
+    content: String,
+    base_version: Option<FileVersion>,
+) -> Result<(), FsError> {
+    let resolved = workspace::resolve(&path)?;
+    let target = match backup_path(&resolved) {
+        Some(target) => target,
+        None => return Ok(()),
+    };
+    let hash = content_hash(content.as_bytes());
+    if LAST_BACKUP.lock().unwrap().get(&resolved) == Some(&hash) && target.exists() {
+        return Ok(());
+    }
+    let backup = BufferBackup {
+        path: resolved.to_string_lossy().to_string(),
+        content,
+        base_version,
+        saved_at: Utc::now().timestamp_millis(),
+    };
+    let bytes = serde_json::to_vec(&backup).map_err(|e| e.to_string())?;
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    write_private(&target, &bytes)?;
+    LAST_BACKUP.lock().unwrap().insert(resolved, hash);
+    Ok(())
+}
+#[tauri::command]
+pub fn discard_buffer_backup(path: String) -> Result<(), FsError> {
+    clear(&workspace::resolve(&path)?);
+    Ok(())
+}
+#[tauri::command]
+pub fn list_recoverable_buffers(workspace: String) -> Result<Vec<RecoverableBuffer>, FsError> {
+    let root = workspace::resolve(&workspace)?;
+    let entries = match fs::read_dir(workspace_dir(&root)) {
+        Ok(entries) => entries,
+        Err(_) => return Ok(Vec::new()),
+    };
+    let mut buffers: Vec<RecoverableBuffer> = entries
+        .flatten()
+        .filter_map(|entry| fs::read(entry.path()).ok())
+        .filter_map(|bytes| serde_json::from_slice::<BufferBackup>(&bytes).ok())
+        .filter(|backup| Path::new(&backup.path).starts_with(&root))
+        .map(|backup| RecoverableBuffer {
+            disk_changed: disk_changed(&backup),
+            backup,
+        })
+        .collect();
+    buffers.sort_by_key(|b| std::cmp::Reverse(b.backup.saved_at));
+    Ok(buffers)
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::filesystem::read_with_version;
+    fn path(p: &Path) -> String {
+        p.to_string_lossy().to_string()
+    }
+    fn recoverable(root: &Path) -> Vec<(String, bool)> {
+        list_recoverable_buffers(path(root))
+            .unwrap()
+            .into_iter()
+            .map(|b| (b.backup.content, b.disk_changed))
+            .collect()
+    }
+    #[test]
+    fn backups_are_listed_until_discarded() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = workspace::open_for_test(dir.path());
+        let file = root.join("a.txt");
+        fs::write(&file, "saved").unwrap();
+        let version = read_with_version(&file).unwrap().version;
+        backup_buffer(path(&file), "draft".to_string(), Some(version)).unwrap();
+        backup_buffer(path(&root.join("new.txt")), "unsaved".to_string(), None).unwrap();
+        let mut found = recoverable(&root);
+        found.sort();
+        assert_eq!(
+            found,
+            vec![("draft".to_string(), false), ("unsaved".to_string(), false)]
+        );
+        discard_buffer_backup(path(&root.join("new.txt"))).unwrap();
+        fs::write(&file, "changed by git").unwrap();
+        assert_eq!(recoverable(&root), vec![("draft".to_string(), true)]);
+        clear(&file);
+        assert!(recoverable(&root).is_empty());
+    }
+}
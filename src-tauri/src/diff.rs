@@ -0,0 +1,30 @@
+use serde::Serialize;
+use similar::{ChangeTag, TextDiff};
+const CONTEXT_LINES: usize = 3;
+#[derive(Serialize, Debug, Clone)]
+pub struct FileDiff {
+    pub unified: String,
+    pub insertions: usize,
+    pub deletions: usize,
+}
+pub fn unified(old: &str, new: &str, old_header: &str, new_header: &str) -> FileDiff {
+    let diff = TextDiff::from_lines(old, new);
+    let (mut insertions, mut deletions) = (0, 0);
+    for change in diff.iter_all_changes() {
+        match change.tag() {
+            ChangeTag::Insert => insertions += 1,
+            ChangeTag::Delete => deletions += 1,
+            ChangeTag::Equal => {}
+        }
+    }
+    let unified = diff
+        .unified_diff()
+        .context_radius(CONTEXT_LINES)
+        .header(old_header, new_header)
+        .to_string();
+    FileDiff {
+        unified,
+        insertions,
+        deletions,
+    }
+}
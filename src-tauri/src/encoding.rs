@@ -0,0 +1,181 @@
+use crate::models::LineEnding;
+use encoding_rs::{Encoding, UTF_16BE, UTF_16LE, UTF_8, WINDOWS_1252};
+const SNIFF_LEN: usize = 8192;
+pub struct Decoded {
+    pub content: String,
+    pub encoding: &'static Encoding,
+    pub has_bom: bool,
+    pub is_binary: bool,
+}
+fn sniff_utf16(sample: &[u8]) -> Option<&'static Encoding> {
+    if sample.len() < 4 {
+        return None;
+    }
+    let pairs = sample.len() / 2;
+    let even_zeros = sample.iter().step_by(2).filter(|b| **b == 0).count();
+    let odd_zeros = sample
+        .iter()
+        .skip(1)
+        .step_by(2)
+        .filter(|b| **b == 0)
+        .count();
+    // Mostly-ASCII UTF-16 text has a zero in every other byte and almost none in the other half.
+    if odd_zeros * 10 >= pairs * 4 && even_zeros * 20 < pairs {
+        Some(UTF_16LE)
+    } else if even_zeros * 10 >= pairs * 4 && odd_zeros * 20 < pairs {
+        Some(UTF_16BE)
+    } else {
+        None
+    }
+}
+pub fn decode(bytes: &[u8]) -> Decoded {
+    if let Some((encoding, bom_len)) = Encoding::for_bom(bytes) {
+        let (content, _) = encoding.decode_without_bom_handling(&bytes[bom_len..]);
+        return Decoded {
+            content: content.into_owned(),
+            encoding,
+            has_bom: true,
+            is_binary: false,
+        };
+    }
+    let sample = &bytes[..bytes.len().min(SNIFF_LEN)];
+    if sample.contains(&0) {
+        if let Some(encoding) = sniff_utf16(sample) {
+            let (content, _) = encoding.decode_without_bom_handling(bytes);
+            return Decoded {
+                content: content.into_owned(),
+                encoding,
+                has_bom: false,
+                is_binary: false,
+            };
+        }
+        return Decoded {
+            content: String::new(),
+            encoding: UTF_8,
+            has_bom: false,
+            is_binary: true,
+        };
+    }
+    let (content, encoding) = match std::str::from_utf8(bytes) {
+        Ok(text) => (text.to_string(), UTF_8),
+        Err(_) => {
+            let (content, _) = WINDOWS_1252.decode_without_bom_handling(bytes);
+            (content.into_owned(), WINDOWS_1252)
+        }
+    };
+    Decoded {
+        content,
+        encoding,
+        has_bom: false,
+        is_binary: false,
+    }
+}
+pub fn encode(
+    content: &str,
+    encoding: &'static Encoding,
+    with_bom: bool,
+) -> Result<Vec<u8>, String> {
+    let mut bytes = Vec::with_capacity(content.len() + 3);
+    if encoding == UTF_16LE || encoding == UTF_16BE {
+        let little = encoding == UTF_16LE;
+        if with_bom {
+            bytes.extend_from_slice(if little { &[0xFF, 0xFE] } else { &[0xFE, 0xFF] });
+        }
+        for unit in content.encode_utf16() {
+            bytes.extend_from_slice(&if little {
+                unit.to_le_bytes()
+            } else {
+                unit.to_be_bytes()
+            });
+        }
+        return Ok(bytes);
+    }
+    if with_bom && encoding == UTF_8 {
+        bytes.extend_from_slice(&[0xEF, 0xBB, 0xBF]);
+    }
+    let (encoded, _, had_errors) = encoding.encode(content);
+    if had_errors {
+        return Err(format!(
+            "Content contains characters that cannot be saved as {}",
+            encoding.name()
+        ));
+    }
+    bytes.extend_from_slice(&encoded);
+    Ok(bytes)
+}
+pub fn lookup(label: &str) -> Result<&'static Encoding, String> {
+    Encoding::for_label(label.as_bytes()).ok_or_else(|| format!("Unknown encoding: {}", label))
+}
+pub fn detect_line_ending(text: &str) -> LineEnding {
+    let crlf = text.matches("\r\n").count();
+    let lf = text.matches('\n').count() - crlf;
+    match (lf, crlf) {
+        (_, 0) => LineEnding::Lf,
+        (0, _) => LineEnding::Crlf,
+        _ => LineEnding::Mixed,
+    }
+}
+pub fn apply_line_ending(text: &str, line_ending: LineEnding) -> String {
+    match line_ending {
+        LineEnding::Lf => text.replace("\r\n", "\n"),
+        LineEnding::Crlf => text.replace("\r\n", "\n").replace('\n', "\r\n"),
+        LineEnding::Mixed => text.to_string(),
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    fn round_trip(bytes: &[u8]) -> Decoded {
+        let decoded = decode(bytes);
+        assert_eq!(
+            encode(&decoded.content, decoded.encoding, decoded.has_bom).unwrap(),
+            bytes
+        );
+        decoded
+    }
+    #[test]
+    fn utf8_with_and_without_bom_round_trips() {
+        let plain = round_trip("naïve\n".as_bytes());
+        assert_eq!((plain.encoding, plain.has_bom), (UTF_8, false));
+        let bom = round_trip(b"\xEF\xBB\xBFhi");
+        assert_eq!((bom.content.as_str(), bom.has_bom), ("hi", true));
+    }
+    #[test]
+    fn utf16_is_detected_with_or_without_bom() {
+        let text = "hello world";
+        let le: Vec<u8> = text.encode_utf16().flat_map(|u| u.to_le_bytes()).collect();
+        let sniffed = round_trip(&le);
+        assert_eq!(
+            (sniffed.encoding, sniffed.content.as_str()),
+            (UTF_16LE, text)
+        );
+        let mut be = vec![0xFE, 0xFF];
+        be.extend(text.encode_utf16().flat_map(|u| u.to_be_bytes()));
+        let bom = round_trip(&be);
+        assert_eq!((bom.encoding, bom.has_bom), (UTF_16BE, true));
+    }
+    #[test]
+    fn invalid_utf8_falls_back_to_windows_1252() {
+        let decoded = round_trip(b"caf\xE9");
+        assert_eq!(
+            (decoded.encoding, decoded.content.as_str()),
+            (WINDOWS_1252, "café")
+        );
+        assert!(encode("日本", WINDOWS_1252, false).is_err());
+    }
+    #[test]
+    fn nul_bytes_without_a_utf16_pattern_are_binary() {
+        assert!(decode(b"\x7fELF\x02\x01\x01\x00\x00\x00\x00\x00\xff\xfe\x03\x00").is_binary);
+    }
+    #[test]
+    fn line_endings_are_detected_and_normalised() {
+        assert!(matches!(detect_line_ending("a\nb\n"), LineEnding::Lf));
+        assert!(matches!(detect_line_ending("a\r\nb\r\n"), LineEnding::Crlf));
+        assert!(matches!(detect_line_ending("a\r\nb\n"), LineEnding::Mixed));
+        assert_eq!(
+            apply_line_ending("a\r\nb\n", LineEnding::Crlf),
+            "a\r\nb\r\n"
+        );
+        assert_eq!(apply_line_ending("a\r\nb\n", LineEnding::Lf), "a\nb\n");
+    }
+}
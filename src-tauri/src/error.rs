@@ -0,0 +1,50 @@
+use crate::models::FileContent;
+use serde::Serialize;
+use std::fmt;
+#[derive(Serialize, Debug)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum FsError {
+    Io { message: String },
+    Stale { path: String, current: FileContent },
+    Deleted { path: String },
+    TooLarge { path: String, size: u64, limit: u64 },
+    OutsideWorkspace { path: String },
+    Binary { path: String },
+}
+impl fmt::Display for FsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FsError::Io { message } => write!(f, "{}", message),
+            FsError::Stale { path, .. } => write!(f, "{} was changed on disk", path),
+            FsError::Deleted { path } => write!(f, "{} was deleted on disk", path),
+            FsError::TooLarge { path, size, limit } => write!(
+                f,
+                "{} is {} bytes, above the {} byte limit for opening in the editor",
+                path, size, limit
+            ),
+            FsError::OutsideWorkspace { path } => {
+                write!(f, "{} is outside the open workspace", path)
+            }
+            FsError::Binary { path } => write!(f, "{} is a binary file", path),
+        }
+    }
+}
+impl From<std::io::Error> for FsError {
+    fn from(e: std::io::Error) -> Self {
+        FsError::Io {
+            message: e.to_string(),
+        }
+    }
+}
+impl From<String> for FsError {
+    fn from(message: String) -> Self {
+        FsError::Io { message }
+    }
+}
+impl From<&str> for FsError {
+    fn from(message: &str) -> Self {
+        FsError::Io {
+            message: message.to_string(),
+        }
+    }
+}
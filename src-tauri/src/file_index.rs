@@ -0,0 +1,227 @@
+use crate::walker;
+use crate::watcher::{self, FsChange};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+struct Entry<T> {
+    size: u64,
+    modified: Option<SystemTime>,
+    value: T,
+}
+// Values are only valid for the key they were derived with, e.g. the TODO tag set.
+struct RootIndex<K, T> {
+    key: K,
+    files: HashMap<PathBuf, Entry<T>>,
+}
+// Per-file results for each workspace root, rescanned by mtime or from watcher changes.
+pub struct FileIndex<K, T> {
+    roots: Mutex<HashMap<PathBuf, RootIndex<K, T>>>,
+}
+fn stamp(path: &Path) -> Option<(u64, Option<SystemTime>)> {
+    let metadata = fs::metadata(path).ok().filter(|m| m.is_file())?;
+    Some((metadata.len(), metadata.modified().ok()))
+}
+// Stamps are taken before scanning, so a write during the scan is picked up next time.
+fn scan_stamped<K, T>(
+    key: &K,
+    paths: Vec<PathBuf>,
+    scan: impl Fn(&K, &[PathBuf]) -> Vec<(PathBuf, T)>,
+) -> Vec<(PathBuf, Entry<T>)> {
+    let stamps: HashMap<PathBuf, (u64, Option<SystemTime>)> = paths
+        .into_iter()
+        .filter_map(|path| stamp(&path).map(|s| (path, s)))
+        .collect();
+    let paths: Vec<PathBuf> = stamps.keys().cloned().collect();
+    scan(key, &paths)
+        .into_iter()
+        .filter_map(|(path, value)| {
+            let (size, modified) = *stamps.get(&path)?;
+            Some((
+                path,
+                Entry {
+                    size,
+                    modified,
+                    value,
+                },
+            ))
+        })
+        .collect()
+}
+impl<K: Clone + PartialEq, T> Default for FileIndex<K, T> {
+    fn default() -> Self {
+        Self {
+            roots: Mutex::new(HashMap::new()),
+        }
+    }
+}
+impl<K: Clone + PartialEq, T> FileIndex<K, T> {
+    // Watched roots are kept current by apply_changes; others are rechecked by mtime.
+    pub fn ensure(
+        &self,
+        root: &Path,
+        key: &K,
+        wanted: impl Fn(&Path) -> bool,
+        scan: impl Fn(&K, &[PathBuf]) -> Vec<(PathBuf, T)>,
+    ) {
+        let current = self
+            .roots
+            .lock()
+            .unwrap()
+            .get(root)
+            .is_some_and(|index| index.key == *key);
+        if !current || !watcher::is_watching(root) {
+            self.refresh(root, key, wanted, scan);
+        }
+    }
+    fn refresh(
+        &self,
+        root: &Path,
+        key: &K,
+        wanted: impl Fn(&Path) -> bool,
+        scan: impl Fn(&K, &[PathBuf]) -> Vec<(PathBuf, T)>,
+    ) {
+        let files: Vec<PathBuf> = walker::walk_files(root).filter(|f| wanted(f)).collect();
+        let stale: Vec<PathBuf> = {
+            let roots = self.roots.lock().unwrap();
+            let known = roots
+                .get(root)
+                .filter(|index| index.key == *key)
+                .map(|index| &index.files);
+            files
+                .iter()
+                .filter(|file| {
+                    let indexed = known
+                        .and_then(|k| k.get(*file))
+                        .map(|e| (e.size, e.modified));
+                    indexed.is_none() || indexed != stamp(file)
+                })
+                .cloned()
+                .collect()
+        };
+        let scanned = scan_stamped(key, stale, scan);
+        let live: HashSet<&PathBuf> = files.iter().collect();
+        let mut roots = self.roots.lock().unwrap();
+        let index = roots
+            .entry(root.to_path_buf())
+            .or_insert_with(|| RootIndex {
+                key: key.clone(),
+                files: HashMap::new(),
+            });
+        if index.key != *key {
+            index.key = key.clone();
+            index.files.clear();
+        }
+        index.files.retain(|path, _| live.contains(path));
+        index.files.extend(scanned);
+    }
+    pub fn apply_changes(
+        &self,
+        root: &Path,
+        changes: &[FsChange],
+        wanted: impl Fn(&Path) -> bool,
+        scan: impl Fn(&K, &[PathBuf]) -> Vec<(PathBuf, T)>,
+    ) {
+        let key = match self.roots.lock().unwrap().get(root) {
+            Some(index) => index.key.clone(),
+            None => return,
+        };
+        let touched: Vec<PathBuf> = changes
+            .iter()
+            .flat_map(FsChange::paths)
+            .map(PathBuf::from)
+            .collect();
+        let mut files = Vec::new();
+        for path in &touched {
+            if path.is_dir() {
+                files.extend(walker::walk_subtree(root, path).filter(|f| wanted(f)));
+            } else if path.is_file() && wanted(path) {
+                files.push(path.clone());
+            }
+        }
+        let scanned = scan_stamped(&key, files, scan);
+        let mut roots = self.roots.lock().unwrap();
+        if let Some(index) = roots.get_mut(root).filter(|index| index.key == key) {
+            index
+                .files
+                .retain(|path, _| !touched.iter().any(|t| path.starts_with(t)));
+            index.files.extend(scanned);
+        }
+    }
+    pub fn unload(&self, root: &Path) {
+        self.roots.lock().unwrap().remove(root);
+    }
+    pub fn with<R>(
+        &self,
+        root: &Path,
+        f: impl FnOnce(&mut dyn Iterator<Item = (&Path, &T)>) -> R,
+    ) -> R {
+        let roots = self.roots.lock().unwrap();
+        let mut files = roots
+            .get(root)
+            .into_iter()
+            .flat_map(|index| index.files.iter())
+            .map(|(path, entry)| (path.as_path(), &entry.value));
+        f(&mut files)
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::FileNode;
+    fn lengths(key: &usize, paths: &[PathBuf]) -> Vec<(PathBuf, usize)> {
+        paths
+            .iter()
+            .map(|p| (p.clone(), fs::read(p).unwrap().len() * key))
+            .collect()
+    }
+    fn total(index: &FileIndex<usize, usize>, root: &Path) -> usize {
+        index.with(root, |files| files.map(|(_, n)| *n).sum())
+    }
+    fn modified(path: &Path) -> FsChange {
+        FsChange::Modified {
+            node: FileNode {
+                name: String::new(),
+                path: path.to_string_lossy().to_string(),
+                is_dir: false,
+                children: None,
+                ignored: false,
+            },
+        }
+    }
+    #[test]
+    fn refreshes_changed_files_and_rebuilds_for_a_new_key() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::write(root.join("a.txt"), "aa").unwrap();
+        fs::write(root.join("skip.md"), "ignored").unwrap();
+        let wanted = |p: &Path| p.extension().is_some_and(|e| e == "txt");
+        let index = FileIndex::default();
+        index.ensure(root, &1, wanted, lengths);
+        assert_eq!(total(&index, root), 2);
+        fs::write(root.join("b.txt"), "bbb").unwrap();
+        fs::remove_file(root.join("a.txt")).unwrap();
+        index.ensure(root, &1, wanted, lengths);
+        assert_eq!(total(&index, root), 3);
+        index.ensure(root, &10, wanted, lengths);
+        assert_eq!(total(&index, root), 30);
+    }
+    #[test]
+    fn applies_watcher_changes_to_loaded_roots_only() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let file = root.join("a.txt");
+        fs::write(&file, "aa").unwrap();
+        let index = FileIndex::default();
+        index.apply_changes(root, &[modified(&file)], |_| true, lengths);
+        assert_eq!(total(&index, root), 0);
+        index.ensure(root, &1, |_| true, lengths);
+        fs::write(&file, "aaaa").unwrap();
+        index.apply_changes(root, &[modified(&file)], |_| true, lengths);
+        assert_eq!(total(&index, root), 4);
+        fs::remove_file(&file).unwrap();
+        index.apply_changes(root, &[modified(&file)], |_| true, lengths);
+        assert_eq!(total(&index, root), 0);
+    }
+}
@@ -1,7 +1,17 @@
-use crate::models::{FileNode, SearchResult};
+use crate::backup;
+use crate::encoding;
+use crate::error::FsError;
+use crate::history::{self, SnapshotKind};
+use crate::large_file;
+use crate::models::{FileContent, FileNode, FileVersion, LineEnding, SaveOptions};
+use crate::trashcan;
+use crate::walker;
+use crate::workspace;
 use std::fs;
-use std::path::Path;
-use walkdir::WalkDir;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+use xxhash_rust::xxh3::xxh3_64;
 fn get_file_node(path: &Path) -> Option<FileNode> {
     let metadata = fs::metadata(path).ok()?;
     let name = path.file_name()?.to_string_lossy().to_string();
@@ -12,23 +22,43 @@ fn get_file_node(path: &Path) -> Option<FileNode> {
         path: path_str,
         is_dir,
         children: None,
+        ignored: false,
     })
 }
 #[tauri::command]
-pub fn load_project_tree(path: String) -> Option<FileNode> {
-    get_file_node(Path::new(&path))
+pub fn load_project_tree(path: String) -> Result<Option<FileNode>, FsError> {
+    workspace::resolve(&path)?;
+    Ok(get_file_node(Path::new(&path)))
 }
 #[tauri::command]
-pub fn read_dir(path: String) -> Result<Vec<FileNode>, String> {
+pub fn read_dir(
+    path: String,
+    root: Option<String>,
+    show_ignored: Option<bool>,
+) -> Result<Vec<FileNode>, FsError> {
+    let resolved = workspace::resolve(&path)?;
     let path_obj = Path::new(&path);
-    if !path_obj.exists() || !path_obj.is_dir() {
-        return Err("Invalid directory".to_string());
+    if !resolved.is_dir() {
+        return Err("Invalid directory".to_string().into());
     }
+    let root = root
+        .map(PathBuf::from)
+        .filter(|r| path_obj.starts_with(r))
+        .unwrap_or_else(|| walker::find_project_root(path_obj));
+    let rules = walker::rules_for(&root);
+    let show_ignored = show_ignored.unwrap_or(false);
     let mut child_nodes = Vec::new();
-    if let Ok(entries) = fs::read_dir(path_obj) {
+    if let Ok(entries) = fs::read_dir(&resolved) {
         for entry in entries.flatten() {
-            let entry_path = entry.path();
-            if let Some(node) = get_file_node(&entry_path) {
+            let entry_path = path_obj.join(entry.file_name());
+            if let Some(mut node) = get_file_node(&entry_path) {
+                if entry.file_name() == ".git" {
+                    continue;
+                }
+                node.ignored = rules.is_ignored(&entry_path, node.is_dir);
+                if node.ignored && !show_ignored {
+                    continue;
+                }
                 child_nodes.push(node);
             }
         }
@@ -36,63 +66,253 @@ pub fn read_dir(path: String) -> Result<Vec<FileNode>, String> {
     child_nodes.sort_by(|a, b| b.is_dir.cmp(&a.is_dir).then(a.name.cmp(&b.name)));
     Ok(child_nodes)
 }
+pub(crate) fn content_hash(bytes: &[u8]) -> String {
+    format!("{:016x}", xxh3_64(bytes))
+}
+pub(crate) fn file_version(bytes: &[u8], metadata: &fs::Metadata) -> FileVersion {
+    let mtime = metadata
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0);
+    FileVersion {
+        mtime,
+        size: metadata.len(),
+        hash: content_hash(bytes),
+    }
+}
+fn persist_atomic(
+    path: &Path,
+    bytes: &[u8],
+    permissions: Option<fs::Permissions>,
+) -> io::Result<()> {
+    let parent = path
+        .parent()
+        .filter(|p| !p.as_os_str().is_empty())
+        .unwrap_or_else(|| Path::new("."));
+    let mut temp = tempfile::Builder::new()
+        .prefix(".mide-save-")
+        .tempfile_in(parent)?;
+    temp.write_all(bytes)?;
+    temp.as_file().sync_all()?;
+    if let Some(permissions) = permissions {
+        fs::set_permissions(temp.path(), permissions)?;
+    }
+    temp.persist(path).map_err(|e| e.error)?;
+    Ok(())
+}
+// For user files: keeps the mode of the file being replaced, and new files get the usual 0644.
+pub(crate) fn write_atomic(path: &Path, bytes: &[u8]) -> io::Result<()> {
+    let permissions = fs::metadata(path).map(|m| m.permissions()).ok();
+    #[cfg(unix)]
+    let permissions = permissions.or_else(|| {
+        use std::os::unix::fs::PermissionsExt;
+        Some(fs::Permissions::from_mode(0o644))
+    });
+    persist_atomic(path, bytes, permissions)
+}
+// For the app's own data, which can hold file contents or terminal output: always owner-only (0600).
+pub(crate) fn write_private(path: &Path, bytes: &[u8]) -> io::Result<()> {
+    persist_atomic(path, bytes, None)
+}
+pub(crate) fn read_with_version(path: &Path) -> Result<FileContent, FsError> {
+    let bytes = fs::read(path)?;
+    let metadata = fs::metadata(path)?;
+    let version = file_version(&bytes, &metadata);
+    let decoded = encoding::decode(&bytes);
+    Ok(FileContent {
+        line_ending: encoding::detect_line_ending(&decoded.content),
+        encoding: decoded.encoding.name().to_string(),
+        has_bom: decoded.has_bom,
+        is_binary: decoded.is_binary,
+        content: decoded.content,
+        version,
+    })
+}
 #[tauri::command]
-pub fn read_file_content(path: String) -> Result<String, String> {
-    fs::read_to_string(&path).map_err(|e| e.to_string())
+pub fn read_file_content(path: String) -> Result<FileContent, FsError> {
+    let resolved = workspace::resolve(&path)?;
+    large_file::ensure_full_read_allowed(&resolved)?;
+    read_with_version(&resolved)
 }
 #[tauri::command]
-pub fn save_file_content(path: String, content: String) -> Result<(), String> {
-    fs::write(&path, content).map_err(|e| e.to_string())
+pub fn save_file_content(
+    path: String,
+    content: String,
+    options: Option<SaveOptions>,
+) -> Result<FileVersion, FsError> {
+    let options = options.unwrap_or_default();
+    let resolved = workspace::resolve(&path)?;
+    let path_obj = resolved.as_path();
+    let current = if path_obj.exists() {
+        Some(read_with_version(path_obj)?)
+    } else {
+        None
+    };
+    // Only the content identity matters for conflicts; a touched but unchanged file is safe to overwrite.
+    match (&options.expected_version, &current) {
+        (Some(expected), Some(current))
+            if current.version.hash != expected.hash || current.version.size != expected.size =>
+        {
+            return Err(FsError::Stale {
+                path,
+                current: current.clone(),
+            });
+        }
+        // The editor read a file that is gone now, so recreating it is the user's call.
+        (Some(_), None) => return Err(FsError::Deleted { path }),
+        _ => {}
+    }
+    // Binary files are decoded lossily, so writing the editor text back would corrupt them.
+    if current.as_ref().is_some_and(|c| c.is_binary) && !options.force.unwrap_or(false) {
+        return Err(FsError::Binary { path });
+    }
+    let on_disk = current.as_ref().filter(|c| !c.is_binary);
+    let target_encoding = match (&options.encoding, on_disk) {
+        (Some(label), _) => encoding::lookup(label)?,
+        (None, Some(c)) => encoding::lookup(&c.encoding)?,
+        (None, None) => encoding_rs::UTF_8,
+    };
+    let with_bom = options
+        .bom
+        .unwrap_or_else(|| on_disk.map(|c| c.has_bom).unwrap_or(false));
+    let line_ending = options
+        .line_ending
+        .or_else(|| on_disk.map(|c| c.line_ending))
+        .unwrap_or(LineEnding::Mixed);
+    let text = encoding::apply_line_ending(&content, line_ending);
+    let bytes = encoding::encode(&text, target_encoding, with_bom)?;
+    if let Some(current) = &current {
+        history::capture_original(path_obj, &current.version.hash);
+    }
+    write_atomic(path_obj, &bytes)?;
+    history::record(path_obj, &bytes, SnapshotKind::Save);
+    backup::clear(path_obj);
+    let metadata = fs::metadata(path_obj)?;
+    Ok(file_version(&bytes, &metadata))
 }
 #[tauri::command]
-pub fn create_file(path: String) -> Result<(), String> {
-    if Path::new(&path).exists() {
-        return Err("File already exists".to_string());
+pub fn create_file(path: String) -> Result<(), FsError> {
+    let resolved = workspace::resolve(&path)?;
+    if resolved.exists() {
+        return Err("File already exists".to_string().into());
     }
-    fs::write(&path, "").map_err(|e| e.to_string())
+    Ok(fs::write(&resolved, "")?)
 }
 #[tauri::command]
-pub fn create_directory(path: String) -> Result<(), String> {
-    if Path::new(&path).exists() {
-        return Err("Directory already exists".to_string());
+pub fn create_directory(path: String) -> Result<(), FsError> {
+    let resolved = workspace::resolve(&path)?;
+    if resolved.exists() {
+        return Err("Directory already exists".to_string().into());
     }
-    fs::create_dir_all(&path).map_err(|e| e.to_string())
+    Ok(fs::create_dir_all(&resolved)?)
 }
 #[tauri::command]
-pub fn delete_item(path: String) -> Result<(), String> {
-    let path_obj = Path::new(&path);
-    if path_obj.is_dir() {
-        fs::remove_dir_all(path).map_err(|e| e.to_string())
+pub fn delete_item(path: String, permanent: Option<bool>) -> Result<(), FsError> {
+    let resolved = workspace::resolve_entry(&path)?;
+    if workspace::is_root(&resolved) {
+        return Err("Cannot delete an open workspace folder".to_string().into());
+    }
+    if !permanent.unwrap_or(false) {
+        Ok(trashcan::move_to_trash(&resolved)?)
+    } else if fs::symlink_metadata(&resolved)?.is_dir() {
+        Ok(fs::remove_dir_all(&resolved)?)
     } else {
-        fs::remove_file(path).map_err(|e| e.to_string())
+        Ok(fs::remove_file(&resolved)?)
     }
 }
 #[tauri::command]
-pub fn rename_item(old_path: String, new_path: String) -> Result<(), String> {
-    fs::rename(old_path, new_path).map_err(|e| e.to_string())
+pub fn rename_item(old_path: String, new_path: String) -> Result<(), FsError> {
+    let from = workspace::resolve_entry(&old_path)?;
+    let to = workspace::resolve_entry(&new_path)?;
+    if workspace::is_root(&from) {
+        return Err("Cannot rename an open workspace folder".to_string().into());
+    }
+    Ok(fs::rename(from, to)?)
 }
-#[tauri::command]
-pub fn search_in_files(path: String, query: String) -> Result<Vec<SearchResult>, String> {
-    let mut results = Vec::new();
-    let query = query.to_lowercase();
-    for entry in WalkDir::new(&path).into_iter().filter_map(|e| e.ok()) {
-        let path = entry.path();
-        if path.is_file() {
-            if let Ok(content) = fs::read_to_string(path) {
-                for (i, line) in content.lines().enumerate() {
-                    if line.to_lowercase().contains(&query) {
-                        results.push(SearchResult {
-                            file: path.to_string_lossy().to_string(),
-                            line: i + 1,
-                            content: line.trim().to_string(),
-                        });
-                        if results.len() > 1000 {
-                            return Ok(results);
-                        }
-                    }
-                }
-            }
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+    fn mode(path: &Path) -> u32 {
+        fs::metadata(path).unwrap().permissions().mode() & 0o777
+    }
+    #[test]
+    fn user_files_keep_their_mode() {
+        let dir = tempfile::tempdir().unwrap();
+        let script = dir.path().join("run.sh");
+        fs::write(&script, "#!/bin/sh\n").unwrap();
+        fs::set_permissions(&script, fs::Permissions::from_mode(0o755)).unwrap();
+        write_atomic(&script, b"#!/bin/sh\necho hi\n").unwrap();
+        assert_eq!(mode(&script), 0o755);
+        let created = dir.path().join("new.txt");
+        write_atomic(&created, b"new").unwrap();
+        assert_eq!(mode(&created), 0o644);
+    }
+    fn version_of(path: &Path) -> FileVersion {
+        read_with_version(path).unwrap().version
+    }
+    fn save(
+        path: &Path,
+        content: &str,
+        expected: Option<FileVersion>,
+    ) -> Result<FileVersion, FsError> {
+        let options = SaveOptions {
+            expected_version: expected,
+            ..Default::default()
+        };
+        save_file_content(
+            path.to_string_lossy().to_string(),
+            content.to_string(),
+            Some(options),
+        )
+    }
+    #[test]
+    fn saves_refuse_files_changed_on_disk() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = crate::workspace::open_for_test(dir.path());
+        let file = root.join("notes.txt");
+        let first = save(&file, "one", None).unwrap();
+        assert_eq!(first, version_of(&file));
+        let second = save(&file, "two", Some(first.clone())).unwrap();
+        assert_eq!(fs::read_to_string(&file).unwrap(), "two");
+        fs::write(&file, "from git").unwrap();
+        match save(&file, "three", Some(second.clone())) {
+            Err(FsError::Stale { current, .. }) => assert_eq!(current.content, "from git"),
+            other => panic!("expected a stale error, got {:?}", other.map(|_| ())),
         }
+        assert_eq!(fs::read_to_string(&file).unwrap(), "from git");
+        // Rewriting the same bytes only touches the mtime, which is not a conflict.
+        fs::write(&file, "two").unwrap();
+        let third = save(&file, "three", Some(second)).unwrap();
+        assert_eq!(fs::read_to_string(&file).unwrap(), "three");
+        fs::remove_file(&file).unwrap();
+        assert!(matches!(
+            save(&file, "four", Some(third)),
+            Err(FsError::Deleted { .. })
+        ));
+        assert!(!file.exists());
     }
-    Ok(results)
-}
\ No newline at end of file
+    #[test]
+    fn saves_keep_the_encoding_and_line_endings_on_disk() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = crate::workspace::open_for_test(dir.path());
+        let file = root.join("crlf.txt");
+        fs::write(&file, b"\xEF\xBB\xBFa\r\nb\r\n").unwrap();
+        let version = version_of(&file);
+        save(&file, "a\nb\nc\n", Some(version)).unwrap();
+        assert_eq!(fs::read(&file).unwrap(), b"\xEF\xBB\xBFa\r\nb\r\nc\r\n");
+    }
+    #[test]
+    fn app_data_is_owner_only() {
+        let dir = tempfile::tempdir().unwrap();
+        let data = dir.path().join("sessions.json");
+        write_private(&data, b"[]").unwrap();
+        assert_eq!(mode(&data), 0o600);
+        fs::set_permissions(&data, fs::Permissions::from_mode(0o644)).unwrap();
+        write_private(&data, b"[{}]").unwrap();
+        assert_eq!(mode(&data), 0o600);
+        assert_eq!(fs::read(&data).unwrap(), b"[{}]");
+    }
+}
@@ -1,3 +1,5 @@
+use crate::error::FsError;
+use crate::workspace;
 use std::process::Command;
 #[tauri::command]
 pub fn format_code(code: String, language: String) -> Result<String, String> {
@@ -12,17 +14,21 @@ pub fn format_code(code: String, language: String) -> Result<String, String> {
     }
 }
 #[tauri::command]
-pub fn format_file(path: String) -> Result<String, String> {
-    let ext = path.split('.').last().unwrap_or("");
-    match ext {
+pub fn format_file(path: String) -> Result<String, FsError> {
+    // Formatters run on the resolved path, the one the sandbox check approved.
+    let resolved = workspace::resolve(&path)?;
+    let resolved = resolved.to_string_lossy();
+    let ext = resolved.rsplit('.').next().unwrap_or("");
+    let formatted = match ext {
         "js" | "jsx" | "ts" | "tsx" | "json" | "html" | "css" | "scss" | "vue" | "svelte" => {
-            format_file_with_prettier(&path)
+            format_file_with_prettier(&resolved)
         }
-        "rs" => format_file_with_rustfmt(&path),
-        "py" => format_file_with_black(&path),
-        "go" => format_file_with_gofmt(&path),
+        "rs" => format_file_with_rustfmt(&resolved),
+        "py" => format_file_with_black(&resolved),
+        "go" => format_file_with_gofmt(&resolved),
         _ => Err(format!("No formatter available for .{}", ext)),
-    }
+    }?;
+    Ok(formatted)
 }
 fn format_with_prettier(code: &str, language: &str) -> Result<String, String> {
     let parser = match language {
@@ -34,7 +40,7 @@ fn format_with_prettier(code: &str, language: &str) -> Result<String, String> {
         _ => "babel",
     };
     let output = Command::new("npx")
-        .args(&[
+        .args([
             "prettier",
             "--parser",
             parser,
@@ -62,7 +68,7 @@ fn format_with_prettier(code: &str, language: &str) -> Result<String, String> {
 }
 fn format_file_with_prettier(path: &str) -> Result<String, String> {
     let output = Command::new("npx")
-        .args(&["prettier", "--write", path])
+        .args(["prettier", "--write", path])
         .output()
         .map_err(|e| format!("Prettier not found: {}", e))?;
     if output.status.success() {
@@ -106,7 +112,7 @@ fn format_file_with_rustfmt(path: &str) -> Result<String, String> {
 }
 fn format_with_black(code: &str) -> Result<String, String> {
     let output = Command::new("black")
-        .args(&["-", "--quiet"])
+        .args(["-", "--quiet"])
         .stdin(std::process::Stdio::piped())
         .stdout(std::process::Stdio::piped())
         .stderr(std::process::Stdio::piped())
@@ -158,7 +164,7 @@ fn format_with_gofmt(code: &str) -> Result<String, String> {
 }
 fn format_file_with_gofmt(path: &str) -> Result<String, String> {
     let output = Command::new("gofmt")
-        .args(&["-w", path])
+        .args(["-w", path])
         .output()
         .map_err(|e| format!("gofmt not found: {}", e))?;
     if output.status.success() {
@@ -0,0 +1,105 @@
+use crate::error::FsError;
+use crate::filesystem::{content_hash, write_private};
+use crate::storage;
+use crate::workspace;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+const MAX_TRACKED_FILES: usize = 500;
+const HOUR_MS: i64 = 60 * 60 * 1000;
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default)]
+pub struct Usage {
+    pub count: u32,
+    pub last_opened: i64,
+}
+lazy_static::lazy_static! {
+    static ref USAGE: Mutex<HashMap<PathBuf, HashMap<String, Usage>>> = Mutex::new(HashMap::new());
+}
+fn usage_path(root: &Path) -> PathBuf {
+    storage::data_dir().join("frecency").join(format!(
+        "{}.json",
+        content_hash(root.to_string_lossy().as_bytes())
+    ))
+}
+fn load(root: &Path) -> HashMap<String, Usage> {
+    fs::read(usage_path(root))
+        .ok()
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        .unwrap_or_default()
+}
+pub fn boost(usage: &Usage) -> i64 {
+    let age = Utc::now().timestamp_millis() - usage.last_opened;
+    let recency = if age < HOUR_MS {
+        40
+    } else if age < 24 * HOUR_MS {
+        20
+    } else if age < 7 * 24 * HOUR_MS {
+        10
+    } else {
+        0
+    };
+    usage.count.min(20) as i64 * 3 + recency
+}
+pub fn snapshot(root: &Path) -> HashMap<String, Usage> {
+    USAGE
+        .lock()
+        .unwrap()
+        .entry(root.to_path_buf())
+        .or_insert_with(|| load(root))
+        .clone()
+}
+#[tauri::command]
+pub fn record_file_open(path: String) -> Result<(), FsError> {
+    let resolved = workspace::resolve(&path)?;
+    let root = match workspace::root_for(&resolved) {
+        Some(root) => root,
+        None => return Ok(()),
+    };
+    let relative = resolved
+        .strip_prefix(&root)
+        .map(|r| r.to_string_lossy().replace('\\', "/"))
+        .unwrap_or_default();
+    let mut all = USAGE.lock().unwrap();
+    let usage = all.entry(root.clone()).or_insert_with(|| load(&root));
+    let entry = usage.entry(relative).or_default();
+    entry.count += 1;
+    entry.last_opened = Utc::now().timestamp_millis();
+    if usage.len() > MAX_TRACKED_FILES {
+        let mut by_age: Vec<(String, i64)> = usage
+            .iter()
+            .map(|(path, u)| (path.clone(), u.last_opened))
+            .collect();
+        by_age.sort_by_key(|(_, last_opened)| std::cmp::Reverse(*last_opened));
+        for (path, _) in by_age.into_iter().skip(MAX_TRACKED_FILES) {
+            usage.remove(&path);
+        }
+    }
+    let target = usage_path(&root);
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let bytes = serde_json::to_vec(&*usage).map_err(|e| e.to_string())?;
+    write_private(&target, &bytes)?;
+    Ok(())
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn boosts_favour_recent_and_frequent_files() {
+        let now = Utc::now().timestamp_millis();
+        let opened = |count, hours_ago: i64| Usage {
+            count,
+            last_opened: now - hours_ago * HOUR_MS,
+        };
+        assert_eq!(boost(&opened(1, 0)), 43);
+        assert_eq!(boost(&opened(1, 2)), 23);
+        assert_eq!(boost(&opened(1, 48)), 13);
+        assert_eq!(boost(&opened(1, 24 * 30)), 3);
+        // Counts are capped so one heavily used file cannot bury every match.
+        assert_eq!(boost(&opened(1000, 24 * 30)), 60);
+    }
+}
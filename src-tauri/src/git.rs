@@ -1,4 +1,5 @@
 use crate::models::{GitBranch, GitCommit, GitDiff, GitFile, GitRemote, GitStatus};
+use std::collections::HashMap;
 #[cfg(target_os = "windows")]
 use std::os::windows::process::CommandExt;
 use std::process::Command;
@@ -314,6 +315,51 @@ pub fn git_rebase_continue(cwd: String) -> Result<String, String> {
 pub fn git_cherry_pick(cwd: String, commit: String) -> Result<String, String> {
     execute_git_command(&["cherry-pick", &commit], &cwd)
 }
+pub(crate) struct BlameLine {
+    pub(crate) commit: String,
+    pub(crate) author: String,
+    pub(crate) author_time: i64,
+}
+// Maps final line numbers to the commit that last touched them; uncommitted lines are left out.
+pub(crate) fn blame_lines(cwd: &str, file: &str) -> Result<HashMap<usize, BlameLine>, String> {
+    let output = execute_git_command(&["blame", "--line-porcelain", "--", file], cwd)?;
+    let mut lines = HashMap::new();
+    let mut current: Option<(usize, String)> = None;
+    let mut author = String::new();
+    let mut author_time = 0;
+    for line in output.lines() {
+        if line.starts_with('\t') {
+            if let Some((number, commit)) = current.take() {
+                if commit.chars().any(|c| c != '0') {
+                    lines.insert(
+                        number,
+                        BlameLine {
+                            commit,
+                            author: std::mem::take(&mut author),
+                            author_time,
+                        },
+                    );
+                }
+            }
+        } else if let Some(value) = line.strip_prefix("author ") {
+            author = value.to_string();
+        } else if let Some(value) = line.strip_prefix("author-time ") {
+            author_time = value.parse::<i64>().unwrap_or(0) * 1000;
+        } else if current.is_none() {
+            let mut parts = line.split(' ');
+            if let (Some(commit), Some(_), Some(number)) =
+                (parts.next(), parts.next(), parts.next())
+            {
+                if commit.len() >= 40 && commit.chars().all(|c| c.is_ascii_hexdigit()) {
+                    if let Ok(number) = number.parse() {
+                        current = Some((number, commit.to_string()));
+                    }
+                }
+            }
+        }
+    }
+    Ok(lines)
+}
 #[tauri::command]
 pub fn git_blame(cwd: String, file: String) -> Result<String, String> {
     execute_git_command(&["blame", &file], &cwd)
@@ -469,4 +515,4 @@ pub fn git_search_commits(
         }
     }
     Ok(commits)
-}
\ No newline at end of file
+}
@@ -0,0 +1,398 @@
+use crate::diff::{self, FileDiff};
+use crate::encoding;
+use crate::error::FsError;
+use crate::filesystem::{content_hash, file_version, write_atomic, write_private};
+use crate::models::FileVersion;
+use crate::project_settings;
+use crate::storage;
+use crate::workspace;
+use chrono::Utc;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::thread;
+const DEFAULT_MAX_ENTRIES: usize = 100;
+const DEFAULT_MAX_AGE_DAYS: u64 = 30;
+const DEFAULT_MAX_SIZE_MB: u64 = 256;
+const INDEX_FILE: &str = "index.json";
+lazy_static::lazy_static! {
+    static ref HISTORY_LOCK: Mutex<()> = Mutex::new(());
+}
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SnapshotKind {
+    Original,
+    Save,
+    Restore,
+}
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct HistoryEntry {
+    pub id: String,
+    pub timestamp: i64,
+    pub kind: SnapshotKind,
+    pub size: u64,
+    pub stored_size: u64,
+    pub hash: String,
+}
+#[derive(Serialize, Debug, Clone)]
+pub struct HistorySnapshot {
+    pub entry: HistoryEntry,
+    pub content: String,
+    pub encoding: String,
+    pub is_binary: bool,
+}
+#[derive(Serialize, Deserialize, Debug, Default)]
+struct HistoryIndex {
+    path: String,
+    // Oldest first; the last entry is the most recent snapshot.
+    entries: Vec<HistoryEntry>,
+}
+#[derive(Clone, Copy)]
+struct Retention {
+    max_entries: usize,
+    max_age_ms: i64,
+    max_bytes: u64,
+}
+impl Retention {
+    fn for_root(root: &Path) -> Self {
+        let settings = project_settings::load(root);
+        let days = settings
+            .history_max_age_days
+            .unwrap_or(DEFAULT_MAX_AGE_DAYS);
+        Retention {
+            max_entries: settings
+                .history_max_entries
+                .unwrap_or(DEFAULT_MAX_ENTRIES)
+                .max(1),
+            // Both come straight from the settings file, so huge values saturate instead of overflowing.
+            max_age_ms: i64::try_from(days.saturating_mul(24 * 60 * 60 * 1000)).unwrap_or(i64::MAX),
+            max_bytes: settings
+                .history_max_size_mb
+                .unwrap_or(DEFAULT_MAX_SIZE_MB)
+                .saturating_mul(1024 * 1024),
+        }
+    }
+}
+fn workspace_dir(root: &Path) -> PathBuf {
+    storage::data_dir()
+        .join("history")
+        .join(content_hash(root.to_string_lossy().as_bytes()))
+}
+fn location(resolved: &Path) -> Option<(PathBuf, PathBuf)> {
+    let root = workspace::root_for(resolved)?;
+    let dir = workspace_dir(&root).join(content_hash(resolved.to_string_lossy().as_bytes()));
+    Some((root, dir))
+}
+fn load_index(dir: &Path) -> HistoryIndex {
+    fs::read(dir.join(INDEX_FILE))
+        .ok()
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        .unwrap_or_default()
+}
+fn save_index(dir: &Path, index: &HistoryIndex) -> io::Result<()> {
+    let bytes = serde_json::to_vec(index).map_err(io::Error::other)?;
+    write_private(&dir.join(INDEX_FILE), &bytes)
+}
+fn snapshot_path(dir: &Path, id: &str) -> PathBuf {
+    dir.join(format!("{}.gz", id))
+}
+fn remove_entries(dir: &Path, index: &mut HistoryIndex, keep: impl Fn(&HistoryEntry) -> bool) {
+    index.entries.retain(|entry| {
+        let kept = keep(entry);
+        if !kept {
+            let _ = fs::remove_file(snapshot_path(dir, &entry.id));
+        }
+        kept
+    });
+}
+fn prune_file(dir: &Path, index: &mut HistoryIndex, retention: Retention) {
+    let cutoff = Utc::now().timestamp_millis() - retention.max_age_ms;
+    let newest = index.entries.last().map(|e| e.id.clone());
+    remove_entries(dir, index, |e| {
+        e.timestamp >= cutoff || Some(&e.id) == newest.as_ref()
+    });
+    let excess = index.entries.len().saturating_sub(retention.max_entries);
+    let dropped: Vec<String> = index.entries[..excess]
+        .iter()
+        .map(|e| e.id.clone())
+        .collect();
+    remove_entries(dir, index, |e| !dropped.contains(&e.id));
+}
+fn prune_workspace(workspace: &Path, max_bytes: u64) {
+    let _guard = HISTORY_LOCK.lock().unwrap();
+    let mut indexes: Vec<(PathBuf, HistoryIndex)> = match fs::read_dir(workspace) {
+        Ok(entries) => entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|dir| dir.is_dir())
+            .map(|dir| {
+                let index = load_index(&dir);
+                (dir, index)
+            })
+            .collect(),
+        Err(_) => return,
+    };
+    let mut total: u64 = indexes
+        .iter()
+        .flat_map(|(_, index)| index.entries.iter())
+        .map(|e| e.stored_size)
+        .sum();
+    if total <= max_bytes {
+        return;
+    }
+    // Evict the oldest snapshots first, but always keep the latest one of each file.
+    let mut candidates: Vec<(i64, usize, String, u64)> = indexes
+        .iter()
+        .enumerate()
+        .flat_map(|(i, (_, index))| {
+            let keep = index.entries.len().saturating_sub(1);
+            index.entries[..keep]
+                .iter()
+                .map(move |e| (e.timestamp, i, e.id.clone(), e.stored_size))
+        })
+        .collect();
+    candidates.sort();
+    let mut evicted: Vec<Vec<String>> = vec![Vec::new(); indexes.len()];
+    for (_, i, id, stored_size) in candidates {
+        if total <= max_bytes {
+            break;
+        }
+        total -= stored_size;
+        evicted[i].push(id);
+    }
+    for ((dir, index), ids) in indexes.iter_mut().zip(evicted) {
+        if ids.is_empty() {
+            continue;
+        }
+        remove_entries(dir, index, |e| !ids.contains(&e.id));
+        if let Err(e) = save_index(dir, index) {
+            eprintln!("Failed to update local history index {:?}: {}", dir, e);
+        }
+    }
+}
+fn try_record(resolved: &Path, bytes: &[u8], kind: SnapshotKind) -> io::Result<()> {
+    let (root, dir) = match location(resolved) {
+        Some(location) => location,
+        None => return Ok(()),
+    };
+    let retention = Retention::for_root(&root);
+    {
+        let _guard = HISTORY_LOCK.lock().unwrap();
+        let mut index = load_index(&dir);
+        let hash = content_hash(bytes);
+        if index.entries.last().is_some_and(|e| e.hash == hash) {
+            return Ok(());
+        }
+        fs::create_dir_all(&dir)?;
+        let timestamp = Utc::now().timestamp_millis();
+        let id = format!("{}-{}", timestamp, &hash[..8]);
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(bytes)?;
+        let compressed = encoder.finish()?;
+        write_private(&snapshot_path(&dir, &id), &compressed)?;
+        index.path = resolved.to_string_lossy().to_string();
+        index.entries.push(HistoryEntry {
+            id,
+            timestamp,
+            kind,
+            size: bytes.len() as u64,
+            stored_size: compressed.len() as u64,
+            hash,
+        });
+        prune_file(&dir, &mut index, retention);
+        save_index(&dir, &index)?;
+    }
+    let workspace = workspace_dir(&root);
+    thread::spawn(move || prune_workspace(&workspace, retention.max_bytes));
+    Ok(())
+}
+pub fn record(resolved: &Path, bytes: &[u8], kind: SnapshotKind) {
+    if let Err(e) = try_record(resolved, bytes, kind) {
+        eprintln!("Failed to record local history for {:?}: {}", resolved, e);
+    }
+}
+pub fn capture_original(resolved: &Path, current_hash: &str) {
+    // Keeps content written outside the editor so the next save does not lose it.
+    let known = location(resolved)
+        .map(|(_, dir)| load_index(&dir))
+        .and_then(|index| index.entries.last().map(|e| e.hash == current_hash))
+        .unwrap_or(false);
+    if !known {
+        if let Ok(bytes) = fs::read(resolved) {
+            record(resolved, &bytes, SnapshotKind::Original);
+        }
+    }
+}
+fn read_snapshot(resolved: &Path, id: &str) -> Result<(HistoryEntry, Vec<u8>), FsError> {
+    let (_, dir) = location(resolved)
+        .ok_or_else(|| "Local history is only kept for files inside a workspace".to_string())?;
+    let entry = load_index(&dir)
+        .entries
+        .into_iter()
+        .find(|e| e.id == id)
+        .ok_or_else(|| format!("Snapshot {} not found", id))?;
+    let mut bytes = Vec::new();
+    GzDecoder::new(File::open(snapshot_path(&dir, id))?).read_to_end(&mut bytes)?;
+    Ok((entry, bytes))
+}
+#[tauri::command]
+pub fn list_file_history(path: String) -> Result<Vec<HistoryEntry>, FsError> {
+    let resolved = workspace::resolve(&path)?;
+    let mut entries = location(&resolved)
+        .map(|(_, dir)| load_index(&dir).entries)
+        .unwrap_or_default();
+    entries.reverse();
+    Ok(entries)
+}
+#[tauri::command]
+pub fn read_history_snapshot(path: String, id: String) -> Result<HistorySnapshot, FsError> {
+    let resolved = workspace::resolve(&path)?;
+    let (entry, bytes) = read_snapshot(&resolved, &id)?;
+    let decoded = encoding::decode(&bytes);
+    Ok(HistorySnapshot {
+        entry,
+        content: decoded.content,
+        encoding: decoded.encoding.name().to_string(),
+        is_binary: decoded.is_binary,
+    })
+}
+#[tauri::command]
+pub fn diff_history_snapshot(path: String, id: String) -> Result<FileDiff, FsError> {
+    let resolved = workspace::resolve(&path)?;
+    let (_, old_bytes) = read_snapshot(&resolved, &id)?;
+    let new_bytes = if resolved.exists() {
+        fs::read(&resolved)?
+    } else {
+        Vec::new()
+    };
+    let old = encoding::decode(&old_bytes);
+    let new = encoding::decode(&new_bytes);
+    if old.is_binary || new.is_binary {
+        return Err("Cannot diff binary content".to_string().into());
+    }
+    Ok(diff::unified(
+        &old.content,
+        &new.content,
+        &format!("{} ({})", path, id),
+        &path,
+    ))
+}
+#[tauri::command]
+pub fn restore_history_snapshot(path: String, id: String) -> Result<FileVersion, FsError> {
+    let resolved = workspace::resolve(&path)?;
+    let (_, bytes) = read_snapshot(&resolved, &id)?;
+    if let Ok(current) = fs::read(&resolved) {
+        record(&resolved, &current, SnapshotKind::Original);
+    }
+    if let Some(parent) = resolved.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    write_atomic(&resolved, &bytes)?;
+    record(&resolved, &bytes, SnapshotKind::Restore);
+    let metadata = fs::metadata(&resolved)?;
+    Ok(file_version(&bytes, &metadata))
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    fn entry(id: &str, timestamp: i64, stored_size: u64) -> HistoryEntry {
+        HistoryEntry {
+            id: id.to_string(),
+            timestamp,
+            kind: SnapshotKind::Save,
+            size: stored_size,
+            stored_size,
+            hash: id.to_string(),
+        }
+    }
+    fn file_history(dir: &Path, entries: Vec<HistoryEntry>) -> HistoryIndex {
+        fs::create_dir_all(dir).unwrap();
+        for e in &entries {
+            fs::write(snapshot_path(dir, &e.id), "").unwrap();
+        }
+        let index = HistoryIndex {
+            path: String::new(),
+            entries,
+        };
+        save_index(dir, &index).unwrap();
+        index
+    }
+    fn ids(index: &HistoryIndex) -> Vec<&str> {
+        index.entries.iter().map(|e| e.id.as_str()).collect()
+    }
+    #[test]
+    fn saves_can_be_listed_diffed_and_restored() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = workspace::open_for_test(dir.path());
+        let file = root.join("a.txt");
+        let path = file.to_string_lossy().to_string();
+        fs::write(&file, "first\n").unwrap();
+        record(&file, b"first\n", SnapshotKind::Save);
+        record(&file, b"first\n", SnapshotKind::Save);
+        fs::write(&file, "second\n").unwrap();
+        record(&file, b"second\n", SnapshotKind::Save);
+        let entries = list_file_history(path.clone()).unwrap();
+        assert_eq!(entries.len(), 2);
+        let oldest = entries[1].id.clone();
+        let snapshot = read_history_snapshot(path.clone(), oldest.clone()).unwrap();
+        assert_eq!(snapshot.content, "first\n");
+        let diff = diff_history_snapshot(path.clone(), oldest.clone()).unwrap();
+        assert_eq!((diff.insertions, diff.deletions), (1, 1));
+        restore_history_snapshot(path.clone(), oldest).unwrap();
+        assert_eq!(fs::read_to_string(&file).unwrap(), "first\n");
+        let kinds: Vec<SnapshotKind> = list_file_history(path)
+            .unwrap()
+            .iter()
+            .map(|e| e.kind)
+            .collect();
+        assert_eq!(kinds[0], SnapshotKind::Restore);
+    }
+    #[test]
+    fn pruning_a_file_keeps_its_newest_snapshots() {
+        let dir = tempfile::tempdir().unwrap();
+        let now = Utc::now().timestamp_millis();
+        let mut index = file_history(
+            dir.path(),
+            vec![
+                entry("old", 0, 1),
+                entry("a", now - 3, 1),
+                entry("b", now - 2, 1),
+                entry("c", now - 1, 1),
+            ],
+        );
+        let retention = Retention {
+            max_entries: 2,
+            max_age_ms: 60_000,
+            max_bytes: u64::MAX,
+        };
+        prune_file(dir.path(), &mut index, retention);
+        assert_eq!(ids(&index), vec!["b", "c"]);
+        assert!(!snapshot_path(dir.path(), "old").exists());
+        assert!(!snapshot_path(dir.path(), "a").exists());
+        let mut stale = file_history(dir.path(), vec![entry("x", 0, 1), entry("y", 1, 1)]);
+        prune_file(dir.path(), &mut stale, retention);
+        assert_eq!(ids(&stale), vec!["y"]);
+    }
+    #[test]
+    fn workspace_budget_evicts_the_oldest_but_never_a_latest_snapshot() {
+        let dir = tempfile::tempdir().unwrap();
+        let a = dir.path().join("a");
+        let b = dir.path().join("b");
+        file_history(&a, vec![entry("a1", 1, 10), entry("a2", 4, 10)]);
+        file_history(
+            &b,
+            vec![entry("b1", 2, 10), entry("b2", 3, 10), entry("b3", 5, 10)],
+        );
+        prune_workspace(dir.path(), 30);
+        assert_eq!(ids(&load_index(&a)), vec!["a2"]);
+        assert_eq!(ids(&load_index(&b)), vec!["b2", "b3"]);
+        prune_workspace(dir.path(), 0);
+        assert_eq!(ids(&load_index(&a)), vec!["a2"]);
+        assert_eq!(ids(&load_index(&b)), vec!["b3"]);
+    }
+}
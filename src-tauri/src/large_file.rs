@@ -0,0 +1,384 @@
+use crate::error::FsError;
+use crate::workspace;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::SystemTime;
+pub const FULL_READ_LIMIT: u64 = 50 * 1024 * 1024;
+const MAX_CHUNK_LEN: u64 = 4 * 1024 * 1024;
+// Enough for one whole UTF-8 character, so every chunk moves the offset forward.
+const MIN_CHUNK_LEN: u64 = 4;
+const MAX_LINES_PER_READ: usize = 10_000;
+const LINE_INDEX_STRIDE: usize = 1024;
+#[derive(Debug, Default)]
+struct LineIndex {
+    size: u64,
+    modified: Option<SystemTime>,
+    checkpoints: Vec<u64>,
+    newlines: usize,
+    lines_indexed: usize,
+    bytes_indexed: u64,
+    last_byte: Option<u8>,
+    complete: bool,
+    building: bool,
+    cancelled: bool,
+}
+lazy_static::lazy_static! {
+    static ref LINE_INDEXES: Mutex<HashMap<PathBuf, Arc<Mutex<LineIndex>>>> = Mutex::new(HashMap::new());
+}
+#[derive(Serialize, Debug, Clone)]
+pub struct LargeFileInfo {
+    pub path: String,
+    pub size: u64,
+    pub read_only: bool,
+}
+#[derive(Serialize, Debug, Clone)]
+pub struct FileChunk {
+    pub offset: u64,
+    pub next_offset: u64,
+    pub total_size: u64,
+    pub content: String,
+    pub eof: bool,
+}
+#[derive(Serialize, Debug, Clone)]
+pub struct LineRange {
+    pub start_line: usize,
+    pub lines: Vec<String>,
+    pub eof: bool,
+}
+#[derive(Serialize, Debug, Clone)]
+pub struct LineIndexStatus {
+    pub lines_indexed: usize,
+    pub bytes_indexed: u64,
+    pub total_size: u64,
+    pub complete: bool,
+}
+pub(crate) fn ensure_full_read_allowed(path: &Path) -> Result<(), FsError> {
+    let size = fs::metadata(path)?.len();
+    if size > FULL_READ_LIMIT {
+        return Err(FsError::TooLarge {
+            path: path.to_string_lossy().to_string(),
+            size,
+            limit: FULL_READ_LIMIT,
+        });
+    }
+    Ok(())
+}
+fn utf8_boundary(bytes: &[u8]) -> usize {
+    // Stop before a multi-byte sequence that is cut off by the chunk end.
+    let len = bytes.len();
+    for back in 1..=len.min(3) {
+        let b = bytes[len - back];
+        if b & 0xC0 == 0x80 {
+            continue;
+        }
+        let width = if b >= 0xF0 {
+            4
+        } else if b >= 0xE0 {
+            3
+        } else if b >= 0xC0 {
+            2
+        } else {
+            1
+        };
+        return if width > back { len - back } else { len };
+    }
+    len
+}
+fn spawn_build(path: &Path, index: &Arc<Mutex<LineIndex>>) {
+    let worker_index = index.clone();
+    let worker_path = path.to_path_buf();
+    thread::spawn(move || {
+        if let Err(e) = build_line_index(&worker_path, &worker_index) {
+            eprintln!("Failed to index lines of {:?}: {}", worker_path, e);
+            worker_index.lock().unwrap().building = false;
+        }
+    });
+}
+fn index_for(path: &Path) -> Result<Arc<Mutex<LineIndex>>, FsError> {
+    let metadata = fs::metadata(path)?;
+    let modified = metadata.modified().ok();
+    let mut indexes = LINE_INDEXES.lock().unwrap();
+    if let Some(existing) = indexes.get(path) {
+        let mut index = existing.lock().unwrap();
+        // A cancelled index stopped on a truncated or replaced file, so it always starts over.
+        if !index.cancelled && index.size == metadata.len() && index.modified == modified {
+            return Ok(existing.clone());
+        }
+        // A file that only grew is treated as appended to, so indexing carries on from where it stopped.
+        if !index.cancelled && metadata.len() > index.size {
+            index.size = metadata.len();
+            index.modified = modified;
+            index.complete = false;
+            if !index.building {
+                index.building = true;
+                spawn_build(path, existing);
+            }
+            return Ok(existing.clone());
+        }
+        // Otherwise its offsets are stale; stop the running build before starting over.
+        index.cancelled = true;
+    }
+    let index = Arc::new(Mutex::new(LineIndex {
+        size: metadata.len(),
+        modified,
+        checkpoints: vec![0],
+        building: true,
+        ..Default::default()
+    }));
+    indexes.insert(path.to_path_buf(), index.clone());
+    spawn_build(path, &index);
+    Ok(index)
+}
+fn build_line_index(path: &Path, index: &Arc<Mutex<LineIndex>>) -> std::io::Result<()> {
+    let (mut offset, mut lines) = {
+        let state = index.lock().unwrap();
+        (state.bytes_indexed, state.newlines)
+    };
+    let mut file = File::open(path)?;
+    file.seek(SeekFrom::Start(offset))?;
+    let mut reader = BufReader::with_capacity(1024 * 1024, file);
+    let mut pending = Vec::new();
+    loop {
+        let buf = reader.fill_buf()?;
+        let mut state = index.lock().unwrap();
+        if state.cancelled {
+            return Ok(());
+        }
+        if buf.is_empty() {
+            // EOF before the recorded size means the file was truncated or replaced under the reader.
+            if offset < state.size {
+                state.cancelled = true;
+                state.building = false;
+                return Ok(());
+            }
+            if state.last_byte.is_some_and(|b| b != b'\n') {
+                state.lines_indexed = lines + 1;
+            }
+            state.complete = true;
+            state.building = false;
+            return Ok(());
+        }
+        for (i, b) in buf.iter().enumerate() {
+            if *b == b'\n' {
+                lines += 1;
+                if lines % LINE_INDEX_STRIDE == 0 {
+                    pending.push(offset + i as u64 + 1);
+                }
+            }
+        }
+        let consumed = buf.len();
+        offset += consumed as u64;
+        state.last_byte = buf.last().copied();
+        state.checkpoints.append(&mut pending);
+        state.newlines = lines;
+        state.lines_indexed = lines;
+        state.bytes_indexed = offset;
+        drop(state);
+        reader.consume(consumed);
+    }
+}
+#[tauri::command]
+pub fn open_large_file(path: String) -> Result<LargeFileInfo, FsError> {
+    let resolved = workspace::resolve(&path)?;
+    let size = fs::metadata(&resolved)?.len();
+    index_for(&resolved)?;
+    Ok(LargeFileInfo {
+        path,
+        size,
+        read_only: true,
+    })
+}
+// Drops the line index of a file whose view was closed, stopping its build if one is running.
+#[tauri::command]
+pub fn close_large_file(path: String) -> Result<(), FsError> {
+    let resolved = workspace::resolve(&path)?;
+    if let Some(index) = LINE_INDEXES.lock().unwrap().remove(&resolved) {
+        index.lock().unwrap().cancelled = true;
+    }
+    Ok(())
+}
+#[tauri::command]
+pub fn read_file_range(path: String, offset: u64, length: u64) -> Result<FileChunk, FsError> {
+    read_range(&workspace::resolve(&path)?, offset, length)
+}
+fn read_range(path: &Path, offset: u64, length: u64) -> Result<FileChunk, FsError> {
+    let mut file = File::open(path)?;
+    let total_size = file.metadata()?.len();
+    let offset = offset.min(total_size);
+    let length = length
+        .clamp(MIN_CHUNK_LEN, MAX_CHUNK_LEN)
+        .min(total_size - offset);
+    file.seek(SeekFrom::Start(offset))?;
+    let mut bytes = Vec::with_capacity(length as usize);
+    file.take(length).read_to_end(&mut bytes)?;
+    let end = if offset + length < total_size {
+        utf8_boundary(&bytes)
+    } else {
+        bytes.len()
+    };
+    let next_offset = offset + end as u64;
+    Ok(FileChunk {
+        offset,
+        next_offset,
+        total_size,
+        content: String::from_utf8_lossy(&bytes[..end]).to_string(),
+        eof: next_offset >= total_size,
+    })
+}
+#[tauri::command]
+pub async fn read_file_lines(
+    path: String,
+    start_line: usize,
+    count: usize,
+) -> Result<LineRange, FsError> {
+    let resolved = workspace::resolve(&path)?;
+    // Until the index reaches start_line this reads on from the last checkpoint, so keep it off the async workers.
+    tauri::async_runtime::spawn_blocking(move || read_lines(&resolved, start_line, count))
+        .await
+        .map_err(|e| e.to_string())?
+}
+fn read_lines(resolved: &Path, start_line: usize, count: usize) -> Result<LineRange, FsError> {
+    let index = index_for(resolved)?;
+    let (checkpoint_line, checkpoint_offset) = {
+        let state = index.lock().unwrap();
+        let slot = (start_line / LINE_INDEX_STRIDE).min(state.checkpoints.len() - 1);
+        (slot * LINE_INDEX_STRIDE, state.checkpoints[slot])
+    };
+    let mut file = File::open(resolved)?;
+    file.seek(SeekFrom::Start(checkpoint_offset))?;
+    let mut reader = BufReader::new(file);
+    let mut line = Vec::new();
+    let mut current = checkpoint_line;
+    let mut lines = Vec::new();
+    let count = count.min(MAX_LINES_PER_READ);
+    let mut eof = false;
+    while lines.len() < count {
+        line.clear();
+        if reader.read_until(b'\n', &mut line)? == 0 {
+            eof = true;
+            break;
+        }
+        if current >= start_line {
+            while matches!(line.last(), Some(b'\n') | Some(b'\r')) {
+                line.pop();
+            }
+            lines.push(String::from_utf8_lossy(&line).to_string());
+        }
+        current += 1;
+    }
+    Ok(LineRange {
+        start_line,
+        lines,
+        eof,
+    })
+}
+#[tauri::command]
+pub fn line_index_status(path: String) -> Result<LineIndexStatus, FsError> {
+    let index = index_for(&workspace::resolve(&path)?)?;
+    let state = index.lock().unwrap();
+    Ok(LineIndexStatus {
+        lines_indexed: state.lines_indexed,
+        bytes_indexed: state.bytes_indexed,
+        total_size: state.size,
+        complete: state.complete,
+    })
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    fn numbered(lines: std::ops::Range<usize>) -> String {
+        lines.map(|i| format!("line {}\n", i)).collect()
+    }
+    fn built(path: &Path) -> Arc<Mutex<LineIndex>> {
+        let index = Arc::new(Mutex::new(LineIndex {
+            size: fs::metadata(path).unwrap().len(),
+            checkpoints: vec![0],
+            building: true,
+            ..Default::default()
+        }));
+        build_line_index(path, &index).unwrap();
+        index
+    }
+    #[test]
+    fn checkpoints_mark_every_stride_of_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("big.log");
+        fs::write(&path, numbered(0..2500) + "tail").unwrap();
+        let index = built(&path);
+        let state = index.lock().unwrap();
+        assert!(state.complete && !state.building);
+        assert_eq!(state.lines_indexed, 2501);
+        assert_eq!(state.checkpoints.len(), 3);
+        let expected = numbered(0..LINE_INDEX_STRIDE).len() as u64;
+        assert_eq!(state.checkpoints[1], expected);
+    }
+    #[test]
+    fn appended_lines_extend_the_existing_index() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("grow.log");
+        fs::write(&path, numbered(0..1000)).unwrap();
+        let index = built(&path);
+        let before = index.lock().unwrap().checkpoints.clone();
+        fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap()
+            .write_all(numbered(1000..2100).as_bytes())
+            .unwrap();
+        {
+            let mut state = index.lock().unwrap();
+            state.size = fs::metadata(&path).unwrap().len();
+            state.complete = false;
+        }
+        build_line_index(&path, &index).unwrap();
+        let state = index.lock().unwrap();
+        assert_eq!(state.lines_indexed, 2100);
+        assert_eq!(&state.checkpoints[..before.len()], &before[..]);
+        assert_eq!(state.checkpoints.len(), 3);
+    }
+    #[test]
+    fn reads_lines_from_the_nearest_checkpoint() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("read.log");
+        fs::write(&path, numbered(0..3000)).unwrap();
+        let range = read_lines(&path, 2047, 3).unwrap();
+        assert_eq!(range.lines, vec!["line 2047", "line 2048", "line 2049"]);
+        assert!(!range.eof);
+        let tail = read_lines(&path, 2999, 5).unwrap();
+        assert_eq!(tail.lines, vec!["line 2999"]);
+        assert!(tail.eof);
+    }
+    #[test]
+    fn a_truncated_file_stops_the_build_instead_of_spinning() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cut.log");
+        fs::write(&path, numbered(0..100)).unwrap();
+        let index = Arc::new(Mutex::new(LineIndex {
+            size: fs::metadata(&path).unwrap().len() * 2,
+            checkpoints: vec![0],
+            building: true,
+            ..Default::default()
+        }));
+        build_line_index(&path, &index).unwrap();
+        let state = index.lock().unwrap();
+        assert!(state.cancelled && !state.building && !state.complete);
+    }
+    #[test]
+    fn empty_range_reads_still_move_forward() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("range.txt");
+        fs::write(&path, "a\u{20AC}b").unwrap();
+        let first = read_range(&path, 0, 0).unwrap();
+        assert_eq!(first.content, "a\u{20AC}");
+        assert_eq!(first.next_offset, 4);
+        let rest = read_range(&path, first.next_offset, 0).unwrap();
+        assert_eq!(rest.content, "b");
+        assert!(rest.eof);
+    }
+}
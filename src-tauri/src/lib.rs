@@ -1,21 +1,56 @@
 mod adb;
+mod backup;
+mod diff;
+mod encoding;
+mod error;
+mod file_index;
 mod filesystem;
 mod formatter;
+mod frecency;
 mod gh;
 mod git;
+mod history;
+mod large_file;
 mod models;
 mod opener;
 mod plugins;
+mod project_settings;
+mod replace;
 mod search;
+mod search_history;
+mod storage;
+mod structural;
+mod symbols;
 mod terminal;
 mod todos;
+mod transfer;
+mod trashcan;
+mod trigram;
+mod walker;
+mod watcher;
+mod workspace;
 use adb::{adb_connect, adb_devices, adb_disconnect, emulator_list_avds, emulator_start};
+use backup::{backup_buffer, discard_buffer_backup, list_recoverable_buffers};
 use filesystem::{
     create_directory, create_file, delete_item, load_project_tree, read_dir, read_file_content,
-    rename_item, save_file_content, search_in_files,
+    rename_item, save_file_content,
 };
 use formatter::{format_code, format_file};
-use search::fuzzy_search_files;
+use frecency::record_file_open;
+use history::{
+    diff_history_snapshot, list_file_history, read_history_snapshot, restore_history_snapshot,
+};
+use large_file::{
+    close_large_file, line_index_status, open_large_file, read_file_lines, read_file_range,
+};
+use replace::{preview_replace, replace_in_files, undo_replace};
+use search::{cancel_search, fuzzy_search_files, search_in_files, start_search};
+use search_history::{
+    clear_search_history, delete_search, list_search_history, pin_search, record_search,
+    rerun_search, save_search,
+};
+use structural::{apply_structural_rewrite, structural_search};
+use symbols::{document_outline, fuzzy_search_symbols};
 use gh::{
     gh_api, gh_auth_login, gh_auth_logout, gh_auth_status, gh_browse, gh_browse_issue,
     gh_browse_pr, gh_gist_create, gh_gist_delete, gh_gist_list, gh_gist_view, gh_issue_close,
@@ -43,10 +78,19 @@ use plugins::{
     uninstall_plugin,
 };
 use std::env;
+use tauri::{Manager, RunEvent};
 use terminal::{
-    detect_virtual_environments, execute_shell_command, resize_pty, spawn_pty, write_pty,
+    attach_pty, detect_virtual_environments, execute_shell_command, kill_pty, list_pty_sessions,
+    list_terminal_profiles, resize_pty, restore_terminal_sessions, spawn_pty, write_pty,
+};
+use todos::{export_todos, list_todo_tags, promote_todo_to_issue, search_todos};
+use transfer::{copy_items, duplicate_item, move_items};
+use trashcan::{list_trashed_items, restore_trashed_item};
+use trigram::{rebuild_search_index, search_index_status, set_search_index_enabled};
+use watcher::{unwatch_project, watch_project};
+use workspace::{
+    close_workspace, list_workspace_roots, open_workspace, pick_workspace, request_path_access,
 };
-use todos::search_todos;
 #[tauri::command]
 fn get_cli_args() -> Vec<String> {
     env::args().collect()
@@ -57,20 +101,73 @@ pub fn run() {
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_fs::init())
         .plugin(tauri_plugin_shell::init())
+        .setup(|app| {
+            storage::init(app.path().app_data_dir()?);
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
+            open_workspace,
+            close_workspace,
+            pick_workspace,
+            list_workspace_roots,
+            request_path_access,
             load_project_tree,
             read_file_content,
             save_file_content,
+            open_large_file,
+            close_large_file,
+            read_file_range,
+            read_file_lines,
+            line_index_status,
+            list_file_history,
+            read_history_snapshot,
+            diff_history_snapshot,
+            restore_history_snapshot,
+            backup_buffer,
+            discard_buffer_backup,
+            list_recoverable_buffers,
             create_file,
             create_directory,
             delete_item,
+            list_trashed_items,
+            restore_trashed_item,
             rename_item,
+            copy_items,
+            move_items,
+            duplicate_item,
             search_in_files,
+            start_search,
+            cancel_search,
+            preview_replace,
+            replace_in_files,
+            undo_replace,
+            search_index_status,
+            set_search_index_enabled,
+            rebuild_search_index,
             fuzzy_search_files,
+            fuzzy_search_symbols,
+            document_outline,
+            structural_search,
+            apply_structural_rewrite,
+            record_search,
+            list_search_history,
+            pin_search,
+            save_search,
+            delete_search,
+            clear_search_history,
+            rerun_search,
+            record_file_open,
             spawn_pty,
+            list_terminal_profiles,
+            list_pty_sessions,
+            attach_pty,
+            kill_pty,
+            restore_terminal_sessions,
             write_pty,
             resize_pty,
             read_dir,
+            watch_project,
+            unwatch_project,
             execute_shell_command,
             detect_virtual_environments,
             opener::open_path,
@@ -175,6 +272,9 @@ pub fn run() {
             gh_api,
             // TODO scanner
             search_todos,
+            export_todos,
+            promote_todo_to_issue,
+            list_todo_tags,
             format_code,
             format_file,
             get_cli_args,
@@ -190,6 +290,11 @@ pub fn run() {
             uninstall_plugin,
             get_plugin_content
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while running tauri application")
+        .run(|_, event| {
+            if let RunEvent::Exit = event {
+                terminal::persist_sessions();
+            }
+        });
 }
\ No newline at end of file
@@ -4,6 +4,33 @@ pub struct SearchResult {
     pub file: String,
     pub line: usize,
     pub content: String,
+    #[serde(default)]
+    pub matches: Vec<MatchRange>,
+    #[serde(default)]
+    pub before: Vec<String>,
+    #[serde(default)]
+    pub after: Vec<String>,
+}
+// Lines are 1-based; columns are UTF-16 offsets so they map directly onto editor positions.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct MatchRange {
+    pub line: usize,
+    pub start: usize,
+    pub end_line: usize,
+    pub end: usize,
+}
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct SearchOptions {
+    pub regex: bool,
+    pub case_sensitive: bool,
+    pub whole_word: bool,
+    pub multiline: bool,
+    pub include: Vec<String>,
+    pub exclude: Vec<String>,
+    pub max_file_size: Option<u64>,
+    pub context_lines: usize,
+    pub max_results: Option<usize>,
 }
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct FileNode {
@@ -11,6 +38,8 @@ pub struct FileNode {
     pub path: String,
     pub is_dir: bool,
     pub children: Option<Vec<FileNode>>,
+    #[serde(default)]
+    pub ignored: bool,
 }
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct GitFile {
@@ -49,4 +78,34 @@ pub struct GitDiff {
 pub struct GitRemote {
     pub name: String,
     pub url: String,
-}
\ No newline at end of file
+}
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct FileVersion {
+    pub mtime: u64,
+    pub size: u64,
+    pub hash: String,
+}
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum LineEnding {
+    Lf,
+    Crlf,
+    Mixed,
+}
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FileContent {
+    pub content: String,
+    pub version: FileVersion,
+    pub encoding: String,
+    pub has_bom: bool,
+    pub line_ending: LineEnding,
+    pub is_binary: bool,
+}
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct SaveOptions {
+    pub expected_version: Option<FileVersion>,
+    pub encoding: Option<String>,
+    pub line_ending: Option<LineEnding>,
+    pub bom: Option<bool>,
+    pub force: Option<bool>,
+}
@@ -1,7 +1,10 @@
+use crate::error::FsError;
+use crate::storage;
+use crate::workspace;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
-use std::path::{Path, PathBuf};
+use std::path::{Component, Path, PathBuf};
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PluginManifest {
     pub id: String,
@@ -115,7 +118,7 @@ impl PluginManager {
         }
         let manifest = self.load_manifest(&manifest_path)?;
         let content = if matches!(manifest.plugin_type, PluginType::Js) {
-            let main_path = plugin_path.join(&manifest.main);
+            let main_path = resolve_main(&plugin_path, &manifest.main)?;
             Some(fs::read_to_string(main_path).map_err(|e| e.to_string())?)
         } else {
             None
@@ -144,9 +147,51 @@ impl PluginManager {
         self.plugins.get(plugin_id)
     }
 }
+// The entry point comes from plugin.json, so it must stay inside the plugin's own folder.
+fn resolve_main(plugin_path: &Path, main: &str) -> Result<PathBuf, String> {
+    let relative = Path::new(main);
+    if main.is_empty()
+        || !relative
+            .components()
+            .all(|c| matches!(c, Component::Normal(_)))
+    {
+        return Err(format!("Invalid plugin entry point: {:?}", main));
+    }
+    let base = fs::canonicalize(plugin_path).map_err(|e| e.to_string())?;
+    let resolved = fs::canonicalize(base.join(relative)).map_err(|e| e.to_string())?;
+    if !resolved.starts_with(&base) {
+        return Err(format!("Invalid plugin entry point: {:?}", main));
+    }
+    Ok(resolved)
+}
+// Plugin commands reach only the plugin folder, which sits outside every workspace.
+fn resolve_in_plugin_dir(path: &Path) -> Result<PathBuf, FsError> {
+    let resolved = workspace::canonicalize(path)?;
+    if resolved.starts_with(workspace::canonicalize(&storage::plugin_dir())?) {
+        Ok(resolved)
+    } else {
+        Err(FsError::OutsideWorkspace {
+            path: path.to_string_lossy().to_string(),
+        })
+    }
+}
+fn resolve_plugin_dir(plugin_dir: &str) -> Result<PathBuf, FsError> {
+    resolve_in_plugin_dir(Path::new(plugin_dir))
+}
+fn resolve_plugin_path(plugin_dir: &str, plugin_id: &str) -> Result<PathBuf, FsError> {
+    // A plugin id names exactly one directory inside the plugin folder.
+    let mut components = Path::new(plugin_id).components();
+    if !matches!(
+        (components.next(), components.next()),
+        (Some(Component::Normal(_)), None)
+    ) {
+        return Err(format!("Invalid plugin id: {:?}", plugin_id).into());
+    }
+    resolve_in_plugin_dir(&Path::new(plugin_dir).join(plugin_id))
+}
 #[tauri::command]
-pub fn ensure_plugin_dir(plugin_dir: String) -> Result<(), String> {
-    let path = PathBuf::from(plugin_dir);
+pub fn ensure_plugin_dir(plugin_dir: String) -> Result<(), FsError> {
+    let path = resolve_plugin_dir(&plugin_dir)?;
     if !path.exists() {
         fs::create_dir_all(&path)
             .map_err(|e| format!("Failed to create plugin directory: {}", e))?;
@@ -154,34 +199,31 @@ pub fn ensure_plugin_dir(plugin_dir: String) -> Result<(), String> {
     Ok(())
 }
 #[tauri::command]
-pub fn discover_plugins(plugin_dir: String) -> Result<Vec<PluginManifest>, String> {
-    let mut manager = PluginManager::new(PathBuf::from(plugin_dir));
-    manager.discover_plugins()
+pub fn discover_plugins(plugin_dir: String) -> Result<Vec<PluginManifest>, FsError> {
+    let mut manager = PluginManager::new(resolve_plugin_dir(&plugin_dir)?);
+    Ok(manager.discover_plugins()?)
 }
 #[tauri::command]
-pub fn load_plugin(plugin_dir: String, plugin_id: String) -> Result<LoadedPlugin, String> {
-    let mut manager = PluginManager::new(PathBuf::from(plugin_dir));
-    manager.load_plugin(&plugin_id)
+pub fn load_plugin(plugin_dir: String, plugin_id: String) -> Result<LoadedPlugin, FsError> {
+    resolve_plugin_path(&plugin_dir, &plugin_id)?;
+    let mut manager = PluginManager::new(resolve_plugin_dir(&plugin_dir)?);
+    Ok(manager.load_plugin(&plugin_id)?)
 }
 #[tauri::command]
-pub fn get_plugin_content(plugin_dir: String, plugin_id: String) -> Result<String, String> {
-    let manager = PluginManager::new(PathBuf::from(plugin_dir));
-    let plugin_path = manager.plugin_dir.join(plugin_id);
+pub fn get_plugin_content(plugin_dir: String, plugin_id: String) -> Result<String, FsError> {
+    let plugin_path = resolve_plugin_path(&plugin_dir, &plugin_id)?;
     let manifest_path = plugin_path.join("plugin.json");
     let manifest: PluginManifest = {
-        let content = fs::read_to_string(&manifest_path).map_err(|e| e.to_string())?;
+        let content = fs::read_to_string(&manifest_path)?;
         serde_json::from_str(&content).map_err(|e| e.to_string())?
     };
-    let main_path = plugin_path.join(&manifest.main);
-    fs::read_to_string(main_path).map_err(|e| e.to_string())
+    let main_path = resolve_main(&plugin_path, &manifest.main)?;
+    Ok(fs::read_to_string(main_path)?)
 }
 #[tauri::command]
-pub fn install_plugin(
-    plugin_dir: String,
-    plugin_url: String,
-    plugin_id: String,
-) -> Result<(), String> {
-    let plugin_path = PathBuf::from(&plugin_dir).join(&plugin_id);
+pub fn install_plugin(plugin_dir: String, plugin_id: String) -> Result<(), FsError> {
+    // Only the bundled plugins below can be installed; no URL is fetched.
+    let plugin_path = resolve_plugin_path(&plugin_dir, &plugin_id)?;
     fs::create_dir_all(&plugin_path)
         .map_err(|e| format!("Failed to create plugin directory: {}", e))?;
     let (manifest_content, index_content) = match plugin_id.as_str() {
@@ -283,7 +325,7 @@ self.addEventListener('message', (e) => {
 });
 "#,
         ),
-        _ => return Err(format!("Unknown plugin: {}", plugin_id)),
+        _ => return Err(format!("Unknown plugin: {}", plugin_id).into()),
     };
     let manifest_path = plugin_path.join("plugin.json");
     fs::write(&manifest_path, manifest_content)
@@ -294,12 +336,73 @@ self.addEventListener('message', (e) => {
     Ok(())
 }
 #[tauri::command]
-pub fn uninstall_plugin(plugin_dir: String, plugin_id: String) -> Result<(), String> {
-    let plugin_path = Path::new(&plugin_dir).join(&plugin_id);
+pub fn uninstall_plugin(plugin_dir: String, plugin_id: String) -> Result<(), FsError> {
+    let plugin_path = resolve_plugin_path(&plugin_dir, &plugin_id)?;
     if !plugin_path.exists() {
-        return Err(format!("Plugin {} not found", plugin_id));
+        return Err(format!("Plugin {} not found", plugin_id).into());
+    }
+    // The id may be a symlink, so the resolved path must still sit directly in the plugin folder.
+    let dir = resolve_plugin_dir(&plugin_dir)?;
+    if workspace::is_root(&plugin_path)
+        || plugin_path == dir
+        || plugin_path.parent() != Some(dir.as_path())
+    {
+        return Err(format!("Refusing to remove {:?}", plugin_path).into());
     }
     fs::remove_dir_all(&plugin_path)
         .map_err(|e| format!("Failed to remove plugin directory: {}", e))?;
     Ok(())
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    fn plugin(main: &str) -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        let plugin_path = dir.path().join("demo");
+        fs::create_dir_all(plugin_path.join("lib")).unwrap();
+        fs::write(plugin_path.join("lib/main.js"), "// demo").unwrap();
+        fs::write(dir.path().join("secret"), "token").unwrap();
+        let manifest = serde_json::json!({
+            "id": "demo",
+            "name": "Demo",
+            "version": "1.0.0",
+            "type": "js",
+            "main": main,
+            "activation_events": [],
+            "permissions": [],
+            "enabled": true
+        });
+        fs::write(plugin_path.join("plugin.json"), manifest.to_string()).unwrap();
+        dir
+    }
+    #[test]
+    fn loads_an_entry_point_inside_the_plugin_folder() {
+        let dir = plugin("lib/main.js");
+        let mut manager = PluginManager::new(dir.path().to_path_buf());
+        let loaded = manager.load_plugin("demo").unwrap();
+        assert_eq!(loaded.content.as_deref(), Some("// demo"));
+    }
+    #[test]
+    fn rejects_entry_points_that_leave_the_plugin_folder() {
+        let dir = plugin("");
+        let plugin_path = dir.path().join("demo");
+        let secret = dir.path().join("secret");
+        for main in [
+            "../secret",
+            "lib/../../secret",
+            secret.to_str().unwrap(),
+            "",
+        ] {
+            assert!(resolve_main(&plugin_path, main).is_err(), "{}", main);
+        }
+    }
+    #[cfg(unix)]
+    #[test]
+    fn rejects_entry_points_that_symlink_out_of_the_plugin_folder() {
+        let dir = plugin("link.js");
+        std::os::unix::fs::symlink(dir.path().join("secret"), dir.path().join("demo/link.js"))
+            .unwrap();
+        let mut manager = PluginManager::new(dir.path().to_path_buf());
+        assert!(manager.load_plugin("demo").is_err());
+    }
+}
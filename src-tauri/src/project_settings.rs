@@ -0,0 +1,65 @@
+use crate::terminal::TerminalProfile;
+use crate::todos::TodoTag;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+#[derive(Deserialize, Debug, Clone)]
+#[serde(untagged)]
+enum ExcludeSetting {
+    List(Vec<String>),
+    Map(HashMap<String, bool>),
+}
+impl Default for ExcludeSetting {
+    fn default() -> Self {
+        ExcludeSetting::List(Vec::new())
+    }
+}
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct ProjectSettings {
+    #[serde(rename = "files.exclude", default)]
+    files_exclude: ExcludeSetting,
+    #[serde(rename = "history.maxEntries")]
+    pub history_max_entries: Option<usize>,
+    #[serde(rename = "history.maxAgeDays")]
+    pub history_max_age_days: Option<u64>,
+    #[serde(rename = "history.maxSizeMB")]
+    pub history_max_size_mb: Option<u64>,
+    #[serde(rename = "todo.tags")]
+    pub todo_tags: Option<Vec<TodoTag>>,
+    // Profiles are only honoured from ~/.mide/settings.json, never from a project's own settings.
+    #[serde(rename = "terminal.profiles", default)]
+    pub terminal_profiles: Vec<TerminalProfile>,
+    #[serde(rename = "terminal.defaultProfile")]
+    pub terminal_default_profile: Option<String>,
+    // Injected into every terminal opened in the project, after the profile's own env.
+    // Project files may only set the plain variables allowed by the terminal, never ones like PATH.
+    #[serde(rename = "terminal.env", default)]
+    pub terminal_env: HashMap<String, Option<String>>,
+}
+impl ProjectSettings {
+    pub fn files_exclude(&self) -> Vec<String> {
+        match &self.files_exclude {
+            ExcludeSetting::List(globs) => globs.clone(),
+            ExcludeSetting::Map(globs) => globs
+                .iter()
+                .filter(|(_, enabled)| **enabled)
+                .map(|(glob, _)| glob.clone())
+                .collect(),
+        }
+    }
+}
+pub fn settings_path(root: &Path) -> PathBuf {
+    root.join(".mide").join("settings.json")
+}
+pub fn load(root: &Path) -> ProjectSettings {
+    let path = settings_path(root);
+    let content = match fs::read_to_string(&path) {
+        Ok(c) => c,
+        Err(_) => return ProjectSettings::default(),
+    };
+    serde_json::from_str(&content).unwrap_or_else(|e| {
+        eprintln!("Invalid project settings in {:?}: {}", path, e);
+        ProjectSettings::default()
+    })
+}
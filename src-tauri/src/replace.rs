@@ -0,0 +1,470 @@
+use crate::diff::{self, FileDiff};
+use crate::encoding;
+use crate::error::FsError;
+use crate::filesystem::{content_hash, file_version, read_with_version, write_atomic};
+use crate::history::{self, SnapshotKind};
+use crate::models::{FileVersion, SearchOptions};
+use crate::search::build_matcher;
+use crate::walker::{self, GlobFilter};
+use crate::workspace;
+use chrono::Utc;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+const MAX_PREVIEW_FILES: usize = 500;
+const MAX_UNDO_OPERATIONS: usize = 20;
+const MAX_UNDO_BYTES: usize = 64 * 1024 * 1024;
+struct UndoEntry {
+    path: PathBuf,
+    original: Vec<u8>,
+    written_hash: String,
+}
+lazy_static::lazy_static! {
+    // Oldest first; trimmed to the operation and byte budgets whenever a replace is committed.
+    static ref UNDO_ENTRIES: Mutex<VecDeque<(String, Vec<UndoEntry>)>> = Mutex::new(VecDeque::new());
+}
+#[derive(Serialize, Debug, Clone)]
+pub struct ReplacePreview {
+    pub file: String,
+    pub version: FileVersion,
+    pub replacements: usize,
+    #[serde(flatten)]
+    pub diff: FileDiff,
+}
+// `truncated` is set when more files would change than the preview lists.
+#[derive(Serialize, Debug, Clone)]
+pub struct ReplacePreviewSet {
+    pub files: Vec<ReplacePreview>,
+    pub truncated: bool,
+}
+#[derive(Deserialize, Debug, Clone)]
+pub struct ReplaceTarget {
+    pub file: String,
+    pub version: Option<FileVersion>,
+}
+#[derive(Serialize, Debug, Clone)]
+pub struct ReplaceOutcome {
+    pub undo_token: String,
+    pub files: Vec<String>,
+    pub replacements: usize,
+}
+struct Replacer<'a> {
+    matcher: Regex,
+    replacement: &'a str,
+    options: &'a SearchOptions,
+    preserve_case: bool,
+}
+pub(crate) struct PlannedChange {
+    pub(crate) path: PathBuf,
+    pub(crate) version: FileVersion,
+    pub(crate) original: Vec<u8>,
+    pub(crate) updated: Vec<u8>,
+    pub(crate) old_text: String,
+    pub(crate) new_text: String,
+    pub(crate) replacements: usize,
+}
+fn apply_case(matched: &str, replacement: String) -> String {
+    let letters: Vec<char> = matched.chars().filter(|c| c.is_alphabetic()).collect();
+    if letters.is_empty() {
+        return replacement;
+    }
+    if letters.len() > 1 && letters.iter().all(|c| c.is_uppercase()) {
+        return replacement.to_uppercase();
+    }
+    if letters.iter().all(|c| c.is_lowercase()) {
+        return replacement.to_lowercase();
+    }
+    if letters[0].is_uppercase() {
+        let mut chars = replacement.chars();
+        return match chars.next() {
+            Some(first) => first.to_uppercase().chain(chars).collect(),
+            None => replacement,
+        };
+    }
+    replacement
+}
+impl<'a> Replacer<'a> {
+    fn new(
+        query: &str,
+        replacement: &'a str,
+        options: &'a SearchOptions,
+        preserve_case: bool,
+    ) -> Result<Self, String> {
+        if query.is_empty() {
+            return Err("Search query is empty".to_string());
+        }
+        Ok(Replacer {
+            matcher: build_matcher(query, options)?,
+            replacement,
+            options,
+            preserve_case,
+        })
+    }
+    fn replace_segment(&self, text: &str) -> (String, usize) {
+        let mut out = String::with_capacity(text.len());
+        let mut last = 0;
+        let mut count = 0;
+        for caps in self.matcher.captures_iter(text) {
+            let m = caps.get(0).unwrap();
+            if m.is_empty() {
+                continue;
+            }
+            out.push_str(&text[last..m.start()]);
+            let mut expanded = String::new();
+            if self.options.regex {
+                caps.expand(self.replacement, &mut expanded);
+            } else {
+                expanded.push_str(self.replacement);
+            }
+            if self.preserve_case {
+                expanded = apply_case(m.as_str(), expanded);
+            }
+            out.push_str(&expanded);
+            last = m.end();
+            count += 1;
+        }
+        out.push_str(&text[last..]);
+        (out, count)
+    }
+    fn replace_text(&self, text: &str) -> (String, usize) {
+        if self.options.multiline {
+            return self.replace_segment(text);
+        }
+        let mut out = String::with_capacity(text.len());
+        let mut total = 0;
+        for line in text.split_inclusive('\n') {
+            let body = line.trim_end_matches(['\n', '\r']);
+            let (replaced, count) = self.replace_segment(body);
+            out.push_str(&replaced);
+            out.push_str(&line[body.len()..]);
+            total += count;
+        }
+        (out, total)
+    }
+    fn plan(&self, path: &Path) -> Result<Option<PlannedChange>, FsError> {
+        let original = fs::read(path)?;
+        let metadata = fs::metadata(path)?;
+        let decoded = encoding::decode(&original);
+        if decoded.is_binary {
+            return Ok(None);
+        }
+        let (new_text, replacements) = self.replace_text(&decoded.content);
+        if replacements == 0 || new_text == decoded.content {
+            return Ok(None);
+        }
+        let updated = encoding::encode(&new_text, decoded.encoding, decoded.has_bom)?;
+        Ok(Some(PlannedChange {
+            path: path.to_path_buf(),
+            version: file_version(&original, &metadata),
+            original,
+            updated,
+            old_text: decoded.content,
+            new_text,
+            replacements,
+        }))
+    }
+}
+fn next_undo_token() -> String {
+    let entries = UNDO_ENTRIES.lock().unwrap();
+    let base = Utc::now().timestamp_millis();
+    (0..)
+        .map(|n| format!("replace-{}-{}", base, n))
+        .find(|token| !entries.iter().any(|(t, _)| t == token))
+        .unwrap()
+}
+fn undo_bytes(entries: &[UndoEntry]) -> usize {
+    entries.iter().map(|e| e.original.len()).sum()
+}
+fn remember_undo(token: String, entries: Vec<UndoEntry>) {
+    let mut undo = UNDO_ENTRIES.lock().unwrap();
+    undo.push_back((token, entries));
+    let mut total: usize = undo.iter().map(|(_, e)| undo_bytes(e)).sum();
+    // The newest operation is always kept, even when it alone exceeds the byte budget.
+    while undo.len() > 1 && (undo.len() > MAX_UNDO_OPERATIONS || total > MAX_UNDO_BYTES) {
+        if let Some((_, evicted)) = undo.pop_front() {
+            total -= undo_bytes(&evicted);
+        }
+    }
+}
+fn collect_previews(
+    root: &Path,
+    replacer: &Replacer,
+    options: &SearchOptions,
+    limit: usize,
+) -> Result<ReplacePreviewSet, FsError> {
+    let filter = GlobFilter::new(root, &options.include, &options.exclude)?;
+    let mut previews = Vec::new();
+    for file in walker::walk_files(root).filter(|file| filter.matches(file)) {
+        if let (Some(max), Ok(metadata)) = (options.max_file_size, fs::metadata(&file)) {
+            if metadata.len() > max {
+                continue;
+            }
+        }
+        let change = match replacer.plan(&file) {
+            Ok(Some(change)) => change,
+            _ => continue,
+        };
+        if previews.len() >= limit {
+            return Ok(ReplacePreviewSet {
+                files: previews,
+                truncated: true,
+            });
+        }
+        let name = file.to_string_lossy().to_string();
+        previews.push(ReplacePreview {
+            diff: diff::unified(&change.old_text, &change.new_text, &name, &name),
+            file: name,
+            version: change.version,
+            replacements: change.replacements,
+        });
+    }
+    Ok(ReplacePreviewSet {
+        files: previews,
+        truncated: false,
+    })
+}
+// Both commands read, diff or write every affected file, so they run on a blocking thread.
+#[tauri::command]
+pub async fn preview_replace(
+    path: String,
+    query: String,
+    replacement: String,
+    options: Option<SearchOptions>,
+    preserve_case: Option<bool>,
+) -> Result<ReplacePreviewSet, FsError> {
+    workspace::resolve(&path)?;
+    tauri::async_runtime::spawn_blocking(move || {
+        let options = options.unwrap_or_default();
+        let replacer = Replacer::new(
+            &query,
+            &replacement,
+            &options,
+            preserve_case.unwrap_or(false),
+        )?;
+        collect_previews(Path::new(&path), &replacer, &options, MAX_PREVIEW_FILES)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+#[tauri::command]
+pub async fn replace_in_files(
+    query: String,
+    replacement: String,
+    options: Option<SearchOptions>,
+    preserve_case: Option<bool>,
+    files: Vec<ReplaceTarget>,
+) -> Result<ReplaceOutcome, FsError> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let options = options.unwrap_or_default();
+        let replacer = Replacer::new(
+            &query,
+            &replacement,
+            &options,
+            preserve_case.unwrap_or(false),
+        )?;
+        apply_replace(&replacer, &files)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+fn apply_replace(replacer: &Replacer, files: &[ReplaceTarget]) -> Result<ReplaceOutcome, FsError> {
+    let mut changes = Vec::new();
+    for target in files {
+        let resolved = workspace::resolve(&target.file)?;
+        let change = match replacer.plan(&resolved)? {
+            Some(change) => change,
+            None => continue,
+        };
+        ensure_version(target, &resolved, &change)?;
+        changes.push((target.file.clone(), change));
+    }
+    commit(changes)
+}
+pub(crate) fn ensure_version(
+    target: &ReplaceTarget,
+    resolved: &Path,
+    change: &PlannedChange,
+) -> Result<(), FsError> {
+    if let Some(expected) = &target.version {
+        if expected.hash != change.version.hash || expected.size != change.version.size {
+            return Err(FsError::Stale {
+                path: target.file.clone(),
+                current: read_with_version(resolved)?,
+            });
+        }
+    }
+    Ok(())
+}
+pub(crate) fn commit(changes: Vec<(String, PlannedChange)>) -> Result<ReplaceOutcome, FsError> {
+    // Callers may plan long before committing, so nothing is written if a file was saved since.
+    for (file, change) in &changes {
+        if content_hash(&fs::read(&change.path)?) != change.version.hash {
+            return Err(FsError::Stale {
+                path: file.clone(),
+                current: read_with_version(&change.path)?,
+            });
+        }
+    }
+    // Either every selected file is rewritten or the ones already written are put back.
+    let mut written: Vec<&PlannedChange> = Vec::new();
+    for (_, change) in &changes {
+        history::capture_original(&change.path, &change.version.hash);
+        if let Err(e) = write_atomic(&change.path, &change.updated) {
+            for done in written {
+                let _ = write_atomic(&done.path, &done.original);
+            }
+            return Err(e.into());
+        }
+        history::record(&change.path, &change.updated, SnapshotKind::Save);
+        written.push(change);
+    }
+    let undo_token = next_undo_token();
+    let outcome = ReplaceOutcome {
+        undo_token: undo_token.clone(),
+        files: changes.iter().map(|(file, _)| file.clone()).collect(),
+        replacements: changes.iter().map(|(_, c)| c.replacements).sum(),
+    };
+    let entries = changes
+        .into_iter()
+        .map(|(_, change)| UndoEntry {
+            written_hash: content_hash(&change.updated),
+            path: change.path,
+            original: change.original,
+        })
+        .collect();
+    remember_undo(undo_token, entries);
+    Ok(outcome)
+}
+#[tauri::command]
+pub fn undo_replace(undo_token: String) -> Result<Vec<String>, FsError> {
+    let mut undo = UNDO_ENTRIES.lock().unwrap();
+    let slot = undo
+        .iter()
+        .position(|(token, _)| token == &undo_token)
+        .ok_or_else(|| format!("Unknown undo token {}", undo_token))?;
+    for entry in &undo[slot].1 {
+        let current = fs::read(&entry.path)?;
+        if content_hash(&current) != entry.written_hash {
+            return Err(FsError::Stale {
+                path: entry.path.to_string_lossy().to_string(),
+                current: read_with_version(&entry.path)?,
+            });
+        }
+    }
+    let (_, entries) = undo.remove(slot).unwrap_or_default();
+    let mut restored = Vec::new();
+    let mut failed = Vec::new();
+    let mut first_error = None;
+    for entry in entries {
+        match write_atomic(&entry.path, &entry.original) {
+            Ok(()) => {
+                history::record(&entry.path, &entry.original, SnapshotKind::Restore);
+                restored.push(entry.path.to_string_lossy().to_string());
+            }
+            Err(e) => {
+                first_error.get_or_insert_with(|| format!("{:?}: {}", entry.path, e));
+                failed.push(entry);
+            }
+        }
+    }
+    // Files that could not be written keep their originals under the same token so undo can be retried.
+    if let Some(error) = first_error {
+        let count = failed.len();
+        undo.insert(slot, (undo_token, failed));
+        return Err(format!(
+            "Restored {} files, {} could not be restored ({}); undo again to retry",
+            restored.len(),
+            count,
+            error
+        )
+        .into());
+    }
+    Ok(restored)
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    fn plan(replacer: &Replacer, path: &Path) -> (String, PlannedChange) {
+        let change = replacer.plan(path).unwrap().unwrap();
+        (path.to_string_lossy().to_string(), change)
+    }
+    #[test]
+    fn previews_report_files_beyond_the_limit() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in ["a.txt", "b.txt", "c.txt"] {
+            fs::write(dir.path().join(name), "old").unwrap();
+        }
+        fs::write(dir.path().join("d.txt"), "other").unwrap();
+        let options = SearchOptions::default();
+        let replacer = Replacer::new("old", "new", &options, false).unwrap();
+        let full = collect_previews(dir.path(), &replacer, &options, 3).unwrap();
+        assert_eq!(full.files.len(), 3);
+        assert!(!full.truncated);
+        let partial = collect_previews(dir.path(), &replacer, &options, 2).unwrap();
+        assert_eq!(partial.files.len(), 2);
+        assert!(partial.truncated);
+    }
+    #[test]
+    fn committed_replacements_can_be_undone_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let a = dir.path().join("a.txt");
+        let b = dir.path().join("b.txt");
+        fs::write(&a, "Foo foo\n").unwrap();
+        fs::write(&b, "FOO\n").unwrap();
+        let options = SearchOptions::default();
+        let replacer = Replacer::new("foo", "bar", &options, true).unwrap();
+        let outcome = commit(vec![plan(&replacer, &a), plan(&replacer, &b)]).unwrap();
+        assert_eq!(outcome.replacements, 3);
+        assert_eq!(fs::read_to_string(&a).unwrap(), "Bar bar\n");
+        assert_eq!(fs::read_to_string(&b).unwrap(), "BAR\n");
+        let restored = undo_replace(outcome.undo_token.clone()).unwrap();
+        assert_eq!(restored.len(), 2);
+        assert_eq!(fs::read_to_string(&a).unwrap(), "Foo foo\n");
+        assert_eq!(fs::read_to_string(&b).unwrap(), "FOO\n");
+        assert!(undo_replace(outcome.undo_token).is_err());
+    }
+    #[test]
+    fn files_saved_after_planning_stop_the_commit() {
+        let dir = tempfile::tempdir().unwrap();
+        let a = dir.path().join("a.txt");
+        let b = dir.path().join("b.txt");
+        fs::write(&a, "foo").unwrap();
+        fs::write(&b, "foo").unwrap();
+        let options = SearchOptions::default();
+        let replacer = Replacer::new("foo", "bar", &options, false).unwrap();
+        let changes = vec![plan(&replacer, &a), plan(&replacer, &b)];
+        fs::write(&b, "foo edited").unwrap();
+        assert!(matches!(commit(changes), Err(FsError::Stale { .. })));
+        assert_eq!(fs::read_to_string(&a).unwrap(), "foo");
+    }
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn a_failed_write_rolls_back_earlier_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let a = dir.path().join("a.txt");
+        fs::write(&a, "foo").unwrap();
+        let options = SearchOptions::default();
+        let replacer = Replacer::new("foo", "bar", &options, false).unwrap();
+        // Readable, but no temporary file can be created beside it, even as root.
+        let locked = PathBuf::from("/proc/version");
+        let original = fs::read(&locked).unwrap();
+        let blocked = PlannedChange {
+            version: file_version(&original, &fs::metadata(&locked).unwrap()),
+            path: locked.clone(),
+            updated: b"bar".to_vec(),
+            original,
+            old_text: String::new(),
+            new_text: String::new(),
+            replacements: 1,
+        };
+        let changes = vec![
+            plan(&replacer, &a),
+            (locked.to_string_lossy().to_string(), blocked),
+        ];
+        assert!(commit(changes).is_err());
+        assert_eq!(fs::read_to_string(&a).unwrap(), "foo");
+    }
+}
@@ -1,43 +1,658 @@
+use crate::encoding;
+use crate::error::FsError;
+use crate::frecency;
+use crate::models::{MatchRange, SearchOptions, SearchResult};
+use crate::trigram;
+use crate::walker::{self, GlobFilter};
+use crate::watcher;
+use crate::workspace;
 use fuzzy_matcher::skim::SkimMatcherV2;
 use fuzzy_matcher::FuzzyMatcher;
+use ignore::WalkState;
+use regex::{Regex, RegexBuilder};
 use serde::{Deserialize, Serialize};
-use std::path::Path;
-use walkdir::WalkDir;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use tauri::{Emitter, Window};
+const DEFAULT_MAX_RESULTS: usize = 1000;
+const DEFAULT_FUZZY_LIMIT: usize = 50;
+const FILENAME_BONUS: i64 = 50;
+const BATCH_INTERVAL: Duration = Duration::from_millis(50);
+const MAX_BATCH_SIZE: usize = 500;
+struct ActiveSearch {
+    window: String,
+    cancelled: Arc<AtomicBool>,
+}
+lazy_static::lazy_static! {
+    static ref SEARCHES: Mutex<HashMap<String, ActiveSearch>> = Mutex::new(HashMap::new());
+    static ref FILE_LISTS: Mutex<HashMap<PathBuf, Arc<Vec<String>>>> = Mutex::new(HashMap::new());
+}
+#[derive(Serialize, Debug, Clone)]
+pub struct SearchBatch {
+    pub search_id: String,
+    pub results: Vec<SearchResult>,
+    pub files_scanned: usize,
+    pub match_count: usize,
+    pub done: bool,
+    pub cancelled: bool,
+}
+// Positions are character indices into `relative_path` for highlighting.
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct FileResult {
     pub path: String,
     pub score: i64,
+    #[serde(default)]
+    pub relative_path: String,
+    #[serde(default)]
+    pub positions: Vec<usize>,
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+}
+pub fn invalidate_file_list(root: &Path) {
+    FILE_LISTS.lock().unwrap().remove(root);
+}
+fn file_list(root: &Path) -> Arc<Vec<String>> {
+    if let Some(files) = FILE_LISTS.lock().unwrap().get(root) {
+        return files.clone();
+    }
+    let files: Arc<Vec<String>> = Arc::new(
+        walker::walk_files(root)
+            .filter_map(|file| {
+                file.strip_prefix(root)
+                    .ok()
+                    .map(|r| r.to_string_lossy().replace('\\', "/"))
+            })
+            .collect(),
+    );
+    // Only watched roots get change notifications, so only those can be cached safely.
+    if watcher::is_watching(root) {
+        FILE_LISTS
+            .lock()
+            .unwrap()
+            .insert(root.to_path_buf(), files.clone());
+    }
+    files
+}
+fn parse_location(query: &str) -> (&str, Option<usize>, Option<usize>) {
+    let mut rest = query;
+    let mut numbers = Vec::new();
+    while numbers.len() < 2 {
+        match rest.rsplit_once(':') {
+            Some((head, tail)) if !tail.is_empty() && tail.chars().all(|c| c.is_ascii_digit()) => {
+                numbers.push(tail.parse().ok());
+                rest = head;
+            }
+            Some((head, "")) if numbers.is_empty() => rest = head,
+            _ => break,
+        }
+    }
+    match numbers.as_slice() {
+        [column, line] => (rest, *line, *column),
+        [line] => (rest, *line, None),
+        _ => (rest, None, None),
+    }
+}
+fn score_path(matcher: &SkimMatcherV2, relative: &str, pattern: &str) -> Option<(i64, Vec<usize>)> {
+    let name_start = relative.rfind('/').map(|i| i + 1).unwrap_or(0);
+    let offset = relative[..name_start].chars().count();
+    if !pattern.contains('/') {
+        if let Some((score, positions)) = matcher.fuzzy_indices(&relative[name_start..], pattern) {
+            let positions = positions.into_iter().map(|p| p + offset).collect();
+            return Some((score * 2 + FILENAME_BONUS, positions));
+        }
+    }
+    let depth = relative.matches('/').count() as i64;
+    matcher
+        .fuzzy_indices(relative, pattern)
+        .map(|(score, positions)| (score - depth, positions))
 }
 #[tauri::command]
-pub fn fuzzy_search_files(path: String, query: String) -> Result<Vec<FileResult>, String> {
+pub fn fuzzy_search_files(
+    path: String,
+    query: String,
+    limit: Option<usize>,
+) -> Result<Vec<FileResult>, FsError> {
+    let resolved = workspace::resolve(&path)?;
+    let (pattern, line, column) = parse_location(query.trim());
+    let pattern: String = pattern.chars().filter(|c| !c.is_whitespace()).collect();
+    let files = file_list(&resolved);
+    let usage = frecency::snapshot(&resolved);
     let matcher = SkimMatcherV2::default();
+    let mut results: Vec<FileResult> = files
+        .iter()
+        .filter_map(|relative| {
+            let boost = usage.get(relative).map(frecency::boost).unwrap_or(0);
+            let (score, positions) = if pattern.is_empty() {
+                // With no pattern the finder lists recently used files.
+                if boost == 0 {
+                    return None;
+                }
+                (0, Vec::new())
+            } else {
+                score_path(&matcher, relative, &pattern)?
+            };
+            Some(FileResult {
+                path: Path::new(&path)
+                    .join(relative)
+                    .to_string_lossy()
+                    .to_string(),
+                relative_path: relative.clone(),
+                score: score + boost,
+                positions,
+                line,
+                column,
+            })
+        })
+        .collect();
+    results.sort_by(|a, b| {
+        b.score
+            .cmp(&a.score)
+            .then_with(|| a.relative_path.len().cmp(&b.relative_path.len()))
+    });
+    results.truncate(limit.unwrap_or(DEFAULT_FUZZY_LIMIT));
+    Ok(results)
+}
+pub(crate) fn build_matcher(query: &str, options: &SearchOptions) -> Result<Regex, String> {
+    let mut pattern = if options.regex {
+        query.to_string()
+    } else {
+        regex::escape(query)
+    };
+    if options.whole_word {
+        // Either boundary holds at a punctuation edge, so `foo(` and `-flag` only need
+        // a boundary on their word-character side.
+        pattern = format!(
+            r"(?:\b|\b{{start-half}})(?:{})(?:\b|\b{{end-half}})",
+            pattern
+        );
+    }
+    RegexBuilder::new(&pattern)
+        .case_insensitive(!options.case_sensitive)
+        .multi_line(options.multiline)
+        .dot_matches_new_line(options.multiline)
+        .build()
+        .map_err(|e| e.to_string())
+}
+fn utf16_len(text: &str) -> usize {
+    text.encode_utf16().count()
+}
+fn position(content: &str, line_starts: &[usize], offset: usize) -> (usize, usize) {
+    let line = line_starts.partition_point(|&start| start <= offset) - 1;
+    (line, utf16_len(&content[line_starts[line]..offset]))
+}
+pub(crate) fn search_file(
+    path: &Path,
+    matcher: &Regex,
+    options: &SearchOptions,
+    limit: usize,
+) -> Vec<SearchResult> {
+    let mut results: Vec<SearchResult> = Vec::new();
+    if let (Some(max), Ok(metadata)) = (options.max_file_size, fs::metadata(path)) {
+        if metadata.len() > max {
+            return results;
+        }
+    }
+    let bytes = match fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(_) => return results,
+    };
+    let decoded = encoding::decode(&bytes);
+    if decoded.is_binary {
+        return results;
+    }
+    let content = decoded.content;
+    let mut line_starts = vec![0];
+    line_starts.extend(content.match_indices('\n').map(|(i, _)| i + 1));
+    let lines: Vec<&str> = content.lines().collect();
+    let mut ranges = Vec::new();
+    if options.multiline {
+        for m in matcher.find_iter(&content).filter(|m| !m.is_empty()) {
+            let (line, start) = position(&content, &line_starts, m.start());
+            let (end_line, end) = position(&content, &line_starts, m.end());
+            ranges.push(MatchRange {
+                line: line + 1,
+                start,
+                end_line: end_line + 1,
+                end,
+            });
+        }
+    } else {
+        for (i, text) in lines.iter().enumerate() {
+            for m in matcher.find_iter(text).filter(|m| !m.is_empty()) {
+                ranges.push(MatchRange {
+                    line: i + 1,
+                    start: utf16_len(&text[..m.start()]),
+                    end_line: i + 1,
+                    end: utf16_len(&text[..m.end()]),
+                });
+            }
+        }
+    }
+    let context = options.context_lines;
+    for range in ranges {
+        if let Some(last) = results.last_mut().filter(|r| r.line == range.line) {
+            last.matches.push(range);
+            continue;
+        }
+        if results.len() >= limit {
+            break;
+        }
+        let index = range.line - 1;
+        results.push(SearchResult {
+            file: path.to_string_lossy().to_string(),
+            line: range.line,
+            content: lines.get(index).unwrap_or(&"").to_string(),
+            before: lines[index.saturating_sub(context)..index.min(lines.len())]
+                .iter()
+                .map(|l| l.to_string())
+                .collect(),
+            after: lines
+                .iter()
+                .skip(index + 1)
+                .take(context)
+                .map(|l| l.to_string())
+                .collect(),
+            matches: vec![range],
+        });
+    }
+    results
+}
+#[tauri::command]
+pub fn search_in_files(
+    path: String,
+    query: String,
+    options: Option<SearchOptions>,
+) -> Result<Vec<SearchResult>, FsError> {
+    let resolved = workspace::resolve(&path)?;
+    let options = options.unwrap_or_default();
+    if query.is_empty() {
+        return Ok(Vec::new());
+    }
+    let matcher = build_matcher(&query, &options)?;
+    let root = Path::new(&path);
+    let filter = GlobFilter::new(root, &options.include, &options.exclude)?;
+    let limit = options.max_results.unwrap_or(DEFAULT_MAX_RESULTS);
+    let files: Box<dyn Iterator<Item = PathBuf>> =
+        match trigram::candidates(&resolved, &query, &options) {
+            Some(relative) => Box::new(relative.into_iter().map(|r| root.join(r))),
+            None => Box::new(walker::walk_files(root)),
+        };
     let mut results = Vec::new();
-    let root_path = Path::new(&path);
-    for entry in WalkDir::new(root_path)
-        .follow_links(false)
-        .into_iter()
-        .filter_entry(|e| {
-            let name = e.file_name().to_string_lossy();
-            !name.starts_with('.') && name != "node_modules" && name != "target" && name != "dist"
+    for file in files.filter(|file| filter.matches(file)) {
+        results.extend(search_file(
+            &file,
+            &matcher,
+            &options,
+            limit - results.len(),
+        ));
+        if results.len() >= limit {
+            break;
+        }
+    }
+    Ok(results)
+}
+struct SearchJob {
+    root: PathBuf,
+    matcher: Regex,
+    filter: GlobFilter,
+    options: SearchOptions,
+    limit: usize,
+    // Files narrowed down by the trigram index; a full walk is used when there are none.
+    candidates: Option<Vec<PathBuf>>,
+    cancelled: Arc<AtomicBool>,
+    files_scanned: AtomicUsize,
+    // Matching lines found so far; `limit` caps lines, not individual matches.
+    line_count: AtomicUsize,
+}
+fn scan_file(job: &SearchJob, path: &Path, tx: &mpsc::Sender<Vec<SearchResult>>) -> bool {
+    if job.cancelled.load(Ordering::Relaxed) || job.line_count.load(Ordering::Relaxed) >= job.limit
+    {
+        return false;
+    }
+    if !job.filter.matches(path) {
+        return true;
+    }
+    let remaining = job
+        .limit
+        .saturating_sub(job.line_count.load(Ordering::Relaxed));
+    let results = search_file(path, &job.matcher, &job.options, remaining);
+    job.files_scanned.fetch_add(1, Ordering::Relaxed);
+    if !results.is_empty() {
+        job.line_count.fetch_add(results.len(), Ordering::Relaxed);
+        let _ = tx.send(results);
+    }
+    true
+}
+fn walk_parallel(job: Arc<SearchJob>, tx: mpsc::Sender<Vec<SearchResult>>) {
+    walker::walk_builder(&job.root).build_parallel().run(|| {
+        let job = job.clone();
+        let tx = tx.clone();
+        Box::new(move |entry| {
+            let entry = match entry {
+                Ok(entry) => entry,
+                Err(_) => return WalkState::Continue,
+            };
+            if !entry.file_type().is_some_and(|t| t.is_file()) {
+                return WalkState::Continue;
+            }
+            if scan_file(&job, entry.path(), &tx) {
+                WalkState::Continue
+            } else {
+                WalkState::Quit
+            }
         })
+    });
+}
+fn scan_candidates(job: Arc<SearchJob>, files: Vec<PathBuf>, tx: mpsc::Sender<Vec<SearchResult>>) {
+    let next = AtomicUsize::new(0);
+    let workers = thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(4);
+    thread::scope(|scope| {
+        for _ in 0..workers {
+            let (job, files, next, tx) = (&job, &files, &next, tx.clone());
+            scope.spawn(move || {
+                while let Some(file) = files.get(next.fetch_add(1, Ordering::Relaxed)) {
+                    if !scan_file(job, file, &tx) {
+                        break;
+                    }
+                }
+            });
+        }
+    });
+}
+fn run_search(window: Window, search_id: String, mut job: SearchJob) {
+    let (tx, rx) = mpsc::channel();
+    let candidates = job.candidates.take();
+    let job = Arc::new(job);
+    let walk_job = job.clone();
+    thread::spawn(move || match candidates {
+        Some(files) => scan_candidates(walk_job, files, tx),
+        None => walk_parallel(walk_job, tx),
+    });
+    let mut pending: Vec<SearchResult> = Vec::new();
+    let mut emitted = 0;
+    let mut matches_emitted = 0;
+    let mut last_emit = Instant::now();
+    let mut emit = |pending: &mut Vec<SearchResult>, done: bool| {
+        let cancelled = job.cancelled.load(Ordering::Relaxed);
+        // Parallel workers can overshoot the limit slightly, so trim here.
+        pending.truncate(job.limit.saturating_sub(emitted));
+        let results = if cancelled {
+            Vec::new()
+        } else {
+            std::mem::take(pending)
+        };
+        emitted += results.len();
+        matches_emitted += results.iter().map(|r| r.matches.len()).sum::<usize>();
+        let _ = window.emit(
+            "search-results",
+            SearchBatch {
+                search_id: search_id.clone(),
+                results,
+                files_scanned: job.files_scanned.load(Ordering::Relaxed),
+                match_count: matches_emitted,
+                done,
+                cancelled,
+            },
+        );
+    };
+    loop {
+        match rx.recv_timeout(BATCH_INTERVAL) {
+            Ok(results) => pending.extend(results),
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+        if job.cancelled.load(Ordering::Relaxed) {
+            break;
+        }
+        if last_emit.elapsed() >= BATCH_INTERVAL || pending.len() >= MAX_BATCH_SIZE {
+            emit(&mut pending, false);
+            last_emit = Instant::now();
+        }
+    }
+    emit(&mut pending, true);
+    // The id may have been reused by a newer search; only drop the entry if it is still this run's.
+    let mut searches = SEARCHES.lock().unwrap();
+    if searches
+        .get(&search_id)
+        .is_some_and(|s| Arc::ptr_eq(&s.cancelled, &job.cancelled))
+    {
+        searches.remove(&search_id);
+    }
+}
+#[tauri::command]
+pub fn start_search(
+    window: Window,
+    search_id: String,
+    path: String,
+    query: String,
+    options: Option<SearchOptions>,
+) -> Result<(), FsError> {
+    let resolved = workspace::resolve(&path)?;
+    let options = options.unwrap_or_default();
+    let matcher = build_matcher(&query, &options)?;
+    let root = PathBuf::from(&path);
+    let filter = GlobFilter::new(&root, &options.include, &options.exclude)?;
+    let cancelled = Arc::new(AtomicBool::new(false));
     {
-        let entry = match entry {
-            Ok(e) => e,
-            Err(_) => continue,
+        // A new query from the same window supersedes whatever it was still searching for.
+        let mut searches = SEARCHES.lock().unwrap();
+        for search in searches.values().filter(|s| s.window == window.label()) {
+            search.cancelled.store(true, Ordering::Relaxed);
+        }
+        searches.insert(
+            search_id.clone(),
+            ActiveSearch {
+                window: window.label().to_string(),
+                cancelled: cancelled.clone(),
+            },
+        );
+    }
+    let candidates = trigram::candidates(&resolved, &query, &options)
+        .map(|files| files.into_iter().map(|f| root.join(f)).collect());
+    let job = SearchJob {
+        root,
+        matcher,
+        filter,
+        limit: options.max_results.unwrap_or(DEFAULT_MAX_RESULTS),
+        candidates,
+        options,
+        cancelled,
+        files_scanned: AtomicUsize::new(0),
+        line_count: AtomicUsize::new(0),
+    };
+    if query.is_empty() {
+        job.cancelled.store(true, Ordering::Relaxed);
+    }
+    thread::spawn(move || run_search(window, search_id, job));
+    Ok(())
+}
+#[tauri::command]
+pub fn cancel_search(search_id: String) {
+    if let Some(search) = SEARCHES.lock().unwrap().get(&search_id) {
+        search.cancelled.store(true, Ordering::Relaxed);
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    fn whole_word(query: &str, regex: bool) -> Regex {
+        let options = SearchOptions {
+            whole_word: true,
+            regex,
+            ..Default::default()
         };
-        if !entry.file_type().is_file() {
-            continue;
+        build_matcher(query, &options).unwrap()
+    }
+    fn job(root: &Path, query: &str, options: SearchOptions) -> SearchJob {
+        SearchJob {
+            root: root.to_path_buf(),
+            matcher: build_matcher(query, &options).unwrap(),
+            filter: GlobFilter::new(root, &options.include, &options.exclude).unwrap(),
+            limit: options.max_results.unwrap_or(DEFAULT_MAX_RESULTS),
+            candidates: None,
+            options,
+            cancelled: Arc::new(AtomicBool::new(false)),
+            files_scanned: AtomicUsize::new(0),
+            line_count: AtomicUsize::new(0),
         }
-        let path_str = entry.path().to_string_lossy();
-        let relative_path = path_str.replace(&path, "").replace('\\', "/");
-        let clean_path = relative_path.trim_start_matches('/');
-        if let Some(score) = matcher.fuzzy_match(clean_path, &query) {
-            results.push(FileResult {
-                path: path_str.to_string(),
-                score,
-            });
+    }
+    fn collect(rx: mpsc::Receiver<Vec<SearchResult>>) -> Vec<SearchResult> {
+        rx.into_iter().flatten().collect()
+    }
+    fn project() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        for i in 0..20 {
+            fs::write(
+                dir.path().join(format!("f{}.txt", i)),
+                "needle\nhay\nneedle\n",
+            )
+            .unwrap();
         }
+        fs::write(dir.path().join("skip.md"), "needle").unwrap();
+        dir
+    }
+    #[test]
+    fn parallel_walks_find_every_filtered_match() {
+        let dir = project();
+        let options = SearchOptions {
+            include: vec!["*.txt".to_string()],
+            ..Default::default()
+        };
+        let job = Arc::new(job(dir.path(), "needle", options));
+        let (tx, rx) = mpsc::channel();
+        walk_parallel(job.clone(), tx);
+        let results = collect(rx);
+        assert_eq!(results.len(), 40);
+        assert!(results.iter().all(|r| r.file.ends_with(".txt")));
+        assert_eq!(job.files_scanned.load(Ordering::Relaxed), 20);
+    }
+    #[test]
+    fn walks_stop_at_the_limit_or_when_cancelled() {
+        let dir = project();
+        let options = SearchOptions {
+            max_results: Some(3),
+            ..Default::default()
+        };
+        let limited = Arc::new(job(dir.path(), "needle", options));
+        let (tx, rx) = mpsc::channel();
+        walk_parallel(limited.clone(), tx);
+        collect(rx);
+        assert!(limited.files_scanned.load(Ordering::Relaxed) < 21);
+        let cancelled = Arc::new(job(dir.path(), "needle", SearchOptions::default()));
+        cancelled.cancelled.store(true, Ordering::Relaxed);
+        let (tx, rx) = mpsc::channel();
+        walk_parallel(cancelled.clone(), tx);
+        assert!(collect(rx).is_empty());
+        assert_eq!(cancelled.files_scanned.load(Ordering::Relaxed), 0);
+    }
+    #[test]
+    fn index_candidates_are_the_only_files_scanned() {
+        let dir = project();
+        let job = Arc::new(job(dir.path(), "needle", SearchOptions::default()));
+        let files = vec![dir.path().join("f1.txt"), dir.path().join("skip.md")];
+        let (tx, rx) = mpsc::channel();
+        scan_candidates(job.clone(), files, tx);
+        let mut found: Vec<(String, usize)> = collect(rx)
+            .into_iter()
+            .map(|r| (r.file.rsplit('/').next().unwrap().to_string(), r.line))
+            .collect();
+        found.sort();
+        assert_eq!(
+            found,
+            vec![
+                ("f1.txt".to_string(), 1),
+                ("f1.txt".to_string(), 3),
+                ("skip.md".to_string(), 1)
+            ]
+        );
+    }
+    #[test]
+    fn matches_report_utf16_columns_and_context() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("a.txt");
+        fs::write(&file, "one\n😀 needle needle\nthree\n").unwrap();
+        let options = SearchOptions {
+            context_lines: 1,
+            ..Default::default()
+        };
+        let matcher = build_matcher("needle", &options).unwrap();
+        let results = search_file(&file, &matcher, &options, 10);
+        assert_eq!(results.len(), 1);
+        let result = &results[0];
+        assert_eq!((result.line, result.matches.len()), (2, 2));
+        assert_eq!((result.matches[0].start, result.matches[0].end), (3, 9));
+        assert_eq!(result.before, vec!["one"]);
+        assert_eq!(result.after, vec!["three"]);
+    }
+    #[test]
+    fn file_queries_may_end_in_a_line_and_column() {
+        assert_eq!(
+            parse_location("main.rs:12:4"),
+            ("main.rs", Some(12), Some(4))
+        );
+        assert_eq!(parse_location("main.rs:12"), ("main.rs", Some(12), None));
+        assert_eq!(parse_location("main.rs:"), ("main.rs", None, None));
+        assert_eq!(parse_location("a:b"), ("a:b", None, None));
     }
-    results.sort_by(|a, b| b.score.cmp(&a.score));
-    Ok(results.into_iter().take(50).collect())
-}
\ No newline at end of file
+    #[test]
+    fn file_names_outrank_matches_spread_over_the_path() {
+        let matcher = SkimMatcherV2::default();
+        let (name_score, positions) = score_path(&matcher, "src/lib/main.rs", "main").unwrap();
+        let (path_score, _) = score_path(&matcher, "main/src/other.rs", "main").unwrap();
+        assert!(name_score > path_score);
+        assert_eq!(positions, vec![8, 9, 10, 11]);
+        let (shallow, _) = score_path(&matcher, "a/b.rs", "a/b").unwrap();
+        let (deep, _) = score_path(&matcher, "x/y/a/b.rs", "a/b").unwrap();
+        assert!(shallow > deep);
+    }
+    #[test]
+    fn recently_opened_files_rank_first_and_fill_an_empty_query() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("src")).unwrap();
+        fs::write(dir.path().join("src/config.rs"), "").unwrap();
+        fs::write(dir.path().join("src/conf.rs"), "").unwrap();
+        let root = workspace::open_for_test(dir.path());
+        let path = root.to_string_lossy().to_string();
+        let names = |query: &str| -> Vec<String> {
+            fuzzy_search_files(path.clone(), query.to_string(), None)
+                .unwrap()
+                .into_iter()
+                .map(|r| r.relative_path)
+                .collect()
+        };
+        assert_eq!(names("conf")[0], "src/conf.rs");
+        assert!(names("").is_empty());
+        let opened = root.join("src/config.rs").to_string_lossy().to_string();
+        frecency::record_file_open(opened).unwrap();
+        assert_eq!(names("conf")[0], "src/config.rs");
+        assert_eq!(names(""), vec!["src/config.rs"]);
+        let results = fuzzy_search_files(path.clone(), "config:3".to_string(), None).unwrap();
+        assert_eq!(results[0].line, Some(3));
+    }
+    #[test]
+    fn whole_word_allows_punctuation_at_the_edges() {
+        let matcher = whole_word("foo", false);
+        assert!(matcher.is_match("a foo b"));
+        assert!(matcher.is_match("foo.bar"));
+        assert!(!matcher.is_match("foobar"));
+        assert!(!matcher.is_match("barfoo"));
+        let matcher = whole_word("foo(", false);
+        assert!(matcher.is_match("x foo(1)"));
+        assert!(matcher.is_match("foo(1)"));
+        assert!(!matcher.is_match("barfoo(1)"));
+        let matcher = whole_word("-flag", false);
+        assert!(matcher.is_match("cmd -flag"));
+        assert!(!matcher.is_match("cmd -flags"));
+        let matcher = whole_word(r"\w+\(", true);
+        assert_eq!(matcher.find("  call(x)").unwrap().as_str(), "call(");
+        assert!(whole_word("a|b", true).is_match("x b y"));
+    }
+}
@@ -0,0 +1,326 @@
+use crate::error::FsError;
+use crate::filesystem::{content_hash, write_private};
+use crate::models::SearchOptions;
+use crate::search::{self, FileResult};
+use crate::storage;
+use crate::structural::{self, StructuralMatch, StructuralQuery};
+use crate::symbols::{self, SymbolMatch};
+use crate::workspace;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::Window;
+const MAX_RECENT: usize = 50;
+lazy_static::lazy_static! {
+    static ref SEARCH_HISTORY_LOCK: Mutex<()> = Mutex::new(());
+}
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchKind {
+    Text,
+    Structural,
+    Files,
+    Symbols,
+}
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SearchHistoryEntry {
+    pub id: String,
+    pub kind: SearchKind,
+    pub query: String,
+    pub options: Option<SearchOptions>,
+    pub structural: Option<StructuralQuery>,
+    // Named entries are saved searches; they are always pinned.
+    pub name: Option<String>,
+    pub pinned: bool,
+    pub last_used: i64,
+    pub use_count: u32,
+}
+#[derive(Serialize, Debug, Clone)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum RerunOutput {
+    Text { search_id: String },
+    Structural { results: Vec<StructuralMatch> },
+    Files { results: Vec<FileResult> },
+    Symbols { results: Vec<SymbolMatch> },
+}
+fn history_path(root: &Path) -> PathBuf {
+    storage::data_dir().join("search-history").join(format!(
+        "{}.json",
+        content_hash(root.to_string_lossy().as_bytes())
+    ))
+}
+fn workspace_root(resolved: &Path) -> PathBuf {
+    workspace::root_for(resolved).unwrap_or_else(|| resolved.to_path_buf())
+}
+fn load(root: &Path) -> Vec<SearchHistoryEntry> {
+    fs::read(history_path(root))
+        .ok()
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        .unwrap_or_default()
+}
+fn save(root: &Path, entries: &[SearchHistoryEntry]) -> Result<(), FsError> {
+    let target = history_path(root);
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let bytes = serde_json::to_vec(entries).map_err(|e| e.to_string())?;
+    write_private(&target, &bytes)?;
+    Ok(())
+}
+fn update<T>(
+    resolved: &Path,
+    change: impl FnOnce(&mut Vec<SearchHistoryEntry>) -> Result<T, FsError>,
+) -> Result<T, FsError> {
+    let root = workspace_root(resolved);
+    let _guard = SEARCH_HISTORY_LOCK.lock().unwrap();
+    let mut entries = load(&root);
+    let value = change(&mut entries)?;
+    let mut recent = 0;
+    entries.retain(|entry| {
+        if entry.pinned {
+            return true;
+        }
+        recent += 1;
+        recent <= MAX_RECENT
+    });
+    save(&root, &entries)?;
+    Ok(value)
+}
+fn find(entries: &[SearchHistoryEntry], id: &str) -> Result<usize, FsError> {
+    entries
+        .iter()
+        .position(|entry| entry.id == id)
+        .ok_or_else(|| format!("Search {} not found", id).into())
+}
+pub fn record(
+    resolved: &Path,
+    kind: SearchKind,
+    query: &str,
+    options: Option<&SearchOptions>,
+    structural: Option<&StructuralQuery>,
+) {
+    if query.trim().is_empty() {
+        return;
+    }
+    // The same query with the same options always maps to the same entry.
+    let key = serde_json::to_vec(&(kind, query, options, structural)).unwrap_or_default();
+    let id = content_hash(&key);
+    let result = update(resolved, |entries| {
+        let mut entry = match entries.iter().position(|e| e.id == id) {
+            Some(index) => entries.remove(index),
+            None => SearchHistoryEntry {
+                id,
+                kind,
+                query: query.to_string(),
+                options: options.cloned(),
+                structural: structural.cloned(),
+                name: None,
+                pinned: false,
+                last_used: 0,
+                use_count: 0,
+            },
+        };
+        entry.last_used = Utc::now().timestamp_millis();
+        entry.use_count += 1;
+        entries.insert(0, entry);
+        Ok(())
+    });
+    if let Err(e) = result {
+        eprintln!("Failed to record search history for {:?}: {}", resolved, e);
+    }
+}
+#[tauri::command]
+pub fn record_search(
+    path: String,
+    kind: SearchKind,
+    query: String,
+    options: Option<SearchOptions>,
+    structural: Option<StructuralQuery>,
+) -> Result<(), FsError> {
+    let resolved = workspace::resolve(&path)?;
+    record(
+        &resolved,
+        kind,
+        &query,
+        options.as_ref(),
+        structural.as_ref(),
+    );
+    Ok(())
+}
+#[tauri::command]
+pub fn list_search_history(
+    path: String,
+    kind: Option<SearchKind>,
+) -> Result<Vec<SearchHistoryEntry>, FsError> {
+    let resolved = workspace::resolve(&path)?;
+    let mut entries: Vec<SearchHistoryEntry> = load(&workspace_root(&resolved))
+        .into_iter()
+        .filter(|entry| kind.map_or(true, |kind| entry.kind == kind))
+        .collect();
+    entries.sort_by(|a, b| {
+        b.pinned
+            .cmp(&a.pinned)
+            .then_with(|| b.last_used.cmp(&a.last_used))
+    });
+    Ok(entries)
+}
+#[tauri::command]
+pub fn pin_search(path: String, id: String, pinned: bool) -> Result<SearchHistoryEntry, FsError> {
+    let resolved = workspace::resolve(&path)?;
+    update(&resolved, |entries| {
+        let index = find(entries, &id)?;
+        entries[index].pinned = pinned;
+        if !pinned {
+            entries[index].name = None;
+        }
+        Ok(entries[index].clone())
+    })
+}
+#[tauri::command]
+pub fn save_search(path: String, id: String, name: String) -> Result<SearchHistoryEntry, FsError> {
+    let resolved = workspace::resolve(&path)?;
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err("Saved searches need a name".to_string().into());
+    }
+    update(&resolved, |entries| {
+        let index = find(entries, &id)?;
+        entries[index].name = Some(name);
+        entries[index].pinned = true;
+        Ok(entries[index].clone())
+    })
+}
+#[tauri::command]
+pub fn delete_search(path: String, id: String) -> Result<(), FsError> {
+    let resolved = workspace::resolve(&path)?;
+    update(&resolved, |entries| {
+        let index = find(entries, &id)?;
+        entries.remove(index);
+        Ok(())
+    })
+}
+#[tauri::command]
+pub fn clear_search_history(path: String) -> Result<(), FsError> {
+    let resolved = workspace::resolve(&path)?;
+    update(&resolved, |entries| {
+        entries.retain(|entry| entry.pinned);
+        Ok(())
+    })
+}
+#[tauri::command]
+pub async fn rerun_search(
+    window: Window,
+    path: String,
+    id: String,
+    search_id: Option<String>,
+) -> Result<RerunOutput, FsError> {
+    let resolved = workspace::resolve(&path)?;
+    let entry = load(&workspace_root(&resolved))
+        .into_iter()
+        .find(|entry| entry.id == id)
+        .ok_or_else(|| format!("Search {} not found", id))?;
+    match entry.kind {
+        SearchKind::Text => {
+            record(
+                &resolved,
+                entry.kind,
+                &entry.query,
+                entry.options.as_ref(),
+                None,
+            );
+            let search_id = search_id.unwrap_or_else(|| format!("rerun-{}", entry.id));
+            search::start_search(window, search_id.clone(), path, entry.query, entry.options)?;
+            Ok(RerunOutput::Text { search_id })
+        }
+        SearchKind::Structural => {
+            let query = entry.structural.unwrap_or_else(|| StructuralQuery {
+                pattern: entry.query,
+                ..Default::default()
+            });
+            record(
+                &resolved,
+                entry.kind,
+                &query.pattern,
+                entry.options.as_ref(),
+                Some(&query),
+            );
+            let results = structural::structural_search(path, query, entry.options).await?;
+            Ok(RerunOutput::Structural { results })
+        }
+        SearchKind::Files => {
+            record(&resolved, entry.kind, &entry.query, None, None);
+            // Walking the workspace for file names is blocking IO.
+            let results = tauri::async_runtime::spawn_blocking(move || {
+                search::fuzzy_search_files(path, entry.query, None)
+            })
+            .await
+            .map_err(|e| e.to_string())??;
+            Ok(RerunOutput::Files { results })
+        }
+        SearchKind::Symbols => {
+            record(&resolved, entry.kind, &entry.query, None, None);
+            let results = symbols::fuzzy_search_symbols(path, entry.query, None).await?;
+            Ok(RerunOutput::Symbols { results })
+        }
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    fn queries(path: &str, kind: Option<SearchKind>) -> Vec<String> {
+        list_search_history(path.to_string(), kind)
+            .unwrap()
+            .into_iter()
+            .map(|e| e.query)
+            .collect()
+    }
+    #[test]
+    fn repeated_queries_share_an_entry_per_options() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = workspace::open_for_test(dir.path());
+        let path = root.to_string_lossy().to_string();
+        let regex = SearchOptions {
+            regex: true,
+            ..Default::default()
+        };
+        record(&root, SearchKind::Text, "fn main", None, None);
+        record(&root, SearchKind::Text, "fn main", None, None);
+        record(&root, SearchKind::Text, "fn main", Some(&regex), None);
+        record(&root, SearchKind::Files, "main.rs", None, None);
+        record(&root, SearchKind::Text, "  ", None, None);
+        let entries = list_search_history(path.clone(), Some(SearchKind::Text)).unwrap();
+        assert_eq!(entries.len(), 2);
+        let plain = entries.iter().find(|e| e.options.is_none()).unwrap();
+        assert_eq!(plain.use_count, 2);
+        assert_eq!(queries(&path, Some(SearchKind::Files)), vec!["main.rs"]);
+        assert_eq!(queries(&path, None).len(), 3);
+    }
+    #[test]
+    fn saved_searches_survive_trimming_and_clearing() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = workspace::open_for_test(dir.path());
+        let path = root.to_string_lossy().to_string();
+        record(&root, SearchKind::Text, "keep me", None, None);
+        let id = list_search_history(path.clone(), None).unwrap()[0]
+            .id
+            .clone();
+        assert!(save_search(path.clone(), id.clone(), " ".to_string()).is_err());
+        let saved = save_search(path.clone(), id.clone(), "Favourite".to_string()).unwrap();
+        assert!(saved.pinned);
+        for i in 0..MAX_RECENT + 5 {
+            record(&root, SearchKind::Text, &format!("query {}", i), None, None);
+        }
+        let listed = queries(&path, None);
+        assert_eq!(listed.len(), MAX_RECENT + 1);
+        assert_eq!(listed[0], "keep me");
+        clear_search_history(path.clone()).unwrap();
+        assert_eq!(queries(&path, None), vec!["keep me"]);
+        let unpinned = pin_search(path.clone(), id.clone(), false).unwrap();
+        assert_eq!(unpinned.name, None);
+        delete_search(path.clone(), id.clone()).unwrap();
+        assert!(queries(&path, None).is_empty());
+        assert!(delete_search(path, id).is_err());
+    }
+}
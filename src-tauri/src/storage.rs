@@ -0,0 +1,40 @@
+use std::env;
+use std::path::PathBuf;
+use std::sync::Mutex;
+lazy_static::lazy_static! {
+    static ref DATA_DIR: Mutex<Option<PathBuf>> = Mutex::new(None);
+}
+pub fn home_dir() -> PathBuf {
+    env::var_os("HOME")
+        .or_else(|| env::var_os("USERPROFILE"))
+        .map(PathBuf::from)
+        .unwrap_or_else(env::temp_dir)
+}
+// User-edited settings and plugins; nothing the editor generates itself.
+pub fn mide_home() -> PathBuf {
+    home_dir().join(".mide")
+}
+pub fn plugin_dir() -> PathBuf {
+    mide_home().join("plugins")
+}
+// Called once from setup with the app data dir.
+pub fn init(app_data_dir: PathBuf) {
+    *DATA_DIR.lock().unwrap() = Some(app_data_dir);
+}
+// The one root for persisted editor state: history, backups, indexes, sessions and trust.
+pub fn data_dir() -> PathBuf {
+    DATA_DIR
+        .lock()
+        .unwrap()
+        .clone()
+        .unwrap_or_else(default_data_dir)
+}
+#[cfg(not(test))]
+fn default_data_dir() -> PathBuf {
+    mide_home()
+}
+// Tests never write history, backups or indexes into the real ~/.mide.
+#[cfg(test)]
+fn default_data_dir() -> PathBuf {
+    env::temp_dir().join(format!("mide-test-data-{}", std::process::id()))
+}
@@ -0,0 +1,556 @@
+use crate::encoding;
+use crate::error::FsError;
+use crate::filesystem::file_version;
+use crate::models::{MatchRange, SearchOptions, SearchResult};
+use crate::replace::{self, PlannedChange, ReplaceOutcome, ReplaceTarget};
+use crate::symbols::{self, SourceLanguage};
+use crate::walker::{self, GlobFilter};
+use crate::workspace;
+use regex::{Captures, Regex};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use tree_sitter::{Node, Tree};
+const DEFAULT_MAX_RESULTS: usize = 1000;
+const SINGLE_PREFIX: &str = "__MIDE_VAR_";
+const MULTI_PREFIX: &str = "__MIDE_MULTI_";
+lazy_static::lazy_static! {
+    static ref METAVARIABLE: Regex = Regex::new(r"\$\$\$([A-Z_][A-Z0-9_]*)?|\$([A-Z_][A-Z0-9_]*)").unwrap();
+}
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct StructuralQuery {
+    pub pattern: String,
+    pub language: Option<String>,
+    pub rewrite: Option<String>,
+    // Only report matches that have an ancestor matching this pattern.
+    pub inside: Option<String>,
+}
+#[derive(Serialize, Debug, Clone)]
+pub struct Capture {
+    pub text: String,
+    pub range: MatchRange,
+}
+#[derive(Serialize, Debug, Clone)]
+pub struct StructuralMatch {
+    #[serde(flatten)]
+    pub result: SearchResult,
+    pub captures: HashMap<String, Capture>,
+    pub replacement: Option<String>,
+}
+type Bindings = HashMap<String, Capture>;
+struct Metavariable {
+    name: String,
+    multi: bool,
+}
+struct Pattern {
+    source: String,
+    tree: Tree,
+    start: usize,
+    end: usize,
+}
+struct CompiledQuery {
+    pattern: Pattern,
+    inside: Option<Pattern>,
+}
+struct FoundMatch {
+    start: usize,
+    end: usize,
+    range: MatchRange,
+    captures: Bindings,
+}
+// Rust only accepts items inside modules and impls, so sequences also get a macro form there.
+fn multi_suffixes(language: SourceLanguage) -> &'static [&'static str] {
+    match language {
+        SourceLanguage::Rust => &["", "!{}"],
+        _ => &[""],
+    }
+}
+fn wrappers(language: SourceLanguage) -> &'static [(&'static str, &'static str)] {
+    match language {
+        SourceLanguage::Rust => &[
+            ("", ""),
+            ("fn __mide_wrapper() { ", " }"),
+            ("impl __MideWrapper { ", " }"),
+        ],
+        SourceLanguage::Go => &[
+            ("package __mide\n", ""),
+            ("package __mide\nfunc __mide_wrapper() { ", " }"),
+        ],
+        SourceLanguage::Python => &[("", ""), ("class __MideWrapper:\n    ", "")],
+        _ => &[("", ""), ("class __MideWrapper { ", " }")],
+    }
+}
+impl Pattern {
+    // Parses the pattern on its own first, then inside wrappers that make statements,
+    // members and items valid in the language.
+    fn compile(text: &str, language: SourceLanguage) -> Option<Self> {
+        multi_suffixes(language).iter().find_map(|multi_suffix| {
+            let substituted = METAVARIABLE.replace_all(text.trim(), |caps: &Captures| {
+                match (caps.get(1), caps.get(2)) {
+                    (_, Some(name)) => format!("{}{}", SINGLE_PREFIX, name.as_str()),
+                    (name, _) => format!(
+                        "{}{}{}",
+                        MULTI_PREFIX,
+                        name.map_or("", |n| n.as_str()),
+                        multi_suffix
+                    ),
+                }
+            });
+            wrappers(language).iter().find_map(|(prefix, suffix)| {
+                Self::parse_wrapped(&substituted, prefix, suffix, language)
+            })
+        })
+    }
+    fn parse_wrapped(
+        substituted: &str,
+        prefix: &str,
+        suffix: &str,
+        language: SourceLanguage,
+    ) -> Option<Self> {
+        let source = format!("{}{}{}", prefix, substituted, suffix);
+        let tree =
+            symbols::parse(language, &source).filter(|tree| !tree.root_node().has_error())?;
+        let start = prefix.len();
+        let core = start + substituted.trim_end_matches(';').trim_end().len();
+        let node = tree.root_node().descendant_for_byte_range(start, core)?;
+        if node.start_byte() != start
+            || node.end_byte() < core
+            || node.end_byte() > start + substituted.len()
+        {
+            return None;
+        }
+        let end = node.end_byte();
+        Some(Pattern {
+            source,
+            tree,
+            start,
+            end,
+        })
+    }
+    fn root(&self) -> Node<'_> {
+        self.tree
+            .root_node()
+            .descendant_for_byte_range(self.start, self.end)
+            .unwrap()
+    }
+}
+impl CompiledQuery {
+    fn new(query: &StructuralQuery, language: SourceLanguage) -> Option<Self> {
+        let inside = match &query.inside {
+            Some(inside) if !inside.trim().is_empty() => Some(Pattern::compile(inside, language)?),
+            _ => None,
+        };
+        Some(CompiledQuery {
+            pattern: Pattern::compile(&query.pattern, language)?,
+            inside,
+        })
+    }
+}
+fn children(node: Node) -> Vec<Node> {
+    let mut cursor = node.walk();
+    node.children(&mut cursor)
+        .filter(|child| !child.is_extra())
+        .collect()
+}
+struct Matcher<'a> {
+    pattern: &'a str,
+    source: &'a str,
+}
+impl<'a> Matcher<'a> {
+    fn metavariable(&self, node: Node) -> Option<Metavariable> {
+        let text = &self.pattern[node.byte_range()];
+        let (name, multi) = match text.trim_end_matches("!{}").strip_prefix(MULTI_PREFIX) {
+            Some(name) => (name, true),
+            None => (text.strip_prefix(SINGLE_PREFIX)?, false),
+        };
+        name.chars()
+            .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_')
+            .then(|| Metavariable {
+                name: name.to_string(),
+                multi,
+            })
+    }
+    fn bind(&self, name: &str, nodes: &[Node], bindings: &mut Bindings) -> bool {
+        if name.is_empty() || name == "_" {
+            return true;
+        }
+        let text = match (nodes.first(), nodes.last()) {
+            (Some(first), Some(last)) => &self.source[first.start_byte()..last.end_byte()],
+            _ => "",
+        };
+        if let Some(bound) = bindings.get(name) {
+            return bound.text == text;
+        }
+        let range = match (nodes.first(), nodes.last()) {
+            (Some(first), Some(last)) => {
+                let start = symbols::node_range(self.source, *first);
+                let end = symbols::node_range(self.source, *last);
+                MatchRange {
+                    line: start.line,
+                    start: start.start,
+                    end_line: end.end_line,
+                    end: end.end,
+                }
+            }
+            _ => MatchRange {
+                line: 0,
+                start: 0,
+                end_line: 0,
+                end: 0,
+            },
+        };
+        bindings.insert(
+            name.to_string(),
+            Capture {
+                text: text.to_string(),
+                range,
+            },
+        );
+        true
+    }
+    fn node(&self, pattern: Node, code: Node, bindings: &mut Bindings) -> bool {
+        if let Some(metavariable) = self.metavariable(pattern) {
+            return self.bind(&metavariable.name, &[code], bindings);
+        }
+        if pattern.kind() != code.kind() {
+            return false;
+        }
+        if pattern.child_count() == 0 || code.child_count() == 0 {
+            return self.pattern[pattern.byte_range()] == self.source[code.byte_range()];
+        }
+        self.sequence(&children(pattern), &children(code), bindings)
+    }
+    fn sequence(&self, patterns: &[Node], code: &[Node], bindings: &mut Bindings) -> bool {
+        let (first, rest) = match patterns.split_first() {
+            Some(split) => split,
+            None => return code.is_empty(),
+        };
+        if let Some(metavariable) = self.metavariable(*first).filter(|m| m.multi) {
+            // Try the shortest run first so later siblings in the pattern still get a chance.
+            for taken in 0..=code.len() {
+                let mut attempt = bindings.clone();
+                if self.bind(&metavariable.name, &code[..taken], &mut attempt)
+                    && self.sequence(rest, &code[taken..], &mut attempt)
+                {
+                    *bindings = attempt;
+                    return true;
+                }
+            }
+            return false;
+        }
+        match code.split_first() {
+            Some((head, tail)) => {
+                let mut attempt = bindings.clone();
+                if self.node(*first, *head, &mut attempt) && self.sequence(rest, tail, &mut attempt)
+                {
+                    *bindings = attempt;
+                    return true;
+                }
+                false
+            }
+            None => false,
+        }
+    }
+}
+fn has_ancestor(inside: &Pattern, node: Node, source: &str) -> bool {
+    let matcher = Matcher {
+        pattern: &inside.source,
+        source,
+    };
+    let root = inside.root();
+    let mut current = node.parent();
+    while let Some(ancestor) = current {
+        if matcher.node(root, ancestor, &mut Bindings::new()) {
+            return true;
+        }
+        current = ancestor.parent();
+    }
+    false
+}
+fn find_matches(query: &CompiledQuery, language: SourceLanguage, source: &str) -> Vec<FoundMatch> {
+    let tree = match symbols::parse(language, source) {
+        Some(tree) => tree,
+        None => return Vec::new(),
+    };
+    let matcher = Matcher {
+        pattern: &query.pattern.source,
+        source,
+    };
+    let pattern = query.pattern.root();
+    let mut found = Vec::new();
+    let mut stack = vec![tree.root_node()];
+    while let Some(node) = stack.pop() {
+        let mut bindings = Bindings::new();
+        if matcher.node(pattern, node, &mut bindings)
+            && query
+                .inside
+                .as_ref()
+                .map_or(true, |inside| has_ancestor(inside, node, source))
+        {
+            // Matched nodes are not searched further so rewrites never overlap.
+            found.push(FoundMatch {
+                start: node.start_byte(),
+                end: node.end_byte(),
+                range: symbols::node_range(source, node),
+                captures: bindings,
+            });
+            continue;
+        }
+        let mut cursor = node.walk();
+        let mut nested: Vec<Node> = node.children(&mut cursor).collect();
+        nested.reverse();
+        stack.extend(nested);
+    }
+    found
+}
+fn expand(template: &str, captures: &Bindings) -> String {
+    METAVARIABLE
+        .replace_all(template, |caps: &Captures| {
+            let name = caps
+                .get(2)
+                .or_else(|| caps.get(1))
+                .map(|m| m.as_str())
+                .unwrap_or("");
+            match captures.get(name) {
+                Some(capture) => capture.text.clone(),
+                None => caps[0].to_string(),
+            }
+        })
+        .to_string()
+}
+// Splices the expanded template over every match; matches never overlap.
+fn rewrite(
+    query: &CompiledQuery,
+    language: SourceLanguage,
+    source: &str,
+    template: &str,
+) -> (String, usize) {
+    let found = find_matches(query, language, source);
+    let mut out = String::with_capacity(source.len());
+    let mut last = 0;
+    for found in &found {
+        out.push_str(&source[last..found.start]);
+        out.push_str(&expand(template, &found.captures));
+        last = found.end;
+    }
+    out.push_str(&source[last..]);
+    (out, found.len())
+}
+fn compile_all(query: &StructuralQuery) -> Result<Vec<(SourceLanguage, CompiledQuery)>, String> {
+    if query.pattern.trim().is_empty() {
+        return Err("Structural pattern is empty".to_string());
+    }
+    let languages: Vec<SourceLanguage> = match &query.language {
+        Some(name) => vec![SourceLanguage::from_name(name)
+            .ok_or_else(|| format!("Structural search does not support {}", name))?],
+        None => SourceLanguage::ALL.to_vec(),
+    };
+    let compiled: Vec<(SourceLanguage, CompiledQuery)> = languages
+        .into_iter()
+        .filter_map(|language| CompiledQuery::new(query, language).map(|q| (language, q)))
+        .collect();
+    if compiled.is_empty() {
+        return Err("Pattern could not be parsed as a single code node".to_string());
+    }
+    Ok(compiled)
+}
+fn read_source(path: &Path) -> Option<String> {
+    let decoded = encoding::decode(&fs::read(path).ok()?);
+    (!decoded.is_binary).then_some(decoded.content)
+}
+// Walking and parsing the whole workspace is slow, so both commands run on a blocking thread.
+#[tauri::command]
+pub async fn structural_search(
+    path: String,
+    query: StructuralQuery,
+    options: Option<SearchOptions>,
+) -> Result<Vec<StructuralMatch>, FsError> {
+    workspace::resolve(&path)?;
+    tauri::async_runtime::spawn_blocking(move || {
+        find_all(Path::new(&path), &query, options.unwrap_or_default())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+fn find_all(
+    root: &Path,
+    query: &StructuralQuery,
+    options: SearchOptions,
+) -> Result<Vec<StructuralMatch>, FsError> {
+    let compiled = compile_all(query)?;
+    let filter = GlobFilter::new(root, &options.include, &options.exclude)?;
+    let limit = options.max_results.unwrap_or(DEFAULT_MAX_RESULTS);
+    let context = options.context_lines;
+    let mut results = Vec::new();
+    for file in walker::walk_files(root).filter(|file| filter.matches(file)) {
+        let language = match SourceLanguage::from_path(&file) {
+            Some(language) => language,
+            None => continue,
+        };
+        let compiled = match compiled.iter().find(|(l, _)| *l == language) {
+            Some((_, compiled)) => compiled,
+            None => continue,
+        };
+        if let (Some(max), Ok(metadata)) = (options.max_file_size, fs::metadata(&file)) {
+            if metadata.len() > max {
+                continue;
+            }
+        }
+        let source = match read_source(&file) {
+            Some(source) => source,
+            None => continue,
+        };
+        let lines: Vec<&str> = source.lines().collect();
+        for found in find_matches(compiled, language, &source) {
+            let index = found.range.line - 1;
+            results.push(StructuralMatch {
+                result: SearchResult {
+                    file: file.to_string_lossy().to_string(),
+                    line: found.range.line,
+                    content: lines.get(index).unwrap_or(&"").to_string(),
+                    before: lines[index.saturating_sub(context)..index.min(lines.len())]
+                        .iter()
+                        .map(|l| l.to_string())
+                        .collect(),
+                    after: lines
+                        .iter()
+                        .skip(index + 1)
+                        .take(context)
+                        .map(|l| l.to_string())
+                        .collect(),
+                    matches: vec![found.range],
+                },
+                replacement: query
+                    .rewrite
+                    .as_ref()
+                    .map(|template| expand(template, &found.captures)),
+                captures: found.captures,
+            });
+            if results.len() >= limit {
+                return Ok(results);
+            }
+        }
+    }
+    Ok(results)
+}
+#[tauri::command]
+pub async fn apply_structural_rewrite(
+    query: StructuralQuery,
+    files: Vec<ReplaceTarget>,
+) -> Result<ReplaceOutcome, FsError> {
+    tauri::async_runtime::spawn_blocking(move || rewrite_files(&query, &files))
+        .await
+        .map_err(|e| e.to_string())?
+}
+fn rewrite_files(
+    query: &StructuralQuery,
+    files: &[ReplaceTarget],
+) -> Result<ReplaceOutcome, FsError> {
+    let template = query
+        .rewrite
+        .clone()
+        .ok_or_else(|| "A rewrite template is required".to_string())?;
+    let compiled = compile_all(query)?;
+    let mut changes = Vec::new();
+    for target in files {
+        let resolved = workspace::resolve(&target.file)?;
+        let language = match SourceLanguage::from_path(&resolved) {
+            Some(language) => language,
+            None => continue,
+        };
+        let compiled = match compiled.iter().find(|(l, _)| *l == language) {
+            Some((_, compiled)) => compiled,
+            None => continue,
+        };
+        let original = fs::read(&resolved)?;
+        let metadata = fs::metadata(&resolved)?;
+        let decoded = encoding::decode(&original);
+        if decoded.is_binary {
+            continue;
+        }
+        let (new_text, replacements) = rewrite(compiled, language, &decoded.content, &template);
+        if replacements == 0 || new_text == decoded.content {
+            continue;
+        }
+        let change = PlannedChange {
+            path: resolved.clone(),
+            version: file_version(&original, &metadata),
+            updated: encoding::encode(&new_text, decoded.encoding, decoded.has_bom)?,
+            original,
+            old_text: decoded.content,
+            new_text,
+            replacements,
+        };
+        replace::ensure_version(target, &resolved, &change)?;
+        changes.push((target.file.clone(), change));
+    }
+    replace::commit(changes)
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    fn compile(pattern: &str, inside: Option<&str>) -> CompiledQuery {
+        let query = StructuralQuery {
+            pattern: pattern.to_string(),
+            inside: inside.map(str::to_string),
+            ..Default::default()
+        };
+        CompiledQuery::new(&query, SourceLanguage::Rust).unwrap()
+    }
+    fn matched(pattern: &str, source: &str) -> Vec<String> {
+        find_matches(&compile(pattern, None), SourceLanguage::Rust, source)
+            .into_iter()
+            .map(|found| source[found.start..found.end].to_string())
+            .collect()
+    }
+    #[test]
+    fn metavariables_bind_whole_nodes_and_runs() {
+        let source = "fn main() { let v = a.b().unwrap(); call(1, x + 2, y); }";
+        let found = find_matches(&compile("$X.unwrap()", None), SourceLanguage::Rust, source);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].captures["X"].text, "a.b()");
+        let found = find_matches(
+            &compile("call($$$ARGS)", None),
+            SourceLanguage::Rust,
+            source,
+        );
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].captures["ARGS"].text, "1, x + 2, y");
+        assert!(matched("other($$$ARGS)", source).is_empty());
+    }
+    #[test]
+    fn repeated_metavariables_must_capture_the_same_text() {
+        let source = "fn main() { let a = x + x; let b = x + y; let c = f(y) + f(y); }";
+        assert_eq!(matched("$A + $A", source), vec!["x + x", "f(y) + f(y)"]);
+        assert_eq!(matched("$_ + $_", source).len(), 3);
+    }
+    #[test]
+    fn inside_limits_matches_to_enclosing_nodes() {
+        let source = "fn a() { x.unwrap(); } fn b() { loop { y.unwrap(); } }";
+        let query = compile("$X.unwrap()", Some("loop { $$$ }"));
+        let found = find_matches(&query, SourceLanguage::Rust, source);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].captures["X"].text, "y");
+    }
+    #[test]
+    fn rewrites_expand_captures_and_keep_the_rest() {
+        let source = "fn main() {\n    let v = load(path).unwrap();\n    keep();\n}\n";
+        let query = compile("$X.unwrap()", None);
+        let (out, count) = rewrite(&query, SourceLanguage::Rust, source, "$X?");
+        assert_eq!(count, 1);
+        assert_eq!(
+            out,
+            "fn main() {\n    let v = load(path)?;\n    keep();\n}\n"
+        );
+        let query = compile("call($$$ARGS)", None);
+        let (out, _) = rewrite(
+            &query,
+            SourceLanguage::Rust,
+            "fn f() { call(a, b); }",
+            "invoke($ARGS, $MISSING)",
+        );
+        assert_eq!(out, "fn f() { invoke(a, b, $MISSING); }");
+    }
+}
@@ -0,0 +1,634 @@
+use crate::encoding;
+use crate::error::FsError;
+use crate::file_index::FileIndex;
+use crate::models::MatchRange;
+use crate::watcher::FsChange;
+use crate::workspace;
+use fuzzy_matcher::skim::SkimMatcherV2;
+use fuzzy_matcher::FuzzyMatcher;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::thread;
+use streaming_iterator::StreamingIterator;
+use tree_sitter::{Language, Node, Parser, Query, QueryCursor, Tree};
+const MAX_PARSE_SIZE: u64 = 1024 * 1024;
+const DEFAULT_SYMBOL_LIMIT: usize = 100;
+const RUST_QUERY: &str = r#"
+(function_item name: (identifier) @name) @definition.function
+(function_signature_item name: (identifier) @name) @definition.function
+(struct_item name: (type_identifier) @name) @definition.struct
+(enum_item name: (type_identifier) @name) @definition.enum
+(enum_variant name: (identifier) @name) @definition.variant
+(union_item name: (type_identifier) @name) @definition.struct
+(trait_item name: (type_identifier) @name) @definition.trait
+(impl_item type: (_) @name) @definition.impl
+(mod_item name: (identifier) @name) @definition.module
+(const_item name: (identifier) @name) @definition.constant
+(static_item name: (identifier) @name) @definition.constant
+(type_item name: (type_identifier) @name) @definition.type
+(macro_definition name: (identifier) @name) @definition.macro
+(field_declaration name: (field_identifier) @name) @definition.field
+"#;
+const TYPESCRIPT_QUERY: &str = r#"
+(function_declaration name: (identifier) @name) @definition.function
+(generator_function_declaration name: (identifier) @name) @definition.function
+(function_signature name: (identifier) @name) @definition.function
+(class_declaration name: (type_identifier) @name) @definition.class
+(abstract_class_declaration name: (type_identifier) @name) @definition.class
+(method_definition name: (_) @name) @definition.method
+(abstract_method_signature name: (_) @name) @definition.method
+(method_signature name: (_) @name) @definition.method
+(public_field_definition name: (_) @name) @definition.field
+(property_signature name: (_) @name) @definition.field
+(interface_declaration name: (type_identifier) @name) @definition.interface
+(type_alias_declaration name: (type_identifier) @name) @definition.type
+(enum_declaration name: (identifier) @name) @definition.enum
+(internal_module name: (_) @name) @definition.module
+(variable_declarator name: (identifier) @name value: [(arrow_function) (function_expression)]) @definition.function
+"#;
+const JAVASCRIPT_QUERY: &str = r#"
+(function_declaration name: (identifier) @name) @definition.function
+(generator_function_declaration name: (identifier) @name) @definition.function
+(class_declaration name: (identifier) @name) @definition.class
+(method_definition name: (_) @name) @definition.method
+(field_definition property: (_) @name) @definition.field
+(variable_declarator name: (identifier) @name value: [(arrow_function) (function_expression)]) @definition.function
+"#;
+const PYTHON_QUERY: &str = r#"
+(function_definition name: (identifier) @name) @definition.function
+(class_definition name: (identifier) @name) @definition.class
+"#;
+const GO_QUERY: &str = r#"
+(function_declaration name: (identifier) @name) @definition.function
+(method_declaration receiver: (_) @receiver name: (field_identifier) @name) @definition.method
+(type_spec name: (type_identifier) @name type: (_) @type) @definition.type
+(const_spec name: (identifier) @name) @definition.constant
+(field_declaration name: (field_identifier) @name) @definition.field
+(method_elem name: (field_identifier) @name) @definition.method
+"#;
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum SourceLanguage {
+    Rust,
+    TypeScript,
+    Tsx,
+    JavaScript,
+    Python,
+    Go,
+}
+impl SourceLanguage {
+    pub const ALL: [SourceLanguage; 6] = [
+        SourceLanguage::Rust,
+        SourceLanguage::TypeScript,
+        SourceLanguage::Tsx,
+        SourceLanguage::JavaScript,
+        SourceLanguage::Python,
+        SourceLanguage::Go,
+    ];
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_lowercase();
+        match extension.as_str() {
+            "rs" => Some(SourceLanguage::Rust),
+            "ts" | "mts" | "cts" => Some(SourceLanguage::TypeScript),
+            "tsx" => Some(SourceLanguage::Tsx),
+            "js" | "jsx" | "mjs" | "cjs" => Some(SourceLanguage::JavaScript),
+            "py" | "pyi" => Some(SourceLanguage::Python),
+            "go" => Some(SourceLanguage::Go),
+            _ => None,
+        }
+    }
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "rust" | "rs" => Some(SourceLanguage::Rust),
+            "typescript" | "ts" => Some(SourceLanguage::TypeScript),
+            "tsx" => Some(SourceLanguage::Tsx),
+            "javascript" | "js" | "jsx" => Some(SourceLanguage::JavaScript),
+            "python" | "py" => Some(SourceLanguage::Python),
+            "go" | "golang" => Some(SourceLanguage::Go),
+            _ => None,
+        }
+    }
+    pub fn grammar(self) -> Language {
+        match self {
+            SourceLanguage::Rust => tree_sitter_rust::LANGUAGE.into(),
+            SourceLanguage::TypeScript => tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into(),
+            SourceLanguage::Tsx => tree_sitter_typescript::LANGUAGE_TSX.into(),
+            SourceLanguage::JavaScript => tree_sitter_javascript::LANGUAGE.into(),
+            SourceLanguage::Python => tree_sitter_python::LANGUAGE.into(),
+            SourceLanguage::Go => tree_sitter_go::LANGUAGE.into(),
+        }
+    }
+    fn symbol_query(self) -> &'static Query {
+        &SYMBOL_QUERIES[&self]
+    }
+}
+lazy_static::lazy_static! {
+    static ref SYMBOL_QUERIES: HashMap<SourceLanguage, Query> = [
+        (SourceLanguage::Rust, RUST_QUERY),
+        (SourceLanguage::TypeScript, TYPESCRIPT_QUERY),
+        (SourceLanguage::Tsx, TYPESCRIPT_QUERY),
+        (SourceLanguage::JavaScript, JAVASCRIPT_QUERY),
+        (SourceLanguage::Python, PYTHON_QUERY),
+        (SourceLanguage::Go, GO_QUERY),
+    ]
+    .into_iter()
+    .map(|(language, source)| {
+        let query = Query::new(&language.grammar(), source).expect("invalid symbol query");
+        (language, query)
+    })
+    .collect();
+    static ref INDEX: FileIndex<(), Vec<Symbol>> = FileIndex::default();
+}
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SymbolKind {
+    Function,
+    Method,
+    Class,
+    Struct,
+    Enum,
+    Variant,
+    Interface,
+    Trait,
+    Impl,
+    Module,
+    Constant,
+    Type,
+    Field,
+    Macro,
+}
+impl SymbolKind {
+    fn from_capture(name: &str) -> Option<Self> {
+        Some(match name {
+            "function" => SymbolKind::Function,
+            "method" => SymbolKind::Method,
+            "class" => SymbolKind::Class,
+            "struct" => SymbolKind::Struct,
+            "enum" => SymbolKind::Enum,
+            "variant" => SymbolKind::Variant,
+            "interface" => SymbolKind::Interface,
+            "trait" => SymbolKind::Trait,
+            "impl" => SymbolKind::Impl,
+            "module" => SymbolKind::Module,
+            "constant" => SymbolKind::Constant,
+            "type" => SymbolKind::Type,
+            "field" => SymbolKind::Field,
+            "macro" => SymbolKind::Macro,
+            _ => return None,
+        })
+    }
+    fn holds_methods(self) -> bool {
+        matches!(
+            self,
+            SymbolKind::Impl | SymbolKind::Trait | SymbolKind::Class | SymbolKind::Interface
+        )
+    }
+}
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Symbol {
+    pub name: String,
+    pub kind: SymbolKind,
+    pub container: Option<String>,
+    pub path: String,
+    pub range: MatchRange,
+    pub selection_range: MatchRange,
+}
+#[derive(Serialize, Debug, Clone)]
+pub struct SymbolMatch {
+    #[serde(flatten)]
+    pub symbol: Symbol,
+    pub score: i64,
+    pub positions: Vec<usize>,
+}
+#[derive(Serialize, Debug, Clone)]
+pub struct OutlineNode {
+    #[serde(flatten)]
+    pub symbol: Symbol,
+    pub children: Vec<OutlineNode>,
+}
+pub(crate) fn parse(language: SourceLanguage, source: &str) -> Option<Tree> {
+    let mut parser = Parser::new();
+    parser.set_language(&language.grammar()).ok()?;
+    parser.parse(source, None)
+}
+fn utf16_column(source: &str, byte: usize, column: usize) -> usize {
+    source
+        .get(byte - column..byte)
+        .map(|prefix| prefix.encode_utf16().count())
+        .unwrap_or(column)
+}
+pub(crate) fn node_range(source: &str, node: Node) -> MatchRange {
+    let start = node.start_position();
+    let end = node.end_position();
+    MatchRange {
+        line: start.row + 1,
+        start: utf16_column(source, node.start_byte(), start.column),
+        end_line: end.row + 1,
+        end: utf16_column(source, node.end_byte(), end.column),
+    }
+}
+fn receiver_type(text: &str) -> Option<String> {
+    let inner = text.trim_start_matches('(').trim_end_matches(')');
+    let name = inner.split_whitespace().last()?.trim_start_matches('*');
+    let name = name.split('[').next().unwrap_or(name);
+    (!name.is_empty()).then(|| name.to_string())
+}
+// Returns symbols in source order, each paired with the index of its enclosing symbol.
+fn extract(path: &Path, language: SourceLanguage, source: &str) -> Vec<(Symbol, Option<usize>)> {
+    let tree = match parse(language, source) {
+        Some(tree) => tree,
+        None => return Vec::new(),
+    };
+    let query = language.symbol_query();
+    let names = query.capture_names();
+    let text = |node: Node| source[node.byte_range()].to_string();
+    let mut definitions: Vec<(Node, Node, SymbolKind, Option<String>)> = Vec::new();
+    let mut seen = HashSet::new();
+    let mut cursor = QueryCursor::new();
+    let mut matches = cursor.matches(query, tree.root_node(), source.as_bytes());
+    while let Some(m) = matches.next() {
+        let mut definition = None;
+        let mut name = None;
+        let mut receiver = None;
+        let mut type_kind = None;
+        for capture in m.captures {
+            match names[capture.index as usize] {
+                "name" => name = Some(capture.node),
+                "receiver" => receiver = receiver_type(&text(capture.node)),
+                "type" => {
+                    type_kind = match capture.node.kind() {
+                        "struct_type" => Some(SymbolKind::Struct),
+                        "interface_type" => Some(SymbolKind::Interface),
+                        _ => None,
+                    }
+                }
+                other => {
+                    if let Some(kind) = other
+                        .strip_prefix("definition.")
+                        .and_then(SymbolKind::from_capture)
+                    {
+                        definition = Some((capture.node, kind));
+                    }
+                }
+            }
+        }
+        if let (Some((node, kind)), Some(name)) = (definition, name) {
+            let kind = type_kind.unwrap_or(kind);
+            if seen.insert(node.id()) {
+                definitions.push((node, name, kind, receiver));
+            }
+        }
+    }
+    definitions.sort_by_key(|(node, ..)| (node.start_byte(), std::cmp::Reverse(node.end_byte())));
+    let mut symbols: Vec<(Symbol, Option<usize>)> = Vec::with_capacity(definitions.len());
+    let mut stack: Vec<(usize, usize)> = Vec::new();
+    for (node, name, mut kind, receiver) in definitions {
+        while stack
+            .last()
+            .is_some_and(|(end, _)| *end <= node.start_byte())
+        {
+            stack.pop();
+        }
+        let parent = stack.last().map(|(_, index)| *index);
+        let parent_symbol = parent.map(|index| &symbols[index].0);
+        if kind == SymbolKind::Function && parent_symbol.is_some_and(|p| p.kind.holds_methods()) {
+            kind = SymbolKind::Method;
+        }
+        let container = receiver.or_else(|| parent_symbol.map(|p| p.name.clone()));
+        stack.push((node.end_byte(), symbols.len()));
+        symbols.push((
+            Symbol {
+                name: text(name),
+                kind,
+                container,
+                path: path.to_string_lossy().to_string(),
+                range: node_range(source, node),
+                selection_range: node_range(source, name),
+            },
+            parent,
+        ));
+    }
+    symbols
+}
+fn build_outline(entries: Vec<(Symbol, Option<usize>)>) -> Vec<OutlineNode> {
+    let (symbols, parents): (Vec<Symbol>, Vec<Option<usize>>) = entries.into_iter().unzip();
+    let mut nodes: Vec<Option<OutlineNode>> = symbols
+        .into_iter()
+        .map(|symbol| {
+            Some(OutlineNode {
+                symbol,
+                children: Vec::new(),
+            })
+        })
+        .collect();
+    // Parents always come before their children, so folding from the back completes each node first.
+    let mut roots = Vec::new();
+    for i in (0..nodes.len()).rev() {
+        let node = nodes[i].take().unwrap();
+        match parents[i] {
+            Some(parent) => nodes[parent].as_mut().unwrap().children.insert(0, node),
+            None => roots.insert(0, node),
+        }
+    }
+    roots
+}
+fn file_symbols(path: &Path) -> Option<Vec<Symbol>> {
+    let language = SourceLanguage::from_path(path)?;
+    if fs::metadata(path).ok()?.len() > MAX_PARSE_SIZE {
+        return Some(Vec::new());
+    }
+    let decoded = encoding::decode(&fs::read(path).ok()?);
+    if decoded.is_binary {
+        return Some(Vec::new());
+    }
+    Some(
+        extract(path, language, &decoded.content)
+            .into_iter()
+            .map(|(symbol, _)| symbol)
+            .collect(),
+    )
+}
+fn index_files(_: &(), paths: &[PathBuf]) -> Vec<(PathBuf, Vec<Symbol>)> {
+    let workers = thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(4);
+    let chunk_size = paths.len().div_ceil(workers).max(1);
+    thread::scope(|scope| {
+        let handles: Vec<_> = paths
+            .chunks(chunk_size)
+            .map(|chunk| {
+                scope.spawn(move || {
+                    chunk
+                        .iter()
+                        .filter_map(|path| {
+                            file_symbols(path).map(|symbols| (path.clone(), symbols))
+                        })
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().unwrap_or_default())
+            .collect()
+    })
+}
+fn is_source(path: &Path) -> bool {
+    SourceLanguage::from_path(path).is_some()
+}
+pub fn unload(root: &Path) {
+    INDEX.unload(root);
+}
+pub fn apply_changes(root: &Path, changes: &[FsChange]) {
+    INDEX.apply_changes(root, changes, is_source, index_files);
+}
+// The first query for a root parses every source file, so it runs on a blocking thread.
+#[tauri::command]
+pub async fn fuzzy_search_symbols(
+    path: String,
+    query: String,
+    limit: Option<usize>,
+) -> Result<Vec<SymbolMatch>, FsError> {
+    let root = workspace::resolve(&path)?;
+    let query: String = query.chars().filter(|c| !c.is_whitespace()).collect();
+    if query.is_empty() {
+        return Ok(Vec::new());
+    }
+    tauri::async_runtime::spawn_blocking(move || search_symbols(&root, &query, limit))
+        .await
+        .map_err(|e| e.to_string().into())
+}
+fn search_symbols(root: &Path, query: &str, limit: Option<usize>) -> Vec<SymbolMatch> {
+    INDEX.ensure(root, &(), is_source, index_files);
+    let matcher = SkimMatcherV2::default();
+    let lowered = query.to_lowercase();
+    let mut results: Vec<SymbolMatch> = INDEX.with(root, |files| {
+        files
+            .flat_map(|(_, symbols)| symbols.iter())
+            .filter_map(|symbol| {
+                let (mut score, positions) = matcher.fuzzy_indices(&symbol.name, query)?;
+                let name = symbol.name.to_lowercase();
+                if name == lowered {
+                    score += 100;
+                } else if name.starts_with(&lowered) {
+                    score += 50;
+                }
+                Some(SymbolMatch {
+                    symbol: symbol.clone(),
+                    score,
+                    positions,
+                })
+            })
+            .collect()
+    });
+    results.sort_by(|a, b| {
+        b.score
+            .cmp(&a.score)
+            .then_with(|| a.symbol.name.len().cmp(&b.symbol.name.len()))
+            .then_with(|| a.symbol.path.cmp(&b.symbol.path))
+    });
+    results.truncate(limit.unwrap_or(DEFAULT_SYMBOL_LIMIT));
+    results
+}
+#[tauri::command]
+pub fn document_outline(
+    path: String,
+    content: Option<String>,
+) -> Result<Vec<OutlineNode>, FsError> {
+    let resolved = workspace::resolve(&path)?;
+    let language = match SourceLanguage::from_path(&resolved) {
+        Some(language) => language,
+        None => return Ok(Vec::new()),
+    };
+    let source = match content {
+        Some(content) => content,
+        None => {
+            let decoded = encoding::decode(&fs::read(&resolved)?);
+            if decoded.is_binary {
+                return Ok(Vec::new());
+            }
+            decoded.content
+        }
+    };
+    Ok(build_outline(extract(Path::new(&path), language, &source)))
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn symbol_search_ranks_exact_and_prefix_names_first() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = fs::canonicalize(dir.path()).unwrap();
+        fs::write(
+            root.join("lib.rs"),
+            "struct Config;\nfn config_path() {}\nfn load_user_config() {}\n",
+        )
+        .unwrap();
+        fs::write(root.join("notes.txt"), "fn config() {}\n").unwrap();
+        let names: Vec<String> = search_symbols(&root, "config", None)
+            .into_iter()
+            .map(|m| m.symbol.name)
+            .collect();
+        assert_eq!(names, vec!["Config", "config_path", "load_user_config"]);
+        assert_eq!(search_symbols(&root, "config", Some(1)).len(), 1);
+        unload(&root);
+    }
+    fn symbols(name: &str, source: &str) -> Vec<(String, SymbolKind, Option<String>)> {
+        let path = Path::new(name);
+        extract(path, SourceLanguage::from_path(path).unwrap(), source)
+            .into_iter()
+            .map(|(symbol, _)| (symbol.name, symbol.kind, symbol.container))
+            .collect()
+    }
+    fn symbol(
+        name: &str,
+        kind: SymbolKind,
+        container: Option<&str>,
+    ) -> (String, SymbolKind, Option<String>) {
+        (name.to_string(), kind, container.map(str::to_string))
+    }
+    // Flattens the outline to one indented name per line, so nesting shows in the assertion.
+    fn outline(name: &str, source: &str) -> Vec<String> {
+        fn walk(nodes: &[OutlineNode], depth: usize, out: &mut Vec<String>) {
+            for node in nodes {
+                out.push(format!("{}{}", "  ".repeat(depth), node.symbol.name));
+                walk(&node.children, depth + 1, out);
+            }
+        }
+        let dir = tempfile::tempdir().unwrap();
+        let root = workspace::open_for_test(dir.path());
+        let path = root.join(name).to_string_lossy().to_string();
+        let nodes = document_outline(path, Some(source.to_string())).unwrap();
+        let mut out = Vec::new();
+        walk(&nodes, 0, &mut out);
+        out
+    }
+    const TYPESCRIPT: &str = "interface Shape {\n  area(): number;\n  name: string;\n}\nclass Circle implements Shape {\n  radius = 1;\n  area() { return 3; }\n}\nenum Color { Red }\ntype Id = string;\nfunction make(): Shape { return new Circle(); }\nconst helper = () => 1;\nnamespace Geo { export function origin() {} }\n";
+    const TSX: &str = "function App() { return <div />; }\nconst Button = () => <button />;\nclass Panel extends Component {\n  render() { return <section />; }\n}\n";
+    const JAVASCRIPT: &str = "class Counter {\n  count = 0;\n  increment() {}\n}\nfunction* ids() {}\nconst reset = function () {};\n";
+    const PYTHON: &str = "class Stack:\n    def push(self, item):\n        pass\ndef main():\n    def helper():\n        pass\n";
+    const GO: &str = "package shapes\ntype Point struct {\n\tX int\n}\ntype Shape interface {\n\tArea() float64\n}\ntype ID string\nconst Origin = 0\nfunc (p *Point) Move(dx int) {}\nfunc (l List[T]) Len() int { return 0 }\nfunc New() Point { return Point{} }\n";
+    #[test]
+    fn every_symbol_query_compiles() {
+        for language in SourceLanguage::ALL {
+            assert!(
+                language.symbol_query().pattern_count() > 0,
+                "{:?}",
+                language
+            );
+        }
+    }
+    #[test]
+    fn extracts_typescript_symbols() {
+        assert_eq!(
+            symbols("shapes.ts", TYPESCRIPT),
+            vec![
+                symbol("Shape", SymbolKind::Interface, None),
+                symbol("area", SymbolKind::Method, Some("Shape")),
+                symbol("name", SymbolKind::Field, Some("Shape")),
+                symbol("Circle", SymbolKind::Class, None),
+                symbol("radius", SymbolKind::Field, Some("Circle")),
+                symbol("area", SymbolKind::Method, Some("Circle")),
+                symbol("Color", SymbolKind::Enum, None),
+                symbol("Id", SymbolKind::Type, None),
+                symbol("make", SymbolKind::Function, None),
+                symbol("helper", SymbolKind::Function, None),
+                symbol("Geo", SymbolKind::Module, None),
+                symbol("origin", SymbolKind::Function, Some("Geo")),
+            ]
+        );
+    }
+    #[test]
+    fn outlines_typescript() {
+        assert_eq!(
+            outline("shapes.ts", TYPESCRIPT),
+            vec![
+                "Shape", "  area", "  name", "Circle", "  radius", "  area", "Color", "Id", "make",
+                "helper", "Geo", "  origin",
+            ]
+        );
+    }
+    #[test]
+    fn extracts_tsx_symbols() {
+        assert_eq!(
+            symbols("app.tsx", TSX),
+            vec![
+                symbol("App", SymbolKind::Function, None),
+                symbol("Button", SymbolKind::Function, None),
+                symbol("Panel", SymbolKind::Class, None),
+                symbol("render", SymbolKind::Method, Some("Panel")),
+            ]
+        );
+    }
+    #[test]
+    fn outlines_tsx() {
+        assert_eq!(
+            outline("app.tsx", TSX),
+            vec!["App", "Button", "Panel", "  render"]
+        );
+    }
+    #[test]
+    fn extracts_javascript_symbols() {
+        assert_eq!(
+            symbols("counter.js", JAVASCRIPT),
+            vec![
+                symbol("Counter", SymbolKind::Class, None),
+                symbol("count", SymbolKind::Field, Some("Counter")),
+                symbol("increment", SymbolKind::Method, Some("Counter")),
+                symbol("ids", SymbolKind::Function, None),
+                symbol("reset", SymbolKind::Function, None),
+            ]
+        );
+    }
+    #[test]
+    fn outlines_javascript() {
+        assert_eq!(
+            outline("counter.js", JAVASCRIPT),
+            vec!["Counter", "  count", "  increment", "ids", "reset"]
+        );
+    }
+    #[test]
+    fn extracts_python_symbols() {
+        assert_eq!(
+            symbols("stack.py", PYTHON),
+            vec![
+                symbol("Stack", SymbolKind::Class, None),
+                symbol("push", SymbolKind::Method, Some("Stack")),
+                symbol("main", SymbolKind::Function, None),
+                symbol("helper", SymbolKind::Function, Some("main")),
+            ]
+        );
+    }
+    #[test]
+    fn outlines_python() {
+        assert_eq!(
+            outline("stack.py", PYTHON),
+            vec!["Stack", "  push", "main", "  helper"]
+        );
+    }
+    #[test]
+    fn extracts_go_symbols_with_receiver_containers() {
+        assert_eq!(
+            symbols("shapes.go", GO),
+            vec![
+                symbol("Point", SymbolKind::Struct, None),
+                symbol("X", SymbolKind::Field, Some("Point")),
+                symbol("Shape", SymbolKind::Interface, None),
+                symbol("Area", SymbolKind::Method, Some("Shape")),
+                symbol("ID", SymbolKind::Type, None),
+                symbol("Origin", SymbolKind::Constant, None),
+                symbol("Move", SymbolKind::Method, Some("Point")),
+                symbol("Len", SymbolKind::Method, Some("List")),
+                symbol("New", SymbolKind::Function, None),
+            ]
+        );
+    }
+    #[test]
+    fn outlines_go() {
+        assert_eq!(
+            outline("shapes.go", GO),
+            vec!["Point", "  X", "Shape", "  Area", "ID", "Origin", "Move", "Len", "New"]
+        );
+    }
+}
@@ -1,18 +1,164 @@
-use portable_pty::{CommandBuilder, NativePtySystem, PtySize, PtySystem};
+use crate::error::FsError;
+use crate::filesystem::{content_hash, write_private};
+use crate::project_settings;
+use crate::storage;
+use crate::walker;
+use crate::workspace;
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use chrono::Utc;
+use portable_pty::{ChildKiller, CommandBuilder, NativePtySystem, PtySize, PtySystem};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::env;
+use std::fs;
 use std::io::{Read, Write};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::sync::mpsc::{self, RecvTimeoutError};
 use std::sync::{Arc, Mutex};
 use std::thread;
+use std::time::{Duration, Instant};
 use tauri::{Emitter, Window};
+const MIN_READ_BUFFER: usize = 4 * 1024;
+const MAX_READ_BUFFER: usize = 64 * 1024;
+// Output arriving within this window of the first chunk is sent as one event.
+const COALESCE_WINDOW: Duration = Duration::from_millis(8);
+const MAX_BATCH: usize = 256 * 1024;
+// Output kept per session so a reloaded webview can pick up where it left off.
+const MAX_BACKLOG: usize = 512 * 1024;
 struct PtySession {
     master: Arc<Mutex<Box<dyn portable_pty::MasterPty + Send>>>,
     writer: Arc<Mutex<Box<dyn Write + Send>>>,
+    killer: Box<dyn ChildKiller + Send + Sync>,
+    output: Arc<Mutex<OutputLog>>,
+    // The last signal kill_pty sent, for shells that trap it and exit with a plain code.
+    signalled: Arc<Mutex<Option<String>>>,
+    info: PtySessionInfo,
+}
+#[derive(Default)]
+struct OutputLog {
+    bytes: VecDeque<u8>,
+    // Sequence number of the last chunk emitted.
+    seq: u64,
+}
+#[derive(Serialize, Debug, Clone)]
+pub struct PtySessionInfo {
+    pub id: String,
+    pub pid: Option<u32>,
+    pub shell: String,
+    pub profile: String,
+    pub cwd: Option<String>,
+    pub started_at: i64,
+    pub binary: bool,
+}
+// A session as it was when the app last shut down.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SavedTerminal {
+    pub id: String,
+    pub profile: String,
+    pub cwd: Option<String>,
+    pub started_at: i64,
+    pub saved_at: i64,
+    pub scrollback: String,
+}
+#[derive(Serialize, Debug, Clone)]
+pub struct PtyData {
+    pub seq: u64,
+    pub data: String,
+}
+#[derive(Serialize, Debug, Clone)]
+pub struct PtyExit {
+    pub code: u32,
+    pub signal: Option<String>,
+}
+#[derive(Serialize, Debug, Clone)]
+pub struct PtyAttachment {
+    #[serde(flatten)]
+    pub info: PtySessionInfo,
+    pub seq: u64,
+    pub backlog: String,
 }
 lazy_static::lazy_static! {
     static ref TERMINAL_SESSIONS: Mutex<HashMap<String, PtySession>> = Mutex::new(HashMap::new());
+    // Ids of terminals that passed the duplicate check but are not in TERMINAL_SESSIONS yet.
+    static ref STARTING: Mutex<HashSet<String>> = Mutex::new(HashSet::new());
+}
+// Holds a terminal id from the duplicate check until its session is inserted or the spawn fails.
+struct Reservation(String);
+impl Drop for Reservation {
+    fn drop(&mut self) {
+        STARTING.lock().unwrap().remove(&self.0);
+    }
+}
+fn reserve(id: &str) -> Result<Reservation, String> {
+    let sessions = TERMINAL_SESSIONS.lock().unwrap();
+    let mut starting = STARTING.lock().unwrap();
+    if sessions.contains_key(id) || !starting.insert(id.to_string()) {
+        return Err(format!("Terminal {} is already running", id));
+    }
+    Ok(Reservation(id.to_string()))
+}
+// portable-pty only reports a child's signal through its display text, as in "Terminated by Hangup".
+fn exit_signal(status: &portable_pty::ExitStatus) -> Option<String> {
+    status
+        .to_string()
+        .strip_prefix("Terminated by ")
+        .map(str::to_string)
+}
+// Decodes UTF-8 across reads, holding back a trailing partial sequence until its remaining bytes arrive.
+#[derive(Default)]
+struct Utf8Stream {
+    pending: Vec<u8>,
+}
+impl Utf8Stream {
+    fn decode(&mut self, bytes: &[u8]) -> String {
+        self.pending.extend_from_slice(bytes);
+        let mut text = String::with_capacity(self.pending.len());
+        let mut rest = self.pending.as_slice();
+        loop {
+            match std::str::from_utf8(rest) {
+                Ok(valid) => {
+                    text.push_str(valid);
+                    rest = &[];
+                    break;
+                }
+                Err(e) => {
+                    let (valid, after) = rest.split_at(e.valid_up_to());
+                    text.push_str(std::str::from_utf8(valid).unwrap_or_default());
+                    match e.error_len() {
+                        Some(len) => {
+                            text.push(char::REPLACEMENT_CHARACTER);
+                            rest = &after[len..];
+                        }
+                        None => {
+                            rest = after;
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+        let consumed = self.pending.len() - rest.len();
+        self.pending.drain(..consumed);
+        text
+    }
+    fn finish(&mut self) -> String {
+        let text = String::from_utf8_lossy(&self.pending).to_string();
+        self.pending.clear();
+        text
+    }
+}
+enum OutputEncoding {
+    Text(Utf8Stream),
+    Base64,
+}
+impl OutputEncoding {
+    fn encode(&mut self, bytes: &[u8]) -> String {
+        match self {
+            OutputEncoding::Text(stream) => stream.decode(bytes),
+            OutputEncoding::Base64 => STANDARD.encode(bytes),
+        }
+    }
 }
 #[derive(Debug, Serialize, Deserialize)]
 pub struct VirtualEnv {
@@ -20,6 +166,193 @@ pub struct VirtualEnv {
     #[serde(rename = "type")]
     env_type: String,
 }
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct TerminalProfile {
+    pub id: String,
+    pub name: String,
+    pub shell: String,
+    pub args: Vec<String>,
+    // A null value removes the variable from the inherited environment.
+    pub env: HashMap<String, Option<String>>,
+    pub cwd: Option<String>,
+    pub login: bool,
+}
+#[derive(Serialize, Debug, Clone)]
+pub struct TerminalProfiles {
+    pub profiles: Vec<TerminalProfile>,
+    pub default_profile: Option<String>,
+}
+fn shell_profile(shell: &Path) -> Option<TerminalProfile> {
+    let id = shell.file_stem()?.to_string_lossy().to_string();
+    Some(TerminalProfile {
+        name: id.clone(),
+        id,
+        shell: shell.to_string_lossy().to_string(),
+        ..Default::default()
+    })
+}
+fn find_in_path(program: &str) -> Option<PathBuf> {
+    env::split_paths(&env::var_os("PATH")?)
+        .map(|dir| dir.join(program))
+        .find(|candidate| candidate.is_file())
+}
+fn detect_shells() -> Vec<TerminalProfile> {
+    let candidates: Vec<PathBuf> = if cfg!(target_os = "windows") {
+        ["pwsh.exe", "powershell.exe", "cmd.exe"]
+            .iter()
+            .filter_map(|program| find_in_path(program))
+            .collect()
+    } else {
+        let listed = fs::read_to_string("/etc/shells").unwrap_or_default();
+        listed
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(PathBuf::from)
+            .filter(|shell| shell.is_file())
+            .chain(
+                ["bash", "zsh", "fish"]
+                    .iter()
+                    .filter_map(|program| find_in_path(program)),
+            )
+            .collect()
+    };
+    let mut profiles: Vec<TerminalProfile> = Vec::new();
+    // The same shell often appears under both /bin and /usr/bin.
+    for profile in candidates.iter().filter_map(|shell| shell_profile(shell)) {
+        if !profiles.iter().any(|p| p.id == profile.id) {
+            profiles.push(profile);
+        }
+    }
+    profiles
+}
+fn merge_profiles(profiles: &mut Vec<TerminalProfile>, configured: Vec<TerminalProfile>) {
+    for profile in configured.into_iter().filter(|p| !p.id.is_empty()) {
+        match profiles.iter_mut().find(|p| p.id == profile.id) {
+            Some(existing) => {
+                // Configured profiles may only override parts of a detected shell.
+                if !profile.shell.is_empty() {
+                    existing.shell = profile.shell;
+                }
+                if !profile.name.is_empty() {
+                    existing.name = profile.name;
+                }
+                if !profile.args.is_empty() {
+                    existing.args = profile.args;
+                }
+                existing.env.extend(profile.env);
+                existing.cwd = profile.cwd.or(existing.cwd.take());
+                existing.login = profile.login;
+            }
+            None if !profile.shell.is_empty() => profiles.push(TerminalProfile {
+                name: if profile.name.is_empty() {
+                    profile.id.clone()
+                } else {
+                    profile.name.clone()
+                },
+                ..profile
+            }),
+            None => {}
+        }
+    }
+}
+// The only variables a cloned repository may set; anything else could make the shell or the
+// tools it starts load code from the project.
+const SAFE_PROJECT_ENV: &[&str] = &[
+    "LANG",
+    "LANGUAGE",
+    "TZ",
+    "TERM",
+    "COLORTERM",
+    "NO_COLOR",
+    "FORCE_COLOR",
+    "CLICOLOR",
+    "CI",
+    "DEBUG",
+    "NODE_ENV",
+    "RUST_LOG",
+    "RUST_BACKTRACE",
+];
+const SAFE_PROJECT_ENV_PREFIXES: &[&str] = &["LC_"];
+fn is_safe_project_env(key: &str) -> bool {
+    SAFE_PROJECT_ENV.contains(&key)
+        || SAFE_PROJECT_ENV_PREFIXES
+            .iter()
+            .any(|prefix| key.starts_with(prefix))
+}
+// Detected shells, then the user's ~/.mide/settings.json, then the project's own settings.
+fn load_profiles(project: Option<&Path>) -> (TerminalProfiles, HashMap<String, Option<String>>) {
+    let mut profiles = detect_shells();
+    let user = project_settings::load(&storage::home_dir());
+    // Project settings come from the repository being edited, so they may only add plain env vars;
+    // shells, arguments and the default profile are taken from the user's own settings.
+    let project = project.map(project_settings::load).unwrap_or_default();
+    merge_profiles(&mut profiles, user.terminal_profiles);
+    let login_shell = env::var("SHELL")
+        .ok()
+        .and_then(|shell| shell_profile(Path::new(&shell)))
+        .map(|profile| profile.id);
+    let default_profile = [user.terminal_default_profile, login_shell]
+        .into_iter()
+        .flatten()
+        .find(|id| profiles.iter().any(|p| &p.id == id))
+        .or_else(|| profiles.first().map(|p| p.id.clone()));
+    let mut env = user.terminal_env;
+    for (key, value) in project.terminal_env {
+        if is_safe_project_env(&key) {
+            env.insert(key, value);
+        } else {
+            eprintln!("Ignoring {} from project terminal.env", key);
+        }
+    }
+    (
+        TerminalProfiles {
+            profiles,
+            default_profile,
+        },
+        env,
+    )
+}
+fn fallback_profile() -> TerminalProfile {
+    let shell = if cfg!(target_os = "windows") {
+        "powershell"
+    } else {
+        "bash"
+    };
+    TerminalProfile {
+        id: shell.to_string(),
+        name: shell.to_string(),
+        shell: shell.to_string(),
+        ..Default::default()
+    }
+}
+// A restored terminal may name a profile that has since been removed; it opens with the default instead.
+fn pick_profile(profiles: TerminalProfiles, wanted: Option<String>) -> TerminalProfile {
+    let find = |id: &str| profiles.profiles.iter().find(|p| p.id == id).cloned();
+    if let Some(wanted) = &wanted {
+        match find(wanted) {
+            Some(profile) => return profile,
+            None => eprintln!("Unknown terminal profile {}, using the default", wanted),
+        }
+    }
+    profiles
+        .default_profile
+        .as_deref()
+        .and_then(find)
+        .unwrap_or_else(fallback_profile)
+}
+fn expand_home(path: &str) -> PathBuf {
+    match path.strip_prefix('~') {
+        Some(rest) => storage::home_dir().join(rest.trim_start_matches(['/', '\\'])),
+        None => PathBuf::from(path),
+    }
+}
+#[tauri::command]
+pub fn list_terminal_profiles(cwd: Option<String>) -> Result<TerminalProfiles, String> {
+    let project = cwd.map(|dir| walker::find_project_root(Path::new(&dir)));
+    Ok(load_profiles(project.as_deref()).0)
+}
 #[tauri::command]
 pub fn spawn_pty(
     window: Window,
@@ -27,7 +360,16 @@ pub fn spawn_pty(
     rows: u16,
     cols: u16,
     cwd: Option<String>,
+    profile: Option<String>,
+    binary: Option<bool>,
 ) -> Result<(), String> {
+    let project = cwd
+        .as_ref()
+        .map(|dir| walker::find_project_root(Path::new(dir)));
+    let (profiles, project_env) = load_profiles(project.as_deref());
+    let profile = pick_profile(profiles, profile);
+    let reservation = reserve(&id)?;
+    let binary = binary.unwrap_or(false);
     let pty_system = NativePtySystem::default();
     let size = PtySize {
         rows,
@@ -36,22 +378,36 @@ pub fn spawn_pty(
         pixel_height: 0,
     };
     let pair = pty_system.openpty(size).map_err(|e| e.to_string())?;
-    let mut cmd_builder = CommandBuilder::new(if cfg!(target_os = "windows") {
-        "powershell"
-    } else {
-        "bash"
-    });
-    if let Some(dir) = cwd {
+    let mut cmd_builder = CommandBuilder::new(&profile.shell);
+    if profile.login && !cfg!(target_os = "windows") {
+        cmd_builder.arg("-l");
+    }
+    cmd_builder.args(&profile.args);
+    // A relative profile cwd is taken from the directory the terminal was opened in.
+    let dir = match (profile.cwd.as_deref().map(expand_home), cwd) {
+        (Some(dir), Some(base)) if dir.is_relative() => Some(Path::new(&base).join(dir)),
+        (Some(dir), _) => Some(dir),
+        (None, base) => base.map(PathBuf::from),
+    };
+    if let Some(dir) = &dir {
         cmd_builder.cwd(dir);
     }
     cmd_builder.env("TERM", "xterm-256color");
     cmd_builder.env("COLORTERM", "truecolor");
+    for (key, value) in profile.env.into_iter().chain(project_env) {
+        match value {
+            Some(value) => cmd_builder.env(key, value),
+            None => cmd_builder.env_remove(key),
+        }
+    }
     let mut child = pair.slave.spawn_command(cmd_builder).map_err(|e| e.to_string())?;
     let mut reader = pair.master.try_clone_reader().map_err(|e| e.to_string())?;
     let writer = pair.master.take_writer().map_err(|e| e.to_string())?;
     let master: Box<dyn portable_pty::MasterPty + Send> = pair.master;
     let master = Arc::new(Mutex::new(master));
     let writer = Arc::new(Mutex::new(writer));
+    let output_log = Arc::new(Mutex::new(OutputLog::default()));
+    let signalled = Arc::new(Mutex::new(None));
     {
         let mut sessions = TERMINAL_SESSIONS.lock().unwrap();
         sessions.insert(
@@ -59,28 +415,107 @@ pub fn spawn_pty(
             PtySession {
                 master: master.clone(),
                 writer: writer.clone(),
+                killer: child.clone_killer(),
+                output: output_log.clone(),
+                signalled: signalled.clone(),
+                info: PtySessionInfo {
+                    id: id.clone(),
+                    pid: child.process_id(),
+                    shell: profile.shell.clone(),
+                    profile: profile.id.clone(),
+                    cwd: dir.map(|d| d.to_string_lossy().to_string()),
+                    started_at: Utc::now().timestamp_millis(),
+                    binary,
+                },
             },
         );
     }
-    let window_clone = window.clone();
-    let id_clone = id.clone();
+    drop(reservation);
+    let (sender, receiver) = mpsc::channel::<Vec<u8>>();
     thread::spawn(move || {
-        let mut buffer = [0u8; 1024];
+        let mut buffer = vec![0u8; MIN_READ_BUFFER];
         loop {
             match reader.read(&mut buffer) {
                 Ok(n) if n > 0 => {
-                    let data = String::from_utf8_lossy(&buffer[..n]).to_string();
-                    let _ = window_clone.emit(&format!("term-data-{}", id_clone), data);
+                    if sender.send(buffer[..n].to_vec()).is_err() {
+                        break;
+                    }
+                    // A full read means output is streaming; read more per call.
+                    // Once it quiets down again, give the memory back.
+                    if n == buffer.len() && buffer.len() < MAX_READ_BUFFER {
+                        buffer.resize(buffer.len() * 2, 0);
+                    } else if n < buffer.len() / 4 && buffer.len() > MIN_READ_BUFFER {
+                        buffer.truncate(buffer.len() / 2);
+                        buffer.shrink_to_fit();
+                    }
                 }
                 Ok(_) => break,
                 Err(_) => break,
             }
         }
-        let _ = window_clone.emit(&format!("term-exit-{}", id_clone), 0);
+    });
+    let window_clone = window.clone();
+    let id_clone = id.clone();
+    let mut output = if binary {
+        OutputEncoding::Base64
+    } else {
+        OutputEncoding::Text(Utf8Stream::default())
+    };
+    thread::spawn(move || {
+        let event = format!("term-data-{}", id_clone);
+        while let Ok(mut batch) = receiver.recv() {
+            let deadline = Instant::now() + COALESCE_WINDOW;
+            while batch.len() < MAX_BATCH {
+                match receiver.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+                    Ok(more) => batch.extend_from_slice(&more),
+                    Err(RecvTimeoutError::Timeout) | Err(RecvTimeoutError::Disconnected) => break,
+                }
+            }
+            let data = output.encode(&batch);
+            // Holding the log while emitting keeps attach_pty's snapshot and sequence in step.
+            let mut log = output_log.lock().unwrap();
+            log.bytes.extend(batch);
+            let overflow = log.bytes.len().saturating_sub(MAX_BACKLOG);
+            log.bytes.drain(..overflow);
+            if !data.is_empty() {
+                log.seq += 1;
+                let _ = window_clone.emit(&event, PtyData { seq: log.seq, data });
+            }
+        }
+        if let OutputEncoding::Text(stream) = &mut output {
+            let rest = stream.finish();
+            if !rest.is_empty() {
+                let mut log = output_log.lock().unwrap();
+                log.seq += 1;
+                let _ = window_clone.emit(
+                    &event,
+                    PtyData {
+                        seq: log.seq,
+                        data: rest,
+                    },
+                );
+            }
+        }
+        let status = child.wait();
         if let Ok(mut sessions) = TERMINAL_SESSIONS.lock() {
             sessions.remove(&id_clone);
         }
-        let _ = child.wait();
+        let exit = match status {
+            Ok(status) => PtyExit {
+                code: status.exit_code(),
+                // Any other cause of death is reported without a signal.
+                signal: if status.success() {
+                    None
+                } else {
+                    exit_signal(&status).or_else(|| signalled.lock().unwrap().take())
+                },
+            },
+            Err(_) => PtyExit {
+                code: 1,
+                signal: None,
+            },
+        };
+        let _ = window_clone.emit(&format!("term-exit-{}", id_clone), exit);
     });
     Ok(())
 }
@@ -110,6 +545,151 @@ pub fn resize_pty(id: String, rows: u16, cols: u16) -> Result<(), String> {
     Ok(())
 }
 #[tauri::command]
+pub fn list_pty_sessions() -> Result<Vec<PtySessionInfo>, String> {
+    let sessions = TERMINAL_SESSIONS.lock().unwrap();
+    let mut infos: Vec<PtySessionInfo> = sessions.values().map(|s| s.info.clone()).collect();
+    infos.sort_by_key(|info| info.started_at);
+    Ok(infos)
+}
+#[tauri::command]
+pub fn attach_pty(id: String) -> Result<PtyAttachment, String> {
+    let sessions = TERMINAL_SESSIONS.lock().unwrap();
+    let session = sessions
+        .get(&id)
+        .ok_or_else(|| format!("Terminal {} is not running", id))?;
+    let log = session.output.lock().unwrap();
+    let backlog = if session.info.binary {
+        let (front, back) = log.bytes.as_slices();
+        STANDARD.encode([front, back].concat())
+    } else {
+        backlog_text(&log.bytes)
+    };
+    Ok(PtyAttachment {
+        info: session.info.clone(),
+        seq: log.seq,
+        backlog,
+    })
+}
+fn backlog_text(bytes: &VecDeque<u8>) -> String {
+    let (front, back) = bytes.as_slices();
+    let bytes = [front, back].concat();
+    // The backlog may start partway through a character after old output was dropped.
+    let start = bytes
+        .iter()
+        .position(|b| b & 0xC0 != 0x80)
+        .unwrap_or(bytes.len());
+    Utf8Stream::default().decode(&bytes[start..])
+}
+fn saved_sessions_path(root: &Path) -> PathBuf {
+    let name = format!("{}.json", content_hash(root.to_string_lossy().as_bytes()));
+    storage::data_dir().join("terminals").join(name)
+}
+// Terminals are saved with the workspace they were opened in, so another project does not pick them up.
+fn session_root(info: &PtySessionInfo) -> Option<PathBuf> {
+    let cwd = fs::canonicalize(info.cwd.as_ref()?).ok()?;
+    workspace::root_for(&cwd).or_else(|| Some(walker::find_project_root(&cwd)))
+}
+// The shell may have changed directory since it started; Linux exposes where it is now.
+fn current_cwd(info: &PtySessionInfo) -> Option<String> {
+    info.pid
+        .and_then(|pid| fs::read_link(format!("/proc/{}/cwd", pid)).ok())
+        .map(|dir| dir.to_string_lossy().to_string())
+        .or_else(|| info.cwd.clone())
+}
+pub fn persist_sessions() {
+    let saved_at = Utc::now().timestamp_millis();
+    let mut by_root: HashMap<PathBuf, Vec<SavedTerminal>> = HashMap::new();
+    for session in TERMINAL_SESSIONS.lock().unwrap().values() {
+        let root = match session_root(&session.info) {
+            Some(root) => root,
+            None => continue,
+        };
+        by_root.entry(root).or_default().push(SavedTerminal {
+            id: session.info.id.clone(),
+            profile: session.info.profile.clone(),
+            cwd: current_cwd(&session.info),
+            started_at: session.info.started_at,
+            saved_at,
+            scrollback: backlog_text(&session.output.lock().unwrap().bytes),
+        });
+    }
+    for (root, mut saved) in by_root {
+        saved.sort_by_key(|terminal| terminal.started_at);
+        let path = saved_sessions_path(&root);
+        let result = fs::create_dir_all(path.parent().unwrap()).and_then(|_| {
+            let bytes = serde_json::to_vec(&saved).unwrap_or_default();
+            write_private(&path, &bytes)
+        });
+        if let Err(e) = result {
+            eprintln!("Failed to save terminal sessions for {:?}: {}", root, e);
+        }
+    }
+}
+// Hands back the sessions saved at last shutdown, once; they are not restored twice.
+#[tauri::command]
+pub fn restore_terminal_sessions(workspace: String) -> Result<Vec<SavedTerminal>, FsError> {
+    let path = saved_sessions_path(&workspace::resolve(&workspace)?);
+    let saved = fs::read(&path)
+        .ok()
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        .unwrap_or_default();
+    let _ = fs::remove_file(&path);
+    Ok(saved)
+}
+#[cfg(unix)]
+fn signal_number(name: &str) -> Result<(i32, String), String> {
+    let upper = name.trim().to_uppercase();
+    let short = upper.strip_prefix("SIG").unwrap_or(&upper);
+    if let Ok(number) = short.parse() {
+        return Ok((number, format!("signal {}", number)));
+    }
+    let number = match short {
+        "HUP" => libc::SIGHUP,
+        "INT" => libc::SIGINT,
+        "QUIT" => libc::SIGQUIT,
+        "KILL" => libc::SIGKILL,
+        "TERM" => libc::SIGTERM,
+        "USR1" => libc::SIGUSR1,
+        "USR2" => libc::SIGUSR2,
+        "STOP" => libc::SIGSTOP,
+        "CONT" => libc::SIGCONT,
+        _ => return Err(format!("Unknown signal: {}", name)),
+    };
+    Ok((number, format!("SIG{}", short)))
+}
+#[tauri::command]
+pub fn kill_pty(id: String, signal: Option<String>) -> Result<(), String> {
+    let mut sessions = TERMINAL_SESSIONS.lock().unwrap();
+    let session = sessions
+        .get_mut(&id)
+        .ok_or_else(|| format!("Terminal {} is not running", id))?;
+    #[cfg(unix)]
+    {
+        if let Some(pid) = session.info.pid {
+            let (signal, name) = signal_number(signal.as_deref().unwrap_or("SIGHUP"))?;
+            let pid = pid as libc::pid_t;
+            // A foreground job runs in its own process group and would not see a signal sent to the shell.
+            let foreground = session
+                .master
+                .lock()
+                .ok()
+                .and_then(|master| master.process_group_leader())
+                .filter(|group| *group > 0 && *group != pid);
+            if let Some(group) = foreground {
+                unsafe { libc::kill(-group, signal) };
+            }
+            if unsafe { libc::kill(pid, signal) } != 0 {
+                return Err(std::io::Error::last_os_error().to_string());
+            }
+            *session.signalled.lock().unwrap() = Some(name);
+            return Ok(());
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = signal;
+    session.killer.kill().map_err(|e| e.to_string())
+}
+#[tauri::command]
 pub fn detect_virtual_environments(project_path: String) -> Result<Vec<VirtualEnv>, String> {
     let mut venvs = Vec::new();
     let project = Path::new(&project_path);
@@ -160,4 +740,132 @@ pub async fn execute_shell_command(
     } else {
         Err(String::from_utf8_lossy(&output.stderr).to_string())
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn utf8_sequences_split_across_reads_are_joined() {
+        let mut stream = Utf8Stream::default();
+        let bytes = "é😀".as_bytes();
+        assert_eq!(stream.decode(&bytes[..1]), "");
+        assert_eq!(stream.decode(&bytes[1..3]), "é");
+        assert_eq!(stream.decode(&bytes[3..5]), "");
+        assert_eq!(stream.decode(&bytes[5..]), "😀");
+        assert_eq!(stream.finish(), "");
+    }
+    #[test]
+    fn invalid_bytes_become_replacement_characters() {
+        let mut stream = Utf8Stream::default();
+        assert_eq!(stream.decode(b"a\xffb\xc3("), "a\u{FFFD}b\u{FFFD}(");
+        // A lead byte followed by another lead byte is invalid, not incomplete.
+        assert_eq!(stream.decode(b"\xe2\xe2\x82\xac"), "\u{FFFD}€");
+    }
+    #[test]
+    fn an_incomplete_sequence_at_eof_is_flushed_by_finish() {
+        let mut stream = Utf8Stream::default();
+        assert_eq!(stream.decode(b"ok\xe2\x82"), "ok");
+        assert_eq!(stream.finish(), "\u{FFFD}");
+        assert_eq!(stream.finish(), "");
+    }
+    #[test]
+    fn missing_profiles_fall_back_to_the_default() {
+        let profiles = || TerminalProfiles {
+            profiles: vec![
+                shell_profile(Path::new("/bin/bash")).unwrap(),
+                shell_profile(Path::new("/bin/zsh")).unwrap(),
+            ],
+            default_profile: Some("zsh".to_string()),
+        };
+        let pick = |wanted: Option<&str>| pick_profile(profiles(), wanted.map(str::to_string)).id;
+        assert_eq!(pick(Some("bash")), "bash");
+        assert_eq!(pick(Some("deleted")), "zsh");
+        assert_eq!(pick(None), "zsh");
+        let empty = TerminalProfiles {
+            profiles: Vec::new(),
+            default_profile: None,
+        };
+        assert_eq!(
+            pick_profile(empty, Some("deleted".to_string())).id,
+            fallback_profile().id
+        );
+    }
+    #[cfg(unix)]
+    #[test]
+    fn signals_are_named_consistently() {
+        assert_eq!(
+            signal_number("term").unwrap(),
+            (libc::SIGTERM, "SIGTERM".to_string())
+        );
+        assert_eq!(signal_number("SIGKILL").unwrap().1, "SIGKILL");
+        assert_eq!(signal_number("9").unwrap(), (9, "signal 9".to_string()));
+        assert!(signal_number("SIGNOPE").is_err());
+    }
+    #[test]
+    fn exit_signals_come_from_the_exit_status() {
+        use portable_pty::ExitStatus;
+        let killed = ExitStatus::with_signal("Hangup");
+        assert_eq!(exit_signal(&killed).as_deref(), Some("Hangup"));
+        assert_eq!(exit_signal(&ExitStatus::with_exit_code(129)), None);
+        assert_eq!(exit_signal(&ExitStatus::with_exit_code(0)), None);
+    }
+    #[test]
+    fn a_terminal_id_is_reserved_until_its_spawn_finishes() {
+        let reservation = reserve("reserved-test").unwrap();
+        assert!(reserve("reserved-test").is_err());
+        drop(reservation);
+        drop(reserve("reserved-test").unwrap());
+    }
+    #[test]
+    fn configured_profiles_override_detected_shells_field_by_field() {
+        let mut profiles = vec![shell_profile(Path::new("/bin/bash")).unwrap()];
+        let configured = vec![
+            TerminalProfile {
+                id: "bash".to_string(),
+                args: vec!["--norc".to_string()],
+                env: HashMap::from([("EDITOR".to_string(), Some("vi".to_string()))]),
+                login: true,
+                ..Default::default()
+            },
+            TerminalProfile {
+                id: "node".to_string(),
+                shell: "/usr/bin/node".to_string(),
+                ..Default::default()
+            },
+            TerminalProfile {
+                id: "no-shell".to_string(),
+                ..Default::default()
+            },
+        ];
+        merge_profiles(&mut profiles, configured);
+        assert_eq!(profiles.len(), 2);
+        assert_eq!(profiles[0].shell, "/bin/bash");
+        assert_eq!(profiles[0].args, vec!["--norc"]);
+        assert!(profiles[0].login);
+        assert_eq!(profiles[0].env["EDITOR"].as_deref(), Some("vi"));
+        assert_eq!(profiles[1].name, "node");
+        assert_eq!(expand_home("~/src"), storage::home_dir().join("src"));
+        assert_eq!(expand_home("/tmp"), PathBuf::from("/tmp"));
+    }
+    #[test]
+    fn project_env_allows_plain_variables() {
+        for key in ["RUST_LOG", "NODE_ENV", "LANG", "LC_ALL"] {
+            assert!(is_safe_project_env(key), "{}", key);
+        }
+    }
+    #[test]
+    fn project_env_rejects_anything_not_listed() {
+        for key in [
+            "PATH",
+            "LD_PRELOAD",
+            "BASH_ENV",
+            "XDG_CONFIG_HOME",
+            "PYTHONHOME",
+            "RUSTC_WRAPPER",
+            "npm_config_script_shell",
+            "rust_log",
+        ] {
+            assert!(!is_safe_project_env(key), "{}", key);
+        }
+    }
+}
@@ -1,100 +1,817 @@
+use crate::encoding;
+use crate::error::FsError;
+use crate::file_index::FileIndex;
+use crate::filesystem::file_version;
+use crate::gh;
+use crate::git;
+use crate::project_settings;
+use crate::replace::{self, PlannedChange};
+use crate::walker::GlobFilter;
+use crate::watcher::FsChange;
+use crate::workspace;
+use chrono::{TimeZone, Utc};
+use regex::Regex;
 use serde::{Deserialize, Serialize};
 use std::fs;
-use std::path::Path;
-use walkdir::WalkDir;
-#[derive(Serialize, Deserialize, Debug)]
+use std::path::{Path, PathBuf};
+use std::thread;
+lazy_static::lazy_static! {
+    // Items are only valid for the tag set they were extracted with, so the tags are the index key.
+    static ref INDEX: FileIndex<Vec<TodoTag>, Vec<TodoItem>> = FileIndex::default();
+}
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct TodoTag {
+    pub name: String,
+    // Lower numbers sort first.
+    #[serde(default)]
+    pub priority: u8,
+    pub color: Option<String>,
+}
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum TodoSort {
+    #[default]
+    Priority,
+    // Oldest first, so stale items surface at the top.
+    Age,
+    File,
+}
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct TodoQuery {
+    pub blame: bool,
+    pub tags: Vec<String>,
+    // Matched case-insensitively against the blame author and the (owner) annotation.
+    pub authors: Vec<String>,
+    pub include: Vec<String>,
+    pub exclude: Vec<String>,
+    pub min_age_days: Option<u64>,
+    pub max_age_days: Option<u64>,
+    pub sort: TodoSort,
+}
+impl TodoQuery {
+    fn needs_blame(&self) -> bool {
+        self.blame
+            || !self.authors.is_empty()
+            || self.min_age_days.is_some()
+            || self.max_age_days.is_some()
+            || self.sort == TodoSort::Age
+    }
+}
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct TodoItem {
     file: String,
     line: usize,
+    column: usize,
     #[serde(rename = "type")]
     todo_type: String,
     text: String,
+    owner: Option<String>,
+    issue: Option<String>,
+    priority: u8,
+    author: Option<String>,
+    commit: Option<String>,
+    committed_at: Option<i64>,
+}
+#[derive(Deserialize, Debug, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum TodoExportFormat {
+    Markdown,
+    Csv,
+}
+#[derive(Serialize, Debug, Clone)]
+pub struct PromotedTodo {
+    pub issue: String,
+    pub url: String,
+    pub undo_token: String,
+    pub item: TodoItem,
+}
+struct CommentSyntax {
+    line: &'static [&'static str],
+    block: &'static [(&'static str, &'static str)],
+    quotes: &'static [char],
+}
+const C_LIKE: CommentSyntax = CommentSyntax {
+    line: &["//"],
+    block: &[("/*", "*/")],
+    quotes: &['"', '\'', '`'],
+};
+const RUST: CommentSyntax = CommentSyntax {
+    line: &["//"],
+    block: &[("/*", "*/")],
+    quotes: &['"'],
+};
+const MARKUP_SCRIPT: CommentSyntax = CommentSyntax {
+    line: &["//"],
+    block: &[("<!--", "-->"), ("/*", "*/")],
+    quotes: &['"', '\'', '`'],
+};
+const CSS: CommentSyntax = CommentSyntax {
+    line: &[],
+    block: &[("/*", "*/")],
+    quotes: &['"', '\''],
+};
+const HASH: CommentSyntax = CommentSyntax {
+    line: &["#"],
+    block: &[],
+    quotes: &['"', '\''],
+};
+const PHP: CommentSyntax = CommentSyntax {
+    line: &["//", "#"],
+    block: &[("/*", "*/")],
+    quotes: &['"', '\''],
+};
+const LUA: CommentSyntax = CommentSyntax {
+    line: &["--"],
+    block: &[("--[[", "]]")],
+    quotes: &['"', '\''],
+};
+const SQL: CommentSyntax = CommentSyntax {
+    line: &["--"],
+    block: &[("/*", "*/")],
+    quotes: &['"', '\''],
+};
+const VIM: CommentSyntax = CommentSyntax {
+    line: &["\""],
+    block: &[],
+    quotes: &['\''],
+};
+const MARKUP: CommentSyntax = CommentSyntax {
+    line: &[],
+    block: &[("<!--", "-->")],
+    quotes: &[],
+};
+fn comment_syntax(path: &Path) -> Option<&'static CommentSyntax> {
+    let extension = path.extension()?.to_string_lossy().to_lowercase();
+    Some(match extension.as_str() {
+        "rs" => &RUST,
+        "js" | "ts" | "jsx" | "tsx" | "mjs" | "cjs" | "go" | "java" | "c" | "cpp" | "h" | "hpp"
+        | "cs" | "swift" | "kt" | "scala" | "scss" | "sass" | "less" => &C_LIKE,
+        "vue" | "svelte" => &MARKUP_SCRIPT,
+        "css" => &CSS,
+        "py" | "rb" | "sh" | "bash" | "zsh" | "fish" | "yaml" | "yml" | "toml" => &HASH,
+        "php" => &PHP,
+        "lua" => &LUA,
+        "sql" => &SQL,
+        "vim" => &VIM,
+        "html" | "xml" | "md" => &MARKUP,
+        _ => return None,
+    })
+}
+fn default_tags() -> Vec<TodoTag> {
+    [
+        ("FIXME", 0, "#f14c4c"),
+        ("BUG", 0, "#f14c4c"),
+        ("TODO", 1, "#3794ff"),
+        ("HACK", 2, "#cca700"),
+        ("NOTE", 3, "#89d185"),
+    ]
+    .into_iter()
+    .map(|(name, priority, color)| TodoTag {
+        name: name.to_string(),
+        priority,
+        color: Some(color.to_string()),
+    })
+    .collect()
+}
+fn tags_for(root: &Path) -> Vec<TodoTag> {
+    project_settings::load(root)
+        .todo_tags
+        .filter(|tags| !tags.is_empty())
+        .unwrap_or_else(default_tags)
+}
+fn tag_matcher(tags: &[TodoTag]) -> Result<Regex, String> {
+    let names: Vec<String> = tags.iter().map(|t| regex::escape(&t.name)).collect();
+    // The tag has to open the comment, optionally followed by (owner) or (#issue) and a colon.
+    Regex::new(&format!(
+        r"^[\s*!/#@-]*({})\b(?:\(([^)]*)\))?:?\s*(.*)$",
+        names.join("|")
+    ))
+    .map_err(|e| e.to_string())
 }
-fn extract_todos_from_file(path: &Path) -> Vec<TodoItem> {
+// Splits a line into the comment text it contains, carrying open block comments to the next line.
+fn comment_segments<'a>(
+    line: &'a str,
+    syntax: &CommentSyntax,
+    open_block: &mut Option<&'static str>,
+) -> Vec<(usize, &'a str)> {
+    let mut segments = Vec::new();
+    let mut quote: Option<char> = None;
+    let mut i = 0;
+    while i < line.len() {
+        if let Some(end) = *open_block {
+            match line[i..].find(end) {
+                Some(offset) => {
+                    segments.push((i, &line[i..i + offset]));
+                    i += offset + end.len();
+                    *open_block = None;
+                    continue;
+                }
+                None => {
+                    segments.push((i, &line[i..]));
+                    break;
+                }
+            }
+        }
+        let rest = &line[i..];
+        let c = rest.chars().next().unwrap();
+        if let Some(q) = quote {
+            if c == '\\' {
+                i += c.len_utf8();
+                if let Some(escaped) = line[i..].chars().next() {
+                    i += escaped.len_utf8();
+                }
+                continue;
+            }
+            if c == q {
+                quote = None;
+            }
+            i += c.len_utf8();
+            continue;
+        }
+        if let Some((start, end)) = syntax
+            .block
+            .iter()
+            .find(|(start, _)| rest.starts_with(start))
+        {
+            *open_block = Some(end);
+            i += start.len();
+            continue;
+        }
+        if let Some(prefix) = syntax.line.iter().find(|prefix| rest.starts_with(*prefix)) {
+            segments.push((i + prefix.len(), &rest[prefix.len()..]));
+            break;
+        }
+        if syntax.quotes.contains(&c) {
+            quote = Some(c);
+        }
+        i += c.len_utf8();
+    }
+    segments
+}
+fn parse_meta(meta: &str) -> (Option<String>, Option<String>) {
+    let mut owner = None;
+    let mut issue = None;
+    for part in meta.split(|c: char| c == ',' || c.is_whitespace()) {
+        let part = part.trim();
+        if let Some(number) = part.strip_prefix('#') {
+            if !number.is_empty() && number.chars().all(|c| c.is_ascii_digit()) {
+                issue = issue.or_else(|| Some(number.to_string()));
+                continue;
+            }
+        }
+        if !part.is_empty() && owner.is_none() {
+            owner = Some(part.trim_start_matches('@').to_string());
+        }
+    }
+    (owner, issue)
+}
+fn extract_todos_from_file(path: &Path, tags: &[TodoTag], matcher: &Regex) -> Vec<TodoItem> {
     let mut todos = Vec::new();
-    let content = match fs::read_to_string(path) {
-        Ok(c) => c,
+    let syntax = match comment_syntax(path) {
+        Some(syntax) => syntax,
+        None => return todos,
+    };
+    let decoded = match fs::read(path) {
+        Ok(bytes) => encoding::decode(&bytes),
         Err(_) => return todos,
     };
-    let patterns = [
-        ("TODO", &["TODO:", "TODO", "@todo"]),
-        ("FIXME", &["FIXME:", "FIXME", "@fixme"]),
-        ("HACK", &["HACK:", "HACK", "@hack"]),
-        ("BUG", &["BUG:", "BUG", "@bug"]),
-        ("NOTE", &["NOTE:", "NOTE", "@note"]),
-    ];
-    for (line_num, line) in content.lines().enumerate() {
-        let line_lower = line.to_lowercase();
-        for (todo_type, keywords) in &patterns {
-            for keyword in *keywords {
-                if line_lower.contains(&keyword.to_lowercase()) {
-                    let keyword_pos = line_lower.find(&keyword.to_lowercase()).unwrap();
-                    let after_keyword = &line[keyword_pos + keyword.len()..];
-                    let text = after_keyword.trim().to_string();
-                    if !text.is_empty() {
-                        todos.push(TodoItem {
-                            file: path.to_string_lossy().to_string(),
-                            line: line_num + 1,
-                            todo_type: todo_type.to_string(),
-                            text,
-                        });
-                        break; // Only match first pattern per line
-                    }
-                }
+    if decoded.is_binary {
+        return todos;
+    }
+    let mut open_block = None;
+    for (line_num, line) in decoded.content.lines().enumerate() {
+        for (offset, comment) in comment_segments(line, syntax, &mut open_block) {
+            let caps = match matcher.captures(comment) {
+                Some(caps) => caps,
+                None => continue,
+            };
+            let text = caps[3]
+                .trim()
+                .trim_end_matches("*/")
+                .trim_end_matches("-->")
+                .trim()
+                .to_string();
+            if text.is_empty() {
+                continue;
             }
+            let tag = &caps[1];
+            let (owner, issue) = caps
+                .get(2)
+                .map(|m| parse_meta(m.as_str()))
+                .unwrap_or_default();
+            let column = offset + caps.get(1).unwrap().start();
+            todos.push(TodoItem {
+                file: path.to_string_lossy().to_string(),
+                line: line_num + 1,
+                column: line[..column].encode_utf16().count(),
+                todo_type: tag.to_string(),
+                text,
+                owner,
+                issue,
+                priority: tags
+                    .iter()
+                    .find(|t| t.name == tag)
+                    .map_or(u8::MAX, |t| t.priority),
+                author: None,
+                commit: None,
+                committed_at: None,
+            });
+            break;
         }
     }
     todos
 }
+fn has_comments(path: &Path) -> bool {
+    comment_syntax(path).is_some()
+}
+fn index_files(
+    tags: &[TodoTag],
+    matcher: &Regex,
+    paths: &[PathBuf],
+) -> Vec<(PathBuf, Vec<TodoItem>)> {
+    paths
+        .iter()
+        .map(|path| (path.clone(), extract_todos_from_file(path, tags, matcher)))
+        .collect()
+}
+pub fn unload(root: &Path) {
+    INDEX.unload(root);
+}
+pub fn apply_changes(root: &Path, changes: &[FsChange]) {
+    INDEX.apply_changes(
+        root,
+        changes,
+        has_comments,
+        |tags, paths| match tag_matcher(tags) {
+            Ok(matcher) => index_files(tags, &matcher, paths),
+            Err(_) => Vec::new(),
+        },
+    );
+}
 #[tauri::command]
-pub fn search_todos(path: String) -> Result<String, String> {
-    let mut all_todos = Vec::new();
-    let root_path = Path::new(&path);
-    for entry in WalkDir::new(root_path)
-        .follow_links(false)
-        .into_iter()
-        .filter_entry(|e| {
-            let name = e.file_name().to_string_lossy();
-            !name.starts_with('.')
-                && name != "node_modules"
-                && name != "target"
-                && name != "dist"
-                && name != "build"
-                && name != "__pycache__"
-        })
-    {
-        let entry = match entry {
-            Ok(e) => e,
-            Err(_) => continue,
-        };
-        if !entry.file_type().is_file() {
-            continue;
-        }
-        let entry_path = entry.path();
-        if let Some(ext) = entry_path.extension() {
-            let ext_str = ext.to_string_lossy().to_lowercase();
-            let valid_extensions = [
-                "rs", "js", "ts", "jsx", "tsx", "py", "go", "java", "c", "cpp", "h", "hpp", "cs",
-                "php", "rb", "swift", "kt", "scala", "vue", "svelte", "css", "scss", "sass",
-                "less", "html", "xml", "yaml", "yml", "toml", "json", "md", "txt", "sh", "bash",
-                "zsh", "fish", "lua", "vim", "sql",
-            ];
-            if valid_extensions.contains(&ext_str.as_str()) {
-                let file_todos = extract_todos_from_file(entry_path);
-                all_todos.extend(file_todos);
+pub fn list_todo_tags(path: String) -> Result<Vec<TodoTag>, FsError> {
+    let resolved = workspace::resolve(&path)?;
+    Ok(tags_for(
+        &workspace::root_for(&resolved).unwrap_or(resolved),
+    ))
+}
+// Blames each file that has items once, spread across a few threads.
+fn attach_blame(root: &Path, todos: &mut [TodoItem]) {
+    let mut files: Vec<String> = todos.iter().map(|t| t.file.clone()).collect();
+    files.sort();
+    files.dedup();
+    let workers = thread::available_parallelism()
+        .map_or(4, |n| n.get())
+        .min(8);
+    let chunk = files.len().div_ceil(workers).max(1);
+    let cwd = root.to_string_lossy();
+    let blames: Vec<_> = thread::scope(|scope| {
+        let handles: Vec<_> = files
+            .chunks(chunk)
+            .map(|chunk| {
+                let cwd = &cwd;
+                scope.spawn(move || {
+                    chunk
+                        .iter()
+                        .map(|file| (file.clone(), git::blame_lines(cwd, file).ok()))
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().unwrap_or_default())
+            .collect()
+    });
+    for (file, blame) in blames {
+        let Some(blame) = blame else { continue };
+        for todo in todos.iter_mut().filter(|t| t.file == file) {
+            if let Some(line) = blame.get(&todo.line) {
+                todo.author = Some(line.author.clone());
+                todo.commit = Some(line.commit.clone());
+                todo.committed_at = Some(line.author_time);
             }
         }
     }
-    // Sort by type priority: FIXME/BUG > TODO > HACK > NOTE
+}
+fn age_days(todo: &TodoItem, now: i64) -> u64 {
+    // Uncommitted lines are brand new.
+    todo.committed_at
+        .map_or(0, |at| ((now - at).max(0) / 86_400_000) as u64)
+}
+// Scanning and blame both shell out or walk the tree, so the work runs on a blocking thread.
+#[tauri::command]
+pub async fn search_todos(
+    path: String,
+    options: Option<TodoQuery>,
+) -> Result<Vec<TodoItem>, FsError> {
+    let resolved = workspace::resolve(&path)?;
+    tauri::async_runtime::spawn_blocking(move || {
+        query_todos(&resolved, options.unwrap_or_default())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+// The index always covers the whole workspace root, the one the watcher keeps current.
+fn query_todos(resolved: &Path, options: TodoQuery) -> Result<Vec<TodoItem>, FsError> {
+    let root = workspace::root_for(resolved).unwrap_or_else(|| resolved.to_path_buf());
+    let tags = tags_for(&root);
+    let matcher = tag_matcher(&tags)?;
+    let filter = GlobFilter::new(resolved, &options.include, &options.exclude)?;
+    INDEX.ensure(&root, &tags, has_comments, |tags, paths| {
+        index_files(tags, &matcher, paths)
+    });
+    let mut all_todos: Vec<TodoItem> = INDEX.with(&root, |files| {
+        files
+            .filter(|(file, _)| file.starts_with(resolved) && filter.matches(file))
+            .flat_map(|(_, todos)| todos.iter().cloned())
+            .collect()
+    });
+    if !options.tags.is_empty() {
+        all_todos.retain(|t| options.tags.contains(&t.todo_type));
+    }
+    if options.needs_blame() {
+        attach_blame(&root, &mut all_todos);
+    }
+    if !options.authors.is_empty() {
+        let authors: Vec<String> = options.authors.iter().map(|a| a.to_lowercase()).collect();
+        all_todos.retain(|t| {
+            [&t.author, &t.owner].into_iter().flatten().any(|name| {
+                let name = name.to_lowercase();
+                authors.iter().any(|author| name.contains(author.as_str()))
+            })
+        });
+    }
+    let now = Utc::now().timestamp_millis();
+    all_todos.retain(|t| {
+        let age = age_days(t, now);
+        options.min_age_days.map_or(true, |min| age >= min)
+            && options.max_age_days.map_or(true, |max| age <= max)
+    });
     all_todos.sort_by(|a, b| {
-        let priority = |t: &str| match t {
-            "FIXME" | "BUG" => 0,
-            "TODO" => 1,
-            "HACK" => 2,
-            "NOTE" => 3,
-            _ => 4,
-        };
-        priority(&a.todo_type).cmp(&priority(&b.todo_type))
+        let by_location = || a.file.cmp(&b.file).then_with(|| a.line.cmp(&b.line));
+        match options.sort {
+            TodoSort::Priority => a.priority.cmp(&b.priority).then_with(by_location),
+            TodoSort::Age => a
+                .committed_at
+                .unwrap_or(i64::MAX)
+                .cmp(&b.committed_at.unwrap_or(i64::MAX))
+                .then_with(by_location),
+            TodoSort::File => by_location(),
+        }
+    });
+    Ok(all_todos)
+}
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+fn markdown_text(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, '\\' | '[' | ']' | '|' | '`' | '*' | '_') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+fn markdown_link(target: &str) -> String {
+    target
+        .replace('%', "%25")
+        .replace(' ', "%20")
+        .replace('(', "%28")
+        .replace(')', "%29")
+}
+#[tauri::command]
+pub async fn export_todos(
+    path: String,
+    format: TodoExportFormat,
+    options: Option<TodoQuery>,
+) -> Result<String, FsError> {
+    let resolved = workspace::resolve(&path)?;
+    tauri::async_runtime::spawn_blocking(move || {
+        render_export(&resolved, format, options.unwrap_or_default())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+fn render_export(
+    root: &Path,
+    format: TodoExportFormat,
+    options: TodoQuery,
+) -> Result<String, FsError> {
+    let todos = query_todos(root, options)?;
+    let relative = |todo: &TodoItem| {
+        Path::new(&todo.file)
+            .strip_prefix(root)
+            .map(|p| p.to_string_lossy().replace('\\', "/"))
+            .unwrap_or_else(|_| todo.file.clone())
+    };
+    let date = |todo: &TodoItem| {
+        todo.committed_at
+            .and_then(|at| Utc.timestamp_millis_opt(at).single())
+            .map(|at| at.format("%Y-%m-%d").to_string())
+    };
+    let mut out = String::new();
+    match format {
+        TodoExportFormat::Markdown => {
+            for todo in &todos {
+                let file = relative(todo);
+                out.push_str(&format!(
+                    "- [ ] **{}** {} ([{}:{}]({}#L{}))",
+                    markdown_text(&todo.todo_type),
+                    markdown_text(&todo.text),
+                    markdown_text(&file),
+                    todo.line,
+                    markdown_link(&file),
+                    todo.line
+                ));
+                if let Some(owner) = &todo.owner {
+                    out.push_str(&format!(" @{}", markdown_text(owner)));
+                }
+                if let Some(issue) = &todo.issue {
+                    out.push_str(&format!(" #{}", issue));
+                }
+                if let (Some(author), Some(date)) = (&todo.author, date(todo)) {
+                    out.push_str(&format!(" — {}, {}", markdown_text(author), date));
+                }
+                out.push('\n');
+            }
+        }
+        TodoExportFormat::Csv => {
+            out.push_str("file,line,column,type,priority,text,owner,issue,author,commit,date\n");
+            for todo in &todos {
+                let fields = [
+                    relative(todo),
+                    todo.line.to_string(),
+                    todo.column.to_string(),
+                    todo.todo_type.clone(),
+                    todo.priority.to_string(),
+                    todo.text.clone(),
+                    todo.owner.clone().unwrap_or_default(),
+                    todo.issue.clone().unwrap_or_default(),
+                    todo.author.clone().unwrap_or_default(),
+                    todo.commit.clone().unwrap_or_default(),
+                    date(todo).unwrap_or_default(),
+                ];
+                let row: Vec<String> = fields.iter().map(|f| csv_field(f)).collect();
+                out.push_str(&row.join(","));
+                out.push('\n');
+            }
+        }
+    }
+    Ok(out)
+}
+// Adds the issue reference to the tag, as in `TODO(#42):` or `TODO(alice, #42):`.
+fn reference_issue(line: &str, column: usize, tag: &str, issue: &str) -> Option<String> {
+    let start = line
+        .char_indices()
+        .scan(0, |units, (index, c)| {
+            let current = *units;
+            *units += c.len_utf16();
+            Some((index, current))
+        })
+        .find(|(_, units)| *units == column)
+        .map(|(index, _)| index)?;
+    let after = start + tag.len();
+    if line.get(start..after) != Some(tag) {
+        return None;
+    }
+    let rest = &line[after..];
+    Some(if rest.starts_with('(') {
+        let close = after + rest.find(')')?;
+        format!("{}, #{}{}", &line[..close], issue, &line[close..])
+    } else {
+        format!("{}(#{}){}", &line[..after], issue, rest)
+    })
+}
+#[tauri::command]
+pub async fn promote_todo_to_issue(
+    file: String,
+    line: usize,
+    title: Option<String>,
+    body: Option<String>,
+) -> Result<PromotedTodo, FsError> {
+    let resolved = workspace::resolve(&file)?;
+    // Creating the issue waits on `gh` and the network, so it runs on a blocking thread.
+    tauri::async_runtime::spawn_blocking(move || promote(resolved, &file, line, title, body))
+        .await
+        .map_err(|e| e.to_string())?
+}
+fn promote(
+    resolved: PathBuf,
+    file: &str,
+    line: usize,
+    title: Option<String>,
+    body: Option<String>,
+) -> Result<PromotedTodo, FsError> {
+    let root = workspace::root_for(&resolved)
+        .or_else(|| resolved.parent().map(Path::to_path_buf))
+        .unwrap_or_else(|| resolved.clone());
+    let tags = tags_for(&root);
+    let matcher = tag_matcher(&tags)?;
+    let item = extract_todos_from_file(&resolved, &tags, &matcher)
+        .into_iter()
+        .find(|todo| todo.line == line)
+        .ok_or_else(|| format!("No TODO comment found at {}:{}", file, line))?;
+    if let Some(issue) = &item.issue {
+        return Err(format!("This comment already references #{}", issue).into());
+    }
+    let relative = resolved
+        .strip_prefix(&root)
+        .unwrap_or(&resolved)
+        .to_string_lossy()
+        .replace('\\', "/");
+    let title = title
+        .filter(|t| !t.trim().is_empty())
+        .unwrap_or_else(|| item.text.clone());
+    let body = body.unwrap_or_else(|| {
+        format!(
+            "{}: {}\n\nFound in `{}:{}`.",
+            item.todo_type, item.text, relative, item.line
+        )
     });
-    serde_json::to_string(&all_todos).map_err(|e| e.to_string())
-}
\ No newline at end of file
+    // Check the line can be rewritten before creating the issue so a failure leaves nothing behind.
+    let original = fs::read(&resolved)?;
+    let metadata = fs::metadata(&resolved)?;
+    let decoded = encoding::decode(&original);
+    let mut lines: Vec<&str> = decoded.content.split('\n').collect();
+    let current = *lines
+        .get(line - 1)
+        .ok_or_else(|| format!("Could not update {}:{}", file, line))?;
+    reference_issue(current, item.column, &item.todo_type, "0")
+        .ok_or_else(|| format!("Could not update {}:{}", file, line))?;
+    encoding::encode(&decoded.content, decoded.encoding, decoded.has_bom)?;
+    let url = gh::gh_issue_create(root.to_string_lossy().to_string(), title, body)?
+        .trim()
+        .to_string();
+    let issue = url
+        .rsplit('/')
+        .next()
+        .filter(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()))
+        .ok_or_else(|| format!("Created {} but could not read its issue number", url))?
+        .to_string();
+    let rewritten = reference_issue(current, item.column, &item.todo_type, &issue)
+        .ok_or_else(|| format!("Created {} but could not update {}:{}", url, file, line))?;
+    lines[line - 1] = &rewritten;
+    let new_text = lines.join("\n");
+    let updated = encoding::encode(&new_text, decoded.encoding, decoded.has_bom)
+        .map_err(|e| format!("Created {} but could not update {}: {}", url, file, e))?;
+    let change = PlannedChange {
+        path: resolved.clone(),
+        version: file_version(&original, &metadata),
+        updated,
+        original,
+        old_text: decoded.content.clone(),
+        new_text,
+        replacements: 1,
+    };
+    let outcome = replace::commit(vec![(file.to_string(), change)])
+        .map_err(|e| format!("Created {} but could not update {}: {}", url, file, e))?;
+    Ok(PromotedTodo {
+        item: TodoItem {
+            issue: Some(issue.clone()),
+            ..item
+        },
+        issue,
+        url,
+        undo_token: outcome.undo_token,
+    })
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    fn todos_in(name: &str, source: &str, tags: &[TodoTag]) -> Vec<TodoItem> {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(name);
+        fs::write(&path, source).unwrap();
+        extract_todos_from_file(&path, tags, &tag_matcher(tags).unwrap())
+    }
+    #[test]
+    fn only_tags_inside_comments_are_reported() {
+        let source = "let s = \"// TODO: not a comment\";\n\
+                      let t = 1; // TODO(alice, #42): real one\n\
+                      /* start\n   FIXME: inside a block */ let u = 2;\n\
+                      // TODOS are not tags\n";
+        let todos = todos_in("lib.rs", source, &default_tags());
+        let found: Vec<(usize, &str, &str)> = todos
+            .iter()
+            .map(|t| (t.line, t.todo_type.as_str(), t.text.as_str()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![(2, "TODO", "real one"), (4, "FIXME", "inside a block")]
+        );
+        assert_eq!(todos[0].owner.as_deref(), Some("alice"));
+        assert_eq!(todos[0].issue.as_deref(), Some("42"));
+        assert_eq!(todos[0].column, 14);
+        assert!(todos_in("notes.txt", "// TODO: no syntax", &default_tags()).is_empty());
+    }
+    #[test]
+    fn comment_syntax_follows_the_language() {
+        let python = todos_in(
+            "a.py",
+            "x = '# TODO: string'  # HACK: real\n",
+            &default_tags(),
+        );
+        assert_eq!(python.len(), 1);
+        assert_eq!(python[0].todo_type, "HACK");
+        let html = todos_in(
+            "a.html",
+            "<p>TODO: text</p><!-- NOTE: c -->\n",
+            &default_tags(),
+        );
+        assert_eq!(html.len(), 1);
+        assert_eq!(html[0].text, "c");
+    }
+    #[test]
+    fn projects_can_define_their_own_tags() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(tags_for(dir.path()), default_tags());
+        fs::create_dir(dir.path().join(".mide")).unwrap();
+        fs::write(
+            project_settings::settings_path(dir.path()),
+            r#"{"todo.tags": [{"name": "REVIEW", "priority": 0}]}"#,
+        )
+        .unwrap();
+        let tags = tags_for(dir.path());
+        assert_eq!(tags.len(), 1);
+        let todos = todos_in("a.rs", "// REVIEW: check\n// TODO: ignored\n", &tags);
+        assert_eq!(todos.len(), 1);
+        assert_eq!(todos[0].priority, 0);
+    }
+    #[test]
+    fn meta_splits_owners_from_issue_numbers() {
+        assert_eq!(
+            parse_meta("@bob, #7"),
+            (Some("bob".to_string()), Some("7".to_string()))
+        );
+        assert_eq!(parse_meta("#x"), (Some("#x".to_string()), None));
+        assert_eq!(parse_meta(""), (None, None));
+    }
+    #[test]
+    fn issue_reference_is_added_after_the_tag() {
+        assert_eq!(
+            reference_issue("// TODO: fix this", 3, "TODO", "12").as_deref(),
+            Some("// TODO(#12): fix this")
+        );
+        assert_eq!(
+            reference_issue("// TODO(alice): fix", 3, "TODO", "7").as_deref(),
+            Some("// TODO(alice, #7): fix")
+        );
+    }
+    #[test]
+    fn issue_reference_columns_count_utf16_units() {
+        assert_eq!(
+            reference_issue("/* 😀 */ // FIXME later", 12, "FIXME", "3").as_deref(),
+            Some("/* 😀 */ // FIXME(#3) later")
+        );
+    }
+    #[test]
+    fn issue_reference_needs_the_tag_at_the_column() {
+        assert_eq!(reference_issue("// TODO: x", 0, "TODO", "1"), None);
+        assert_eq!(reference_issue("// TODO(owner: x", 3, "TODO", "1"), None);
+    }
+    #[test]
+    fn queries_filter_by_folder_and_tag() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = fs::canonicalize(dir.path()).unwrap();
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(
+            root.join("src/lib.rs"),
+            "// TODO: in src\n// FIXME: also src\n",
+        )
+        .unwrap();
+        fs::write(root.join("main.rs"), "// TODO: at the root\n").unwrap();
+        let all = query_todos(&root, TodoQuery::default()).unwrap();
+        assert_eq!(all.len(), 3);
+        let nested = query_todos(&root.join("src"), TodoQuery::default()).unwrap();
+        assert_eq!(nested.len(), 2);
+        let fixmes = query_todos(
+            &root,
+            TodoQuery {
+                tags: vec!["FIXME".to_string()],
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(fixmes.len(), 1);
+        assert_eq!(fixmes[0].text, "also src");
+        let export = render_export(&root, TodoExportFormat::Csv, TodoQuery::default()).unwrap();
+        assert!(export.contains("src/lib.rs,1,"));
+        assert!(export.contains("main.rs,1,"));
+    }
+    #[test]
+    fn markdown_export_escapes_text_and_link_targets() {
+        assert_eq!(markdown_text("a]b|c`d"), r"a\]b\|c\`d");
+        assert_eq!(
+            markdown_link("src/my file (1).rs"),
+            "src/my%20file%20%281%29.rs"
+        );
+    }
+}
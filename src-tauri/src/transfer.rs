@@ -0,0 +1,480 @@
+use crate::error::FsError;
+use crate::workspace;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tauri::{Emitter, Window};
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+const COPY_BUFFER_SIZE: usize = 256 * 1024;
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ConflictStrategy {
+    Skip,
+    Overwrite,
+    #[default]
+    Rename,
+}
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct TransferProgress {
+    pub operation_id: String,
+    pub bytes_done: u64,
+    pub bytes_total: u64,
+    pub files_done: usize,
+    pub files_total: usize,
+    pub current: String,
+}
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct TransferResult {
+    pub created: Vec<String>,
+    pub skipped: Vec<String>,
+}
+struct Progress<'a> {
+    window: Option<&'a Window>,
+    state: TransferProgress,
+    last_emit: Instant,
+}
+impl Progress<'_> {
+    fn advance(&mut self, bytes: u64, current: &Path, file_done: bool) {
+        self.state.bytes_done += bytes;
+        if file_done {
+            self.state.files_done += 1;
+        }
+        if self.last_emit.elapsed() >= PROGRESS_INTERVAL {
+            self.state.current = current.to_string_lossy().to_string();
+            self.emit();
+        }
+    }
+    fn emit(&mut self) {
+        if let Some(window) = self.window {
+            let _ = window.emit("fs-transfer-progress", self.state.clone());
+        }
+        self.last_emit = Instant::now();
+    }
+}
+fn measure(path: &Path) -> (u64, usize) {
+    let metadata = match fs::symlink_metadata(path) {
+        Ok(m) => m,
+        Err(_) => return (0, 0),
+    };
+    if !metadata.is_dir() {
+        return (metadata.len(), 1);
+    }
+    let mut totals = (0, 0);
+    if let Ok(entries) = fs::read_dir(path) {
+        for entry in entries.flatten() {
+            let (bytes, files) = measure(&entry.path());
+            totals.0 += bytes;
+            totals.1 += files;
+        }
+    }
+    totals
+}
+fn copy_name(path: &Path, attempt: usize) -> PathBuf {
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let (stem, ext) = match name.rfind('.') {
+        Some(pos) if pos > 0 && !path.is_dir() => (&name[..pos], &name[pos..]),
+        _ => (name.as_str(), ""),
+    };
+    let suffix = if attempt == 1 {
+        " (copy)".to_string()
+    } else {
+        format!(" (copy {})", attempt)
+    };
+    path.with_file_name(format!("{}{}{}", stem, suffix, ext))
+}
+fn free_copy_path(path: &Path) -> PathBuf {
+    (1..)
+        .map(|attempt| copy_name(path, attempt))
+        .find(|candidate| fs::symlink_metadata(candidate).is_err())
+        .unwrap()
+}
+pub(crate) fn remove_existing(path: &Path) -> io::Result<()> {
+    let metadata = fs::symlink_metadata(path)?;
+    if metadata.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    }
+}
+fn copy_file(source: &Path, target: &Path, progress: &mut Progress) -> io::Result<()> {
+    let mut reader = File::open(source)?;
+    // create_new refuses to follow a symlink left at the target.
+    let mut writer = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(target)?;
+    let mut buffer = vec![0u8; COPY_BUFFER_SIZE];
+    loop {
+        let n = reader.read(&mut buffer)?;
+        if n == 0 {
+            break;
+        }
+        writer.write_all(&buffer[..n])?;
+        progress.advance(n as u64, source, false);
+    }
+    fs::set_permissions(target, reader.metadata()?.permissions())?;
+    progress.advance(0, source, true);
+    Ok(())
+}
+fn copy_tree(source: &Path, target: &Path, progress: &mut Progress) -> io::Result<()> {
+    let metadata = fs::symlink_metadata(source)?;
+    if metadata.file_type().is_symlink() {
+        let link = fs::read_link(source)?;
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(&link, target)?;
+        #[cfg(windows)]
+        {
+            if source.is_dir() {
+                std::os::windows::fs::symlink_dir(&link, target)?;
+            } else {
+                std::os::windows::fs::symlink_file(&link, target)?;
+            }
+        }
+        progress.advance(0, source, true);
+        return Ok(());
+    }
+    if metadata.is_file() {
+        return copy_file(source, target, progress);
+    }
+    // Opening a FIFO would block forever and a device would be streamed, so neither is copied.
+    if !metadata.is_dir() {
+        return Err(io::Error::other(format!(
+            "Cannot copy special file {:?}",
+            source
+        )));
+    }
+    fs::create_dir(target)?;
+    for entry in fs::read_dir(source)? {
+        let entry = entry?;
+        copy_tree(&entry.path(), &target.join(entry.file_name()), progress)?;
+    }
+    fs::set_permissions(target, metadata.permissions())?;
+    Ok(())
+}
+pub(crate) fn is_cross_device(e: &io::Error) -> bool {
+    #[cfg(unix)]
+    return e.raw_os_error() == Some(libc::EXDEV);
+    #[cfg(windows)]
+    return e.raw_os_error() == Some(17);
+    #[cfg(not(any(unix, windows)))]
+    return false;
+}
+fn temp_sibling(target: &Path, purpose: &str) -> PathBuf {
+    let name = target
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    (0..)
+        .map(|n| target.with_file_name(format!(".{}.mide-{}-{}", name, purpose, n)))
+        .find(|candidate| fs::symlink_metadata(candidate).is_err())
+        .unwrap()
+}
+// Puts `source` at `target`, keeping an existing target aside until that has worked.
+fn place_over(
+    source: &Path,
+    target: &Path,
+    place: impl FnOnce(&Path, &Path) -> io::Result<()>,
+) -> io::Result<()> {
+    if fs::symlink_metadata(target).is_err() {
+        return place(source, target);
+    }
+    let aside = temp_sibling(target, "old");
+    fs::rename(target, &aside)?;
+    if let Err(e) = place(source, target) {
+        let _ = fs::rename(&aside, target);
+        return Err(e);
+    }
+    remove_existing(&aside)
+}
+// Copies into a hidden sibling first, so a failed copy leaves neither a partial target nor a damaged old one.
+fn copy_into_place(source: &Path, target: &Path, progress: &mut Progress) -> io::Result<()> {
+    let staging = temp_sibling(target, "copy");
+    let copied = copy_tree(source, &staging, progress)
+        .and_then(|_| place_over(&staging, target, |from, to| fs::rename(from, to)));
+    if copied.is_err() {
+        let _ = remove_existing(&staging);
+    }
+    copied
+}
+// Copies a file or tree without reporting progress, keeping symlinks as links.
+pub(crate) fn copy_entry(source: &Path, target: &Path) -> io::Result<()> {
+    let mut progress = Progress {
+        window: None,
+        state: TransferProgress::default(),
+        last_emit: Instant::now(),
+    };
+    copy_tree(source, target, &mut progress)
+}
+fn transfer(
+    window: Option<&Window>,
+    operation_id: String,
+    sources: Vec<String>,
+    destination: String,
+    conflict: ConflictStrategy,
+    remove_source: bool,
+) -> Result<TransferResult, FsError> {
+    let destination = workspace::resolve(&destination)?;
+    if !destination.is_dir() {
+        return Err("Destination is not a directory".into());
+    }
+    // Sources keep their own name, so symlinks are compared and copied as links, not targets.
+    let sources = sources
+        .iter()
+        .map(|s| workspace::resolve_entry(s))
+        .collect::<Result<Vec<_>, _>>()?;
+    let exists = |path: &Path| fs::symlink_metadata(path).is_ok();
+    let (bytes_total, files_total) = sources
+        .iter()
+        .map(|s| measure(s))
+        .fold((0, 0), |acc, m| (acc.0 + m.0, acc.1 + m.1));
+    let mut progress = Progress {
+        window,
+        state: TransferProgress {
+            operation_id,
+            bytes_done: 0,
+            bytes_total,
+            files_done: 0,
+            files_total,
+            current: String::new(),
+        },
+        last_emit: Instant::now(),
+    };
+    if conflict == ConflictStrategy::Overwrite {
+        // Replacing /p/x with /p/x/x would delete the source along with the old target,
+        // so every source is checked before anything is removed.
+        for source in &sources {
+            let target = source.file_name().map(|name| destination.join(name));
+            if let Some(target) = target.filter(|t| exists(t)) {
+                if target != *source && source.starts_with(&target) {
+                    return Err(
+                        format!("Cannot replace {:?} with an item it contains", target).into(),
+                    );
+                }
+            }
+        }
+    }
+    let mut result = TransferResult::default();
+    for source in sources {
+        let name = source
+            .file_name()
+            .ok_or_else(|| format!("Invalid source path: {:?}", source))?;
+        if workspace::is_root(&source) {
+            return Err(format!("Cannot transfer the workspace folder {:?}", source).into());
+        }
+        let source_is_dir = fs::symlink_metadata(&source)?.is_dir();
+        if source_is_dir && destination.starts_with(&source) {
+            return Err(format!("Cannot copy {:?} into itself", source).into());
+        }
+        let mut target = destination.join(name);
+        if remove_source && source.parent() == Some(destination.as_path()) {
+            result.skipped.push(source.to_string_lossy().to_string());
+            continue;
+        }
+        if target == source {
+            target = free_copy_path(&target);
+        } else if exists(&target) {
+            match conflict {
+                ConflictStrategy::Skip => {
+                    result.skipped.push(source.to_string_lossy().to_string());
+                    continue;
+                }
+                // The old target is only removed once the new one is in place.
+                ConflictStrategy::Overwrite => {}
+                ConflictStrategy::Rename => target = free_copy_path(&target),
+            }
+        }
+        if remove_source {
+            match place_over(&source, &target, |from, to| fs::rename(from, to)) {
+                Ok(()) => {
+                    let (bytes, files) = measure(&target);
+                    progress.state.files_done += files.saturating_sub(1);
+                    progress.advance(bytes, &target, true);
+                }
+                Err(e) if is_cross_device(&e) => {
+                    copy_into_place(&source, &target, &mut progress)?;
+                    remove_existing(&source)?;
+                }
+                Err(e) => return Err(e.into()),
+            }
+        } else {
+            copy_into_place(&source, &target, &mut progress)?;
+        }
+        result.created.push(target.to_string_lossy().to_string());
+    }
+    progress.emit();
+    Ok(result)
+}
+#[tauri::command]
+pub async fn copy_items(
+    window: Window,
+    operation_id: String,
+    sources: Vec<String>,
+    destination: String,
+    conflict: Option<ConflictStrategy>,
+) -> Result<TransferResult, FsError> {
+    // Copies, renames and deletes can take a long time, so transfers run on a blocking thread.
+    tauri::async_runtime::spawn_blocking(move || {
+        transfer(
+            Some(&window),
+            operation_id,
+            sources,
+            destination,
+            conflict.unwrap_or_default(),
+            false,
+        )
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+#[tauri::command]
+pub async fn move_items(
+    window: Window,
+    operation_id: String,
+    sources: Vec<String>,
+    destination: String,
+    conflict: Option<ConflictStrategy>,
+) -> Result<TransferResult, FsError> {
+    tauri::async_runtime::spawn_blocking(move || {
+        transfer(
+            Some(&window),
+            operation_id,
+            sources,
+            destination,
+            conflict.unwrap_or_default(),
+            true,
+        )
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+#[tauri::command]
+pub async fn duplicate_item(path: String) -> Result<String, FsError> {
+    let source = PathBuf::from(&path);
+    let parent = source
+        .parent()
+        .ok_or("Cannot duplicate the filesystem root")?
+        .to_string_lossy()
+        .to_string();
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        transfer(
+            None,
+            String::new(),
+            vec![path],
+            parent,
+            ConflictStrategy::Rename,
+            false,
+        )
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+    result
+        .created
+        .into_iter()
+        .next()
+        .ok_or_else(|| "Nothing was duplicated".into())
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    fn project() -> (tempfile::TempDir, PathBuf) {
+        let dir = tempfile::tempdir().unwrap();
+        let root = workspace::open_for_test(dir.path());
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::create_dir_all(root.join("dest")).unwrap();
+        fs::write(root.join("src/a.txt"), "new").unwrap();
+        fs::write(root.join("dest/a.txt"), "old").unwrap();
+        (dir, root)
+    }
+    fn copy(
+        root: &Path,
+        source: &str,
+        conflict: ConflictStrategy,
+    ) -> Result<TransferResult, FsError> {
+        transfer(
+            None,
+            String::new(),
+            vec![root.join(source).to_string_lossy().to_string()],
+            root.join("dest").to_string_lossy().to_string(),
+            conflict,
+            false,
+        )
+    }
+    fn entries(dir: &Path) -> Vec<String> {
+        let mut names: Vec<String> = fs::read_dir(dir)
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        names.sort();
+        names
+    }
+    #[test]
+    fn conflicts_are_skipped_renamed_or_overwritten() {
+        let (_dir, root) = project();
+        let skipped = copy(&root, "src/a.txt", ConflictStrategy::Skip).unwrap();
+        assert_eq!(skipped.skipped.len(), 1);
+        assert_eq!(fs::read_to_string(root.join("dest/a.txt")).unwrap(), "old");
+        copy(&root, "src/a.txt", ConflictStrategy::Rename).unwrap();
+        assert_eq!(
+            fs::read_to_string(root.join("dest/a (copy).txt")).unwrap(),
+            "new"
+        );
+        copy(&root, "src/a.txt", ConflictStrategy::Overwrite).unwrap();
+        assert_eq!(fs::read_to_string(root.join("dest/a.txt")).unwrap(), "new");
+        assert_eq!(entries(&root.join("dest")), vec!["a (copy).txt", "a.txt"]);
+    }
+    #[test]
+    fn overwriting_a_folder_replaces_its_contents() {
+        let (_dir, root) = project();
+        fs::create_dir_all(root.join("dest/src")).unwrap();
+        fs::write(root.join("dest/src/stale.txt"), "old").unwrap();
+        copy(&root, "src", ConflictStrategy::Overwrite).unwrap();
+        assert_eq!(entries(&root.join("dest/src")), vec!["a.txt"]);
+        assert_eq!(entries(&root.join("dest")), vec!["a.txt", "src"]);
+    }
+    #[test]
+    fn moves_overwrite_and_remove_the_source() {
+        let (_dir, root) = project();
+        transfer(
+            None,
+            String::new(),
+            vec![root.join("src/a.txt").to_string_lossy().to_string()],
+            root.join("dest").to_string_lossy().to_string(),
+            ConflictStrategy::Overwrite,
+            true,
+        )
+        .unwrap();
+        assert_eq!(fs::read_to_string(root.join("dest/a.txt")).unwrap(), "new");
+        assert!(entries(&root.join("src")).is_empty());
+    }
+    #[cfg(unix)]
+    #[test]
+    fn a_failed_copy_keeps_the_old_target_and_leaves_nothing_behind() {
+        let (_dir, root) = project();
+        // A socket is a special file, so copying the folder fails partway.
+        let _socket = std::os::unix::net::UnixListener::bind(root.join("src/z.sock")).unwrap();
+        fs::create_dir_all(root.join("dest/src")).unwrap();
+        fs::write(root.join("dest/src/keep.txt"), "old").unwrap();
+        assert!(copy(&root, "src", ConflictStrategy::Overwrite).is_err());
+        assert_eq!(entries(&root.join("dest")), vec!["a.txt", "src"]);
+        assert_eq!(entries(&root.join("dest/src")), vec!["keep.txt"]);
+        fs::remove_dir_all(root.join("dest/src")).unwrap();
+        assert!(copy(&root, "src", ConflictStrategy::Rename).is_err());
+        assert_eq!(entries(&root.join("dest")), vec!["a.txt"]);
+    }
+    #[cfg(unix)]
+    #[test]
+    fn special_files_are_refused_instead_of_read() {
+        let (_dir, root) = project();
+        let fifo =
+            std::ffi::CString::new(root.join("src/pipe").to_string_lossy().as_bytes()).unwrap();
+        assert_eq!(unsafe { libc::mkfifo(fifo.as_ptr(), 0o644) }, 0);
+        let error = copy(&root, "src", ConflictStrategy::Rename).unwrap_err();
+        assert!(error.to_string().contains("special file"));
+        assert_eq!(entries(&root.join("dest")), vec!["a.txt"]);
+    }
+}
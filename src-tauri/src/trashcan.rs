@@ -0,0 +1,444 @@
+use crate::error::FsError;
+use crate::workspace;
+use serde::Serialize;
+use std::path::Path;
+#[derive(Serialize, Debug, Clone)]
+pub struct TrashedItem {
+    pub id: String,
+    pub name: String,
+    pub original_path: String,
+    pub deleted_at: i64,
+    pub is_dir: bool,
+}
+#[cfg(target_os = "linux")]
+mod xdg {
+    use super::TrashedItem;
+    use crate::error::FsError;
+    use crate::{transfer, workspace};
+    use chrono::{Local, NaiveDateTime, TimeZone};
+    use std::env;
+    use std::fs::{self, OpenOptions};
+    use std::io::{self, Write};
+    use std::os::unix::fs::{MetadataExt, PermissionsExt};
+    use std::path::{Component, Path, PathBuf};
+    const DATE_FORMAT: &str = "%Y-%m-%dT%H:%M:%S";
+    struct TrashDir {
+        base: PathBuf,
+        // Top-directory trashes store paths relative to the mount point.
+        topdir: Option<PathBuf>,
+    }
+    impl TrashDir {
+        fn files(&self) -> PathBuf {
+            self.base.join("files")
+        }
+        fn info(&self) -> PathBuf {
+            self.base.join("info")
+        }
+    }
+    fn home_trash() -> Result<TrashDir, String> {
+        let data_home = env::var_os("XDG_DATA_HOME")
+            .map(PathBuf::from)
+            .filter(|p| p.is_absolute())
+            .or_else(|| env::var_os("HOME").map(|h| PathBuf::from(h).join(".local/share")))
+            .ok_or("Cannot locate the home trash directory")?;
+        Ok(TrashDir {
+            base: data_home.join("Trash"),
+            topdir: None,
+        })
+    }
+    fn mount_point(path: &Path) -> PathBuf {
+        let dev = match fs::metadata(path) {
+            Ok(m) => m.dev(),
+            Err(_) => return PathBuf::from("/"),
+        };
+        let mut top = path.to_path_buf();
+        for parent in path.ancestors().skip(1) {
+            match fs::metadata(parent) {
+                Ok(m) if m.dev() == dev => top = parent.to_path_buf(),
+                _ => break,
+            }
+        }
+        top
+    }
+    // $topdir/.Trash/$uid when the shared trash is a sticky directory, then $topdir/.Trash-$uid.
+    fn topdir_trashes(topdir: &Path) -> Vec<TrashDir> {
+        let uid = unsafe { libc::getuid() };
+        let shared = topdir.join(".Trash");
+        let shared_ok = fs::symlink_metadata(&shared)
+            .map(|m| m.is_dir() && m.permissions().mode() & 0o1000 != 0)
+            .unwrap_or(false);
+        let mut bases = Vec::new();
+        if shared_ok {
+            bases.push(shared.join(uid.to_string()));
+        }
+        bases.push(topdir.join(format!(".Trash-{}", uid)));
+        bases
+            .into_iter()
+            .map(|base| TrashDir {
+                base,
+                topdir: Some(topdir.to_path_buf()),
+            })
+            .collect()
+    }
+    // Trashes to try in order; the spec falls back to the home trash when a volume's own are unusable.
+    fn trash_for(path: &Path) -> Result<Vec<TrashDir>, String> {
+        let home = home_trash()?;
+        let path_dev = fs::symlink_metadata(path).map_err(|e| e.to_string())?.dev();
+        let home_dev = home
+            .base
+            .ancestors()
+            .find_map(|p| fs::metadata(p).ok())
+            .map(|m| m.dev());
+        if home_dev == Some(path_dev) {
+            return Ok(vec![home]);
+        }
+        let parent = path.parent().unwrap_or(Path::new("/"));
+        let mut trashes = topdir_trashes(&mount_point(parent));
+        trashes.push(home);
+        Ok(trashes)
+    }
+    fn encode_path(path: &Path) -> String {
+        use std::os::unix::ffi::OsStrExt;
+        let mut out = String::new();
+        for b in path.as_os_str().as_bytes() {
+            match *b {
+                b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'/' => {
+                    out.push(*b as char)
+                }
+                other => out.push_str(&format!("%{:02X}", other)),
+            }
+        }
+        out
+    }
+    fn decode_path(encoded: &str) -> PathBuf {
+        use std::ffi::OsString;
+        use std::os::unix::ffi::OsStringExt;
+        let bytes = encoded.as_bytes();
+        let mut out = Vec::with_capacity(bytes.len());
+        let mut i = 0;
+        while i < bytes.len() {
+            if bytes[i] == b'%' && i + 2 < bytes.len() {
+                let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).unwrap_or("");
+                if let Ok(b) = u8::from_str_radix(hex, 16) {
+                    out.push(b);
+                    i += 3;
+                    continue;
+                }
+            }
+            out.push(bytes[i]);
+            i += 1;
+        }
+        PathBuf::from(OsString::from_vec(out))
+    }
+    fn reserve_info_file(trash: &TrashDir, name: &str, contents: &str) -> io::Result<String> {
+        let stem = Path::new(name)
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_else(|| name.to_string());
+        let ext = Path::new(name)
+            .extension()
+            .map(|e| format!(".{}", e.to_string_lossy()))
+            .unwrap_or_default();
+        for attempt in 1.. {
+            let candidate = if attempt == 1 {
+                name.to_string()
+            } else {
+                format!("{}.{}{}", stem, attempt, ext)
+            };
+            // A dangling symlink still occupies the name.
+            if fs::symlink_metadata(trash.files().join(&candidate)).is_ok() {
+                continue;
+            }
+            let info_path = trash.info().join(format!("{}.trashinfo", candidate));
+            match OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&info_path)
+            {
+                Ok(mut file) => {
+                    file.write_all(contents.as_bytes())?;
+                    return Ok(candidate);
+                }
+                Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+                Err(e) => return Err(e),
+            }
+        }
+        unreachable!()
+    }
+    // Renames when possible and otherwise copies, since the home trash may be on another device.
+    fn move_entry(source: &Path, target: &Path) -> io::Result<()> {
+        match fs::rename(source, target) {
+            Err(e) if transfer::is_cross_device(&e) => {
+                if let Err(e) = transfer::copy_entry(source, target) {
+                    let _ = transfer::remove_existing(target);
+                    return Err(e);
+                }
+                transfer::remove_existing(source)
+            }
+            result => result,
+        }
+    }
+    pub fn move_to_trash(path: &Path) -> Result<(), String> {
+        // Only the parent is resolved so a symlink is trashed itself, not its target.
+        let entry = path.file_name().ok_or("Cannot trash the filesystem root")?;
+        let parent = path.parent().unwrap_or(Path::new("/"));
+        let path = fs::canonicalize(parent)
+            .map_err(|e| e.to_string())?
+            .join(entry);
+        let mut result = Err("No usable trash directory".to_string());
+        for trash in trash_for(&path)? {
+            result = trash_into(&trash, &path);
+            match &result {
+                Ok(()) => break,
+                Err(e) => eprintln!("Cannot use trash {:?} for {:?}: {}", trash.base, path, e),
+            }
+        }
+        result
+    }
+    fn trash_into(trash: &TrashDir, path: &Path) -> Result<(), String> {
+        let existed = trash.base.exists();
+        for dir in [trash.files(), trash.info()] {
+            fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+        }
+        if !existed {
+            let _ = fs::set_permissions(&trash.base, fs::Permissions::from_mode(0o700));
+        }
+        let recorded = match &trash.topdir {
+            Some(top) => path.strip_prefix(top).unwrap_or(path).to_path_buf(),
+            None => path.to_path_buf(),
+        };
+        let info = format!(
+            "[Trash Info]\nPath={}\nDeletionDate={}\n",
+            encode_path(&recorded),
+            Local::now().format(DATE_FORMAT)
+        );
+        let name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .ok_or("Cannot trash the filesystem root")?;
+        let trashed_name = reserve_info_file(trash, &name, &info).map_err(|e| e.to_string())?;
+        if let Err(e) = move_entry(path, &trash.files().join(&trashed_name)) {
+            let _ = fs::remove_file(trash.info().join(format!("{}.trashinfo", trashed_name)));
+            return Err(format!("Failed to move {:?} to trash: {}", path, e));
+        }
+        Ok(())
+    }
+    fn parse_info(trash: &TrashDir, info_path: &Path) -> Option<TrashedItem> {
+        let content = fs::read_to_string(info_path).ok()?;
+        let mut original = None;
+        let mut deleted_at = 0;
+        for line in content.lines() {
+            if let Some(value) = line.strip_prefix("Path=") {
+                let decoded = decode_path(value);
+                original = Some(match &trash.topdir {
+                    Some(top) if decoded.is_relative() => top.join(decoded),
+                    _ => decoded,
+                });
+            } else if let Some(value) = line.strip_prefix("DeletionDate=") {
+                deleted_at = NaiveDateTime::parse_from_str(value, DATE_FORMAT)
+                    .ok()
+                    .and_then(|d| Local.from_local_datetime(&d).earliest())
+                    .map(|d| d.timestamp())
+                    .unwrap_or(0);
+            }
+        }
+        let trashed_name = info_path.file_name()?.to_string_lossy();
+        let trashed_name = trashed_name.strip_suffix(".trashinfo")?;
+        let file_path = trash.files().join(trashed_name);
+        let original = original?;
+        Some(TrashedItem {
+            id: file_path.to_string_lossy().to_string(),
+            name: original
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            original_path: original.to_string_lossy().to_string(),
+            deleted_at,
+            is_dir: fs::symlink_metadata(&file_path).ok()?.is_dir(),
+        })
+    }
+    pub fn list(workspace: &Path) -> Result<Vec<TrashedItem>, String> {
+        let workspace = fs::canonicalize(workspace).map_err(|e| e.to_string())?;
+        let mut trashes = vec![home_trash()?];
+        trashes.extend(
+            topdir_trashes(&mount_point(&workspace))
+                .into_iter()
+                .filter(|local| local.base.exists()),
+        );
+        let mut items = Vec::new();
+        for trash in &trashes {
+            let entries = match fs::read_dir(trash.info()) {
+                Ok(entries) => entries,
+                Err(_) => continue,
+            };
+            for entry in entries.flatten() {
+                if let Some(item) = parse_info(trash, &entry.path()) {
+                    if Path::new(&item.original_path).starts_with(&workspace) {
+                        items.push(item);
+                    }
+                }
+            }
+        }
+        items.sort_by_key(|item| std::cmp::Reverse(item.deleted_at));
+        Ok(items)
+    }
+    // The home trash plus the top-directory trash of every open workspace's volume.
+    fn known_trashes() -> Result<Vec<TrashDir>, String> {
+        let mut trashes = vec![home_trash()?];
+        let real_dir = |p: &Path| fs::symlink_metadata(p).is_ok_and(|m| m.is_dir());
+        for root in workspace::roots() {
+            for local in topdir_trashes(&mount_point(&root)) {
+                if real_dir(&local.base)
+                    && real_dir(&local.files())
+                    && !trashes.iter().any(|t| t.base == local.base)
+                {
+                    trashes.push(local);
+                }
+            }
+        }
+        Ok(trashes)
+    }
+    pub fn restore(id: &str) -> Result<String, FsError> {
+        let file_path = PathBuf::from(id);
+        let name = match file_path.components().next_back() {
+            Some(Component::Normal(name)) => name.to_string_lossy().to_string(),
+            _ => return Err("Invalid trash item".into()),
+        };
+        let trash = known_trashes()?
+            .into_iter()
+            .find(|trash| file_path.parent() == Some(trash.files().as_path()))
+            .ok_or("Item is not inside a known trash directory")?;
+        fs::symlink_metadata(&file_path)?;
+        let info_path = trash.info().join(format!("{}.trashinfo", name));
+        let item = parse_info(&trash, &info_path).ok_or("Trash item metadata not found")?;
+        let original = PathBuf::from(&item.original_path);
+        crate::workspace::resolve(&item.original_path)?;
+        if original.exists() {
+            return Err(format!("{} already exists", item.original_path).into());
+        }
+        if let Some(parent) = original.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        move_entry(&file_path, &original)?;
+        let _ = fs::remove_file(info_path);
+        Ok(item.original_path)
+    }
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        #[test]
+        fn trash_paths_round_trip_through_percent_encoding() {
+            let path = Path::new("/home/me/My Files/50%/naïve.txt");
+            let encoded = encode_path(path);
+            assert_eq!(encoded, "/home/me/My%20Files/50%25/na%C3%AFve.txt");
+            assert_eq!(decode_path(&encoded), path);
+            assert_eq!(decode_path("100%"), Path::new("100%"));
+        }
+        #[test]
+        fn info_files_resolve_against_the_top_directory() {
+            let dir = tempfile::tempdir().unwrap();
+            let trash = TrashDir {
+                base: dir.path().join(".Trash-1000"),
+                topdir: Some(dir.path().to_path_buf()),
+            };
+            fs::create_dir_all(trash.info()).unwrap();
+            fs::create_dir_all(trash.files().join("notes.2")).unwrap();
+            let info_path = trash.info().join("notes.2.trashinfo");
+            fs::write(
+                &info_path,
+                "[Trash Info]\nPath=docs/old%20notes\nDeletionDate=2024-01-02T03:04:05\n",
+            )
+            .unwrap();
+            let item = parse_info(&trash, &info_path).unwrap();
+            assert_eq!(
+                Path::new(&item.original_path),
+                dir.path().join("docs/old notes")
+            );
+            assert_eq!(item.name, "old notes");
+            assert_eq!(Path::new(&item.id), trash.files().join("notes.2"));
+            assert!(item.is_dir);
+            assert!(item.deleted_at > 0);
+        }
+        #[test]
+        fn info_files_without_a_trashed_entry_are_skipped() {
+            let dir = tempfile::tempdir().unwrap();
+            let trash = TrashDir {
+                base: dir.path().to_path_buf(),
+                topdir: None,
+            };
+            fs::create_dir_all(trash.info()).unwrap();
+            let info_path = trash.info().join("gone.txt.trashinfo");
+            fs::write(&info_path, "[Trash Info]\nPath=/tmp/gone.txt\n").unwrap();
+            assert!(parse_info(&trash, &info_path).is_none());
+        }
+        #[test]
+        fn dangling_symlinks_keep_their_trash_name() {
+            let dir = tempfile::tempdir().unwrap();
+            let trash = TrashDir {
+                base: dir.path().to_path_buf(),
+                topdir: None,
+            };
+            fs::create_dir_all(trash.files()).unwrap();
+            fs::create_dir_all(trash.info()).unwrap();
+            std::os::unix::fs::symlink("missing", trash.files().join("a.txt")).unwrap();
+            let name = reserve_info_file(&trash, "a.txt", "[Trash Info]\n").unwrap();
+            assert_eq!(name, "a.2.txt");
+        }
+        #[test]
+        fn the_per_user_top_directory_trash_follows_the_shared_one() {
+            let dir = tempfile::tempdir().unwrap();
+            let uid = unsafe { libc::getuid() };
+            let bases = |top: &Path| -> Vec<PathBuf> {
+                topdir_trashes(top).into_iter().map(|t| t.base).collect()
+            };
+            let own = dir.path().join(format!(".Trash-{}", uid));
+            assert_eq!(bases(dir.path()), vec![own.clone()]);
+            let shared = dir.path().join(".Trash");
+            fs::create_dir(&shared).unwrap();
+            assert_eq!(bases(dir.path()), vec![own.clone()]);
+            fs::set_permissions(&shared, fs::Permissions::from_mode(0o1777)).unwrap();
+            assert_eq!(
+                bases(dir.path()),
+                vec![shared.join(uid.to_string()), own.clone()]
+            );
+            // An unusable shared trash leaves the per-user one to try next.
+            fs::write(shared.join(uid.to_string()), "").unwrap();
+            let trashes = topdir_trashes(dir.path());
+            let file = dir.path().join("doomed.txt");
+            fs::write(&file, "x").unwrap();
+            assert!(trash_into(&trashes[0], &file).is_err());
+            trash_into(&trashes[1], &file).unwrap();
+            assert!(!file.exists());
+            assert!(own.join("files/doomed.txt").exists());
+        }
+    }
+}
+#[cfg(target_os = "linux")]
+pub fn move_to_trash(path: &Path) -> Result<(), String> {
+    xdg::move_to_trash(path)
+}
+#[cfg(not(target_os = "linux"))]
+pub fn move_to_trash(path: &Path) -> Result<(), String> {
+    trash::delete(path).map_err(|e| e.to_string())
+}
+#[tauri::command]
+pub fn list_trashed_items(workspace: String) -> Result<Vec<TrashedItem>, FsError> {
+    let workspace = workspace::resolve(&workspace)?;
+    #[cfg(target_os = "linux")]
+    return Ok(xdg::list(&workspace)?);
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = workspace;
+        Err("Restoring from trash is only supported on Linux".into())
+    }
+}
+#[tauri::command]
+pub fn restore_trashed_item(id: String) -> Result<String, FsError> {
+    #[cfg(target_os = "linux")]
+    return xdg::restore(&id);
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = id;
+        Err("Restoring from trash is only supported on Linux".into())
+    }
+}
@@ -0,0 +1,783 @@
+use crate::encoding;
+use crate::error::FsError;
+use crate::filesystem::{content_hash, write_private};
+use crate::models::SearchOptions;
+use crate::storage;
+use crate::walker;
+use crate::watcher::{self, FsChange};
+use crate::workspace;
+use chrono::Utc;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use regex_syntax::hir::{Hir, HirKind};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, UNIX_EPOCH};
+const MAGIC: &[u8; 8] = b"MIDETRI1";
+const MAX_INDEXED_FILE_SIZE: u64 = 2 * 1024 * 1024;
+const INDEX_FILE: &str = "trigrams.bin.gz";
+const CONFIG_FILE: &str = "settings.json";
+const SAVE_DELAY: Duration = Duration::from_secs(30);
+struct IndexedFile {
+    path: PathBuf,
+    mtime: u64,
+    size: u64,
+    live: bool,
+    // Files over the size limit have no postings and are always handed to the exact matcher.
+    indexed: bool,
+}
+struct ScannedFile {
+    mtime: u64,
+    size: u64,
+    grams: Option<Vec<u32>>,
+}
+// Work for one touched path, read from disk before the index lock is taken.
+enum Update {
+    Remove(PathBuf),
+    Replace(PathBuf, Option<ScannedFile>),
+}
+#[derive(Default)]
+struct TrigramIndex {
+    files: Vec<IndexedFile>,
+    ids: HashMap<PathBuf, u32>,
+    postings: HashMap<u32, Vec<u32>>,
+}
+#[derive(Default)]
+struct IndexState {
+    index: Option<TrigramIndex>,
+    building: bool,
+    pending: Vec<PathBuf>,
+    // A from-scratch rebuild asked for while another build was running.
+    rebuild_queued: bool,
+    updated_at: Option<i64>,
+    // Bumped by every build so a pass that was superseded or switched off does not install or save.
+    generation: u64,
+    save_scheduled: bool,
+}
+#[derive(Serialize, Deserialize, Default)]
+struct IndexConfig {
+    enabled: bool,
+}
+#[derive(Serialize, Debug, Clone)]
+pub struct IndexStatus {
+    pub enabled: bool,
+    pub building: bool,
+    pub ready: bool,
+    pub files: usize,
+    pub trigrams: usize,
+    pub updated_at: Option<i64>,
+}
+lazy_static::lazy_static! {
+    static ref INDEXES: Mutex<HashMap<PathBuf, IndexState>> = Mutex::new(HashMap::new());
+}
+static NEXT_GENERATION: AtomicU64 = AtomicU64::new(1);
+fn pack(window: &[u8]) -> u32 {
+    (window[0] as u32) << 16 | (window[1] as u32) << 8 | window[2] as u32
+}
+fn trigrams(text: &[u8]) -> Vec<u32> {
+    let mut grams: Vec<u32> = text.windows(3).map(pack).collect();
+    grams.sort_unstable();
+    grams.dedup();
+    grams
+}
+fn scan(path: &Path) -> Option<ScannedFile> {
+    let metadata = fs::metadata(path).ok().filter(|m| m.is_file())?;
+    let (mtime, size) = stamp(&metadata);
+    let grams = if size <= MAX_INDEXED_FILE_SIZE {
+        let decoded = encoding::decode(&fs::read(path).ok()?);
+        // Binary files are never searched, so they are recorded without postings.
+        if decoded.is_binary {
+            Some(Vec::new())
+        } else {
+            Some(trigrams(decoded.content.to_lowercase().as_bytes()))
+        }
+    } else {
+        None
+    };
+    Some(ScannedFile { mtime, size, grams })
+}
+fn scan_updates(root: &Path, relative: &Path) -> Vec<Update> {
+    let path = root.join(relative);
+    if path.is_dir() {
+        walker::walk_subtree(root, &path)
+            .filter_map(|file| {
+                let r = file.strip_prefix(root).ok()?.to_path_buf();
+                Some(Update::Replace(r, scan(&file)))
+            })
+            .collect()
+    } else if path.exists() {
+        vec![Update::Replace(relative.to_path_buf(), scan(&path))]
+    } else {
+        vec![Update::Remove(relative.to_path_buf())]
+    }
+}
+fn stamp(metadata: &fs::Metadata) -> (u64, u64) {
+    let mtime = metadata
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0);
+    (mtime, metadata.len())
+}
+fn intersect(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let (mut i, mut j) = (0, 0);
+    let mut out = Vec::new();
+    while i < a.len() && j < b.len() {
+        match a[i].cmp(&b[j]) {
+            std::cmp::Ordering::Less => i += 1,
+            std::cmp::Ordering::Greater => j += 1,
+            std::cmp::Ordering::Equal => {
+                out.push(a[i]);
+                i += 1;
+                j += 1;
+            }
+        }
+    }
+    out
+}
+fn read_u32(input: &mut impl Read) -> io::Result<u32> {
+    let mut buf = [0u8; 4];
+    input.read_exact(&mut buf)?;
+    Ok(u32::from_le_bytes(buf))
+}
+fn read_u64(input: &mut impl Read) -> io::Result<u64> {
+    let mut buf = [0u8; 8];
+    input.read_exact(&mut buf)?;
+    Ok(u64::from_le_bytes(buf))
+}
+fn corrupt(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+// Counts come from disk, so one that could not fit in the rest of the file is rejected before allocating.
+fn read_count(input: &mut &[u8], entry_size: usize) -> io::Result<usize> {
+    let count = read_u32(input)? as usize;
+    if count.saturating_mul(entry_size) > input.len() {
+        return Err(corrupt("Truncated index"));
+    }
+    Ok(count)
+}
+impl TrigramIndex {
+    fn live_files(&self) -> usize {
+        self.ids.len()
+    }
+    fn is_fresh(&self, relative: &Path, metadata: &fs::Metadata) -> bool {
+        let (mtime, size) = stamp(metadata);
+        self.ids
+            .get(relative)
+            .map(|&id| &self.files[id as usize])
+            .is_some_and(|f| f.mtime == mtime && f.size == size)
+    }
+    fn remove_prefix(&mut self, relative: &Path) {
+        let dead: Vec<PathBuf> = self
+            .ids
+            .keys()
+            .filter(|path| path.starts_with(relative))
+            .cloned()
+            .collect();
+        for path in dead {
+            if let Some(id) = self.ids.remove(&path) {
+                self.files[id as usize].live = false;
+            }
+        }
+    }
+    fn insert(&mut self, relative: PathBuf, scanned: Option<ScannedFile>) {
+        if let Some(id) = self.ids.remove(&relative) {
+            self.files[id as usize].live = false;
+        }
+        let scanned = match scanned {
+            Some(scanned) => scanned,
+            None => return,
+        };
+        let id = self.files.len() as u32;
+        self.files.push(IndexedFile {
+            path: relative.clone(),
+            mtime: scanned.mtime,
+            size: scanned.size,
+            live: true,
+            indexed: scanned.grams.is_some(),
+        });
+        self.ids.insert(relative, id);
+        for gram in scanned.grams.unwrap_or_default() {
+            self.postings.entry(gram).or_default().push(id);
+        }
+    }
+    fn apply(&mut self, updates: Vec<Update>) {
+        for update in updates {
+            match update {
+                Update::Remove(relative) => self.remove_prefix(&relative),
+                Update::Replace(relative, scanned) => self.insert(relative, scanned),
+            }
+        }
+    }
+    fn compact(&mut self) {
+        let mut remap = vec![u32::MAX; self.files.len()];
+        let mut files = Vec::with_capacity(self.ids.len());
+        for (old, file) in std::mem::take(&mut self.files).into_iter().enumerate() {
+            if file.live {
+                remap[old] = files.len() as u32;
+                files.push(file);
+            }
+        }
+        self.postings.retain(|_, ids| {
+            ids.retain_mut(|id| {
+                *id = remap[*id as usize];
+                *id != u32::MAX
+            });
+            !ids.is_empty()
+        });
+        self.ids = files
+            .iter()
+            .enumerate()
+            .map(|(id, f)| (f.path.clone(), id as u32))
+            .collect();
+        self.files = files;
+    }
+    fn candidates(&self, literals: &[Vec<u8>]) -> Vec<PathBuf> {
+        let mut matched: Option<Vec<u32>> = None;
+        for gram in literals.iter().flat_map(|l| l.windows(3).map(pack)) {
+            let postings = self
+                .postings
+                .get(&gram)
+                .map(|p| p.as_slice())
+                .unwrap_or(&[]);
+            matched = Some(match matched {
+                Some(ids) => intersect(&ids, postings),
+                None => postings.to_vec(),
+            });
+        }
+        let matched = matched.unwrap_or_default();
+        let mut paths: Vec<PathBuf> = matched
+            .iter()
+            .map(|&id| &self.files[id as usize])
+            .filter(|f| f.live)
+            .map(|f| f.path.clone())
+            .collect();
+        paths.extend(
+            self.files
+                .iter()
+                .filter(|f| f.live && !f.indexed)
+                .map(|f| f.path.clone()),
+        );
+        paths
+    }
+    fn write(&self, out: &mut impl Write) -> io::Result<()> {
+        out.write_all(MAGIC)?;
+        out.write_all(&(self.files.len() as u32).to_le_bytes())?;
+        for file in &self.files {
+            let path = file.path.to_string_lossy();
+            out.write_all(&[file.live as u8 | (file.indexed as u8) << 1])?;
+            out.write_all(&file.mtime.to_le_bytes())?;
+            out.write_all(&file.size.to_le_bytes())?;
+            out.write_all(&(path.len() as u32).to_le_bytes())?;
+            out.write_all(path.as_bytes())?;
+        }
+        out.write_all(&(self.postings.len() as u32).to_le_bytes())?;
+        for (gram, ids) in &self.postings {
+            out.write_all(&gram.to_le_bytes())?;
+            out.write_all(&(ids.len() as u32).to_le_bytes())?;
+            for id in ids {
+                out.write_all(&id.to_le_bytes())?;
+            }
+        }
+        Ok(())
+    }
+    fn read(mut input: &[u8]) -> io::Result<Self> {
+        let input = &mut input;
+        let mut magic = [0u8; 8];
+        input.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(corrupt("Unknown index format"));
+        }
+        let mut index = TrigramIndex::default();
+        // Flags, mtime, size and path length.
+        let file_count = read_count(input, 21)?;
+        for id in 0..file_count as u32 {
+            let mut flags = [0u8; 1];
+            input.read_exact(&mut flags)?;
+            let mtime = read_u64(input)?;
+            let size = read_u64(input)?;
+            let mut path = vec![0u8; read_count(input, 1)?];
+            input.read_exact(&mut path)?;
+            let path = PathBuf::from(String::from_utf8_lossy(&path).to_string());
+            let live = flags[0] & 1 != 0;
+            if live {
+                index.ids.insert(path.clone(), id);
+            }
+            index.files.push(IndexedFile {
+                path,
+                mtime,
+                size,
+                live,
+                indexed: flags[0] & 2 != 0,
+            });
+        }
+        // Trigram and id count.
+        for _ in 0..read_count(input, 8)? {
+            let gram = read_u32(input)?;
+            let ids = (0..read_count(input, 4)?)
+                .map(|_| read_u32(input))
+                .collect::<io::Result<Vec<_>>>()?;
+            if ids.iter().any(|&id| id as usize >= file_count) {
+                return Err(corrupt("Index refers to an unknown file"));
+            }
+            index.postings.insert(gram, ids);
+        }
+        Ok(index)
+    }
+}
+fn index_dir(root: &Path) -> PathBuf {
+    storage::data_dir()
+        .join("index")
+        .join(content_hash(root.to_string_lossy().as_bytes()))
+}
+fn load_config(root: &Path) -> IndexConfig {
+    fs::read(index_dir(root).join(CONFIG_FILE))
+        .ok()
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        .unwrap_or_default()
+}
+fn save_config(root: &Path, config: &IndexConfig) -> io::Result<()> {
+    let dir = index_dir(root);
+    fs::create_dir_all(&dir)?;
+    let bytes = serde_json::to_vec(config).map_err(io::Error::other)?;
+    write_private(&dir.join(CONFIG_FILE), &bytes)
+}
+fn load_index(root: &Path) -> io::Result<TrigramIndex> {
+    let file = File::open(index_dir(root).join(INDEX_FILE))?;
+    let mut raw = Vec::new();
+    GzDecoder::new(file).read_to_end(&mut raw)?;
+    TrigramIndex::read(&raw)
+}
+fn save_index(root: &Path, index: &TrigramIndex) -> io::Result<()> {
+    let mut raw = Vec::new();
+    index.write(&mut raw)?;
+    save_raw_index(root, &raw)
+}
+// Compression happens here so callers holding the index lock only pay for the plain copy.
+fn save_raw_index(root: &Path, raw: &[u8]) -> io::Result<()> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::fast());
+    encoder.write_all(raw)?;
+    write_private(&index_dir(root).join(INDEX_FILE), &encoder.finish()?)
+}
+fn refresh(root: &Path, mut index: TrigramIndex) -> TrigramIndex {
+    let mut seen = HashSet::new();
+    for file in walker::walk_files(root) {
+        let relative = match file.strip_prefix(root) {
+            Ok(r) => r.to_path_buf(),
+            Err(_) => continue,
+        };
+        let fresh = fs::metadata(&file).is_ok_and(|m| index.is_fresh(&relative, &m));
+        if !fresh {
+            index.insert(relative.clone(), scan(&file));
+        }
+        seen.insert(relative);
+    }
+    let stale: Vec<PathBuf> = index
+        .ids
+        .keys()
+        .filter(|path| !seen.contains(*path))
+        .cloned()
+        .collect();
+    for path in stale {
+        index.remove_prefix(&path);
+    }
+    index.compact();
+    index
+}
+fn is_current(root: &Path, generation: u64) -> bool {
+    INDEXES
+        .lock()
+        .unwrap()
+        .get(root)
+        .is_some_and(|s| s.generation == generation)
+}
+fn start_build(root: PathBuf, from_scratch: bool) {
+    let generation = {
+        let mut indexes = INDEXES.lock().unwrap();
+        let state = indexes.entry(root.clone()).or_default();
+        if state.building {
+            state.rebuild_queued |= from_scratch;
+            return;
+        }
+        state.building = true;
+        state.rebuild_queued = false;
+        state.pending.clear();
+        state.generation = NEXT_GENERATION.fetch_add(1, Ordering::Relaxed);
+        state.generation
+    };
+    thread::spawn(move || {
+        let base = if from_scratch {
+            TrigramIndex::default()
+        } else {
+            // A missing or unreadable index is rebuilt from scratch and overwritten below.
+            load_index(&root).unwrap_or_else(|e| {
+                if e.kind() != io::ErrorKind::NotFound {
+                    eprintln!("Rebuilding search index for {:?}: {}", root, e);
+                }
+                TrigramIndex::default()
+            })
+        };
+        let mut index = refresh(&root, base);
+        // The index may have been switched off while it was being built.
+        if !is_current(&root, generation) {
+            return;
+        }
+        if let Err(e) = save_index(&root, &index) {
+            eprintln!("Failed to save search index for {:?}: {}", root, e);
+        }
+        loop {
+            let pending = {
+                let mut indexes = INDEXES.lock().unwrap();
+                let state = match indexes.get_mut(&root) {
+                    Some(state) if state.generation == generation => state,
+                    _ => {
+                        drop(indexes);
+                        // Switched off during the save; do not leave the file behind.
+                        if !load_config(&root).enabled {
+                            let _ = fs::remove_file(index_dir(&root).join(INDEX_FILE));
+                        }
+                        return;
+                    }
+                };
+                if state.pending.is_empty() {
+                    state.index = Some(index);
+                    state.building = false;
+                    state.updated_at = Some(Utc::now().timestamp_millis());
+                    let rebuild = std::mem::take(&mut state.rebuild_queued);
+                    drop(indexes);
+                    if rebuild {
+                        start_build(root, true);
+                    }
+                    return;
+                }
+                std::mem::take(&mut state.pending)
+            };
+            for relative in pending {
+                index.apply(scan_updates(&root, &relative));
+            }
+        }
+    });
+}
+// Writes incremental updates back after a quiet period instead of on every change.
+fn schedule_save(root: PathBuf, generation: u64) {
+    thread::spawn(move || {
+        thread::sleep(SAVE_DELAY);
+        let raw = {
+            let mut indexes = INDEXES.lock().unwrap();
+            let state = match indexes.get_mut(&root) {
+                Some(state) => state,
+                None => return,
+            };
+            state.save_scheduled = false;
+            // A rebuild started since then saves its own result.
+            if state.generation != generation {
+                return;
+            }
+            let mut raw = Vec::new();
+            match state.index.as_ref().map(|index| index.write(&mut raw)) {
+                Some(Ok(())) => raw,
+                _ => return,
+            }
+        };
+        if let Err(e) = save_raw_index(&root, &raw) {
+            eprintln!("Failed to save search index for {:?}: {}", root, e);
+        }
+    });
+}
+fn status_for(root: &Path) -> IndexStatus {
+    let indexes = INDEXES.lock().unwrap();
+    let state = indexes.get(root);
+    let index = state.and_then(|s| s.index.as_ref());
+    IndexStatus {
+        enabled: load_config(root).enabled,
+        building: state.is_some_and(|s| s.building),
+        ready: index.is_some(),
+        files: index.map(|i| i.live_files()).unwrap_or(0),
+        trigrams: index.map(|i| i.postings.len()).unwrap_or(0),
+        updated_at: state.and_then(|s| s.updated_at),
+    }
+}
+fn collect_literals(hir: &Hir, out: &mut Vec<Vec<u8>>) {
+    match hir.kind() {
+        HirKind::Literal(literal) => out.push(literal.0.to_vec()),
+        HirKind::Capture(capture) => collect_literals(&capture.sub, out),
+        HirKind::Repetition(repetition) if repetition.min >= 1 => {
+            collect_literals(&repetition.sub, out)
+        }
+        HirKind::Concat(subs) => {
+            let mut run = Vec::new();
+            for sub in subs {
+                match sub.kind() {
+                    HirKind::Literal(literal) => run.extend_from_slice(&literal.0),
+                    _ => {
+                        if !run.is_empty() {
+                            out.push(std::mem::take(&mut run));
+                        }
+                        collect_literals(sub, out);
+                    }
+                }
+            }
+            if !run.is_empty() {
+                out.push(run);
+            }
+        }
+        _ => {}
+    }
+}
+fn required_literals(query: &str, options: &SearchOptions) -> Vec<Vec<u8>> {
+    let literals = if options.regex {
+        let hir = match regex_syntax::ParserBuilder::new()
+            .multi_line(options.multiline)
+            .build()
+            .parse(query)
+        {
+            Ok(hir) => hir,
+            Err(_) => return Vec::new(),
+        };
+        let mut out = Vec::new();
+        collect_literals(&hir, &mut out);
+        out
+    } else {
+        vec![query.as_bytes().to_vec()]
+    };
+    literals
+        .into_iter()
+        .map(|l| String::from_utf8_lossy(&l).to_lowercase().into_bytes())
+        .filter(|l| l.len() >= 3)
+        .collect()
+}
+pub fn candidates(root: &Path, query: &str, options: &SearchOptions) -> Option<Vec<PathBuf>> {
+    let literals = required_literals(query, options);
+    // Only watched roots get change notifications, so an unwatched index may be stale.
+    if literals.is_empty() || !watcher::is_watching(root) {
+        return None;
+    }
+    let indexes = INDEXES.lock().unwrap();
+    let index = indexes.get(root)?.index.as_ref()?;
+    Some(index.candidates(&literals))
+}
+pub fn ensure_loaded(root: &Path) {
+    let loaded = INDEXES.lock().unwrap().contains_key(root);
+    if !loaded && load_config(root).enabled {
+        start_build(root.to_path_buf(), false);
+    }
+}
+// Drops the in-memory index of a closed workspace, writing back updates that were not saved yet.
+pub fn unload(root: &Path) {
+    let state = INDEXES.lock().unwrap().remove(root);
+    if let Some(IndexState {
+        index: Some(index),
+        save_scheduled: true,
+        ..
+    }) = state
+    {
+        let root = root.to_path_buf();
+        thread::spawn(move || {
+            if let Err(e) = save_index(&root, &index) {
+                eprintln!("Failed to save search index for {:?}: {}", root, e);
+            }
+        });
+    }
+}
+pub fn apply_changes(root: &Path, changes: &[FsChange]) {
+    let touched: Vec<PathBuf> = changes
+        .iter()
+        .flat_map(FsChange::paths)
+        .filter_map(|path| Path::new(path).strip_prefix(root).ok())
+        .map(Path::to_path_buf)
+        .collect();
+    {
+        let mut indexes = INDEXES.lock().unwrap();
+        let state = match indexes.get_mut(root) {
+            Some(state) => state,
+            None => return,
+        };
+        // A running build rescans these before it installs its index.
+        if state.building {
+            state.pending.extend(touched.iter().cloned());
+        }
+        if state.index.is_none() {
+            return;
+        }
+    }
+    // Files are read without the lock so searches are not blocked behind the disk.
+    let updates: Vec<Update> = touched
+        .iter()
+        .flat_map(|relative| scan_updates(root, relative))
+        .collect();
+    let mut indexes = INDEXES.lock().unwrap();
+    let state = match indexes.get_mut(root) {
+        Some(state) => state,
+        None => return,
+    };
+    if let Some(index) = state.index.as_mut() {
+        index.apply(updates);
+        if index.files.len() > index.live_files() * 2 + 1024 {
+            index.compact();
+        }
+        state.updated_at = Some(Utc::now().timestamp_millis());
+        if !state.save_scheduled {
+            state.save_scheduled = true;
+            schedule_save(root.to_path_buf(), state.generation);
+        }
+    }
+}
+#[tauri::command]
+pub fn search_index_status(path: String) -> Result<IndexStatus, FsError> {
+    Ok(status_for(&workspace::resolve(&path)?))
+}
+#[tauri::command]
+pub fn set_search_index_enabled(path: String, enabled: bool) -> Result<IndexStatus, FsError> {
+    let root = workspace::resolve(&path)?;
+    save_config(&root, &IndexConfig { enabled })?;
+    if enabled {
+        start_build(root.clone(), false);
+    } else {
+        INDEXES.lock().unwrap().remove(&root);
+        let _ = fs::remove_file(index_dir(&root).join(INDEX_FILE));
+    }
+    Ok(status_for(&root))
+}
+#[tauri::command]
+pub fn rebuild_search_index(path: String) -> Result<IndexStatus, FsError> {
+    let root = workspace::resolve(&path)?;
+    if !load_config(&root).enabled {
+        return Err("The search index is turned off for this project"
+            .to_string()
+            .into());
+    }
+    start_build(root.clone(), true);
+    Ok(status_for(&root))
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    fn scanned(text: &str) -> Option<ScannedFile> {
+        Some(ScannedFile {
+            mtime: 0,
+            size: text.len() as u64,
+            grams: Some(trigrams(text.as_bytes())),
+        })
+    }
+    fn sample_index() -> TrigramIndex {
+        let mut index = TrigramIndex::default();
+        index.insert(PathBuf::from("a.rs"), scanned("fn parse_args()"));
+        index.insert(PathBuf::from("b.rs"), scanned("fn main()"));
+        index.insert(
+            PathBuf::from("huge.log"),
+            Some(ScannedFile {
+                mtime: 0,
+                size: MAX_INDEXED_FILE_SIZE + 1,
+                grams: None,
+            }),
+        );
+        index
+    }
+    #[test]
+    fn plain_queries_need_their_lowercased_text() {
+        let options = SearchOptions::default();
+        assert_eq!(
+            required_literals("Parse", &options),
+            vec![b"parse".to_vec()]
+        );
+        assert!(required_literals("fn", &options).is_empty());
+    }
+    #[test]
+    fn regex_queries_need_each_literal_run() {
+        let options = SearchOptions {
+            regex: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            required_literals(r"fn\s+parse_\w+", &options),
+            vec![b"parse_".to_vec()]
+        );
+        assert!(required_literals("main|parse", &options).is_empty());
+        assert!(required_literals("(", &options).is_empty());
+    }
+    #[test]
+    fn candidates_intersect_postings_and_keep_unindexed_files() {
+        let mut index = sample_index();
+        let literals = [b"parse_".to_vec()];
+        assert_eq!(
+            index.candidates(&literals),
+            vec![PathBuf::from("a.rs"), PathBuf::from("huge.log")]
+        );
+        index.apply(vec![Update::Remove(PathBuf::from("a.rs"))]);
+        assert_eq!(index.candidates(&literals), vec![PathBuf::from("huge.log")]);
+    }
+    #[test]
+    fn compacted_index_survives_a_write_and_read() {
+        let mut index = sample_index();
+        index.insert(PathBuf::from("b.rs"), scanned("fn parse_env()"));
+        index.compact();
+        let mut raw = Vec::new();
+        index.write(&mut raw).unwrap();
+        let restored = TrigramIndex::read(&raw).unwrap();
+        let mut found = restored.candidates(&[b"parse_".to_vec()]);
+        found.sort();
+        assert_eq!(
+            found,
+            vec![
+                PathBuf::from("a.rs"),
+                PathBuf::from("b.rs"),
+                PathBuf::from("huge.log")
+            ]
+        );
+        assert_eq!(restored.live_files(), 3);
+    }
+    #[test]
+    fn corrupt_counts_and_ids_are_rejected() {
+        let mut raw = Vec::new();
+        sample_index().write(&mut raw).unwrap();
+        assert!(TrigramIndex::read(&raw[..raw.len() - 1]).is_err());
+        let mut huge_count = raw.clone();
+        huge_count[8..12].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(TrigramIndex::read(&huge_count).is_err());
+        let mut unknown_id = raw.clone();
+        let last = unknown_id.len() - 4;
+        unknown_id[last..].copy_from_slice(&99u32.to_le_bytes());
+        let err = TrigramIndex::read(&unknown_id).err().unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+    #[test]
+    fn rebuilds_requested_during_a_build_are_queued() {
+        let root = PathBuf::from("/mide-test/queued-rebuild");
+        INDEXES.lock().unwrap().insert(
+            root.clone(),
+            IndexState {
+                building: true,
+                generation: 7,
+                ..Default::default()
+            },
+        );
+        start_build(root.clone(), false);
+        assert!(!INDEXES.lock().unwrap()[&root].rebuild_queued);
+        start_build(root.clone(), true);
+        let state = INDEXES.lock().unwrap().remove(&root).unwrap();
+        assert!(state.rebuild_queued);
+        assert_eq!(state.generation, 7);
+    }
+    #[test]
+    fn unwatched_roots_do_not_use_their_index() {
+        let root = PathBuf::from("/mide-test/unwatched");
+        INDEXES.lock().unwrap().insert(
+            root.clone(),
+            IndexState {
+                index: Some(sample_index()),
+                ..Default::default()
+            },
+        );
+        let found = candidates(&root, "parse", &SearchOptions::default());
+        INDEXES.lock().unwrap().remove(&root);
+        assert!(found.is_none());
+    }
+}
@@ -0,0 +1,258 @@
+use crate::project_settings;
+use crate::watcher;
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ignore::{Match, WalkBuilder};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+const DEFAULT_EXCLUDES: &[&str] = &[".git", ".hg", ".svn", ".DS_Store", "node_modules"];
+lazy_static::lazy_static! {
+    static ref RULES: Mutex<HashMap<PathBuf, Arc<IgnoreRules>>> = Mutex::new(HashMap::new());
+}
+pub struct IgnoreRules {
+    root: PathBuf,
+    excludes: Gitignore,
+    repo_exclude: Gitignore,
+    global: Gitignore,
+    dir_ignores: Mutex<HashMap<PathBuf, Arc<Gitignore>>>,
+}
+impl IgnoreRules {
+    pub fn new(root: &Path) -> Self {
+        let mut excludes = GitignoreBuilder::new(root);
+        for glob in DEFAULT_EXCLUDES {
+            let _ = excludes.add_line(None, glob);
+        }
+        for glob in project_settings::load(root).files_exclude() {
+            if let Err(e) = excludes.add_line(None, &glob) {
+                eprintln!("Invalid files.exclude pattern {:?}: {}", glob, e);
+            }
+        }
+        let mut repo_exclude = GitignoreBuilder::new(root);
+        repo_exclude.add(root.join(".git").join("info").join("exclude"));
+        Self {
+            root: root.to_path_buf(),
+            excludes: excludes.build().unwrap_or_else(|_| Gitignore::empty()),
+            repo_exclude: repo_exclude.build().unwrap_or_else(|_| Gitignore::empty()),
+            global: Gitignore::global().0,
+            dir_ignores: Mutex::new(HashMap::new()),
+        }
+    }
+    fn dir_ignore(&self, dir: &Path) -> Arc<Gitignore> {
+        let mut cache = self.dir_ignores.lock().unwrap();
+        cache
+            .entry(dir.to_path_buf())
+            .or_insert_with(|| {
+                let mut builder = GitignoreBuilder::new(dir);
+                builder.add(dir.join(".gitignore"));
+                Arc::new(builder.build().unwrap_or_else(|_| Gitignore::empty()))
+            })
+            .clone()
+    }
+    pub fn is_excluded(&self, path: &Path, is_dir: bool) -> bool {
+        path.strip_prefix(&self.root).is_ok_and(|relative| {
+            self.excludes
+                .matched_path_or_any_parents(relative, is_dir)
+                .is_ignore()
+        })
+    }
+    fn matches_entry(&self, path: &Path, is_dir: bool) -> bool {
+        // The deepest .gitignore that has an opinion wins, then the repo exclude file, then global excludes.
+        for dir in path.ancestors().skip(1) {
+            if !dir.starts_with(&self.root) {
+                break;
+            }
+            match self.dir_ignore(dir).matched(path, is_dir) {
+                Match::Ignore(_) => return true,
+                Match::Whitelist(_) => return false,
+                Match::None => {}
+            }
+        }
+        let relative = path.strip_prefix(&self.root).unwrap_or(path);
+        for matcher in [&self.repo_exclude, &self.global] {
+            match matcher.matched(relative, is_dir) {
+                Match::Ignore(_) => return true,
+                Match::Whitelist(_) => return false,
+                Match::None => {}
+            }
+        }
+        false
+    }
+    pub fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        let relative = match path.strip_prefix(&self.root) {
+            Ok(r) => r,
+            Err(_) => return true,
+        };
+        if self.is_excluded(path, is_dir) {
+            return true;
+        }
+        let components: Vec<_> = relative.components().collect();
+        let mut current = self.root.clone();
+        for (i, component) in components.iter().enumerate() {
+            current.push(component);
+            let entry_is_dir = i + 1 < components.len() || is_dir;
+            if self.matches_entry(&current, entry_is_dir) {
+                return true;
+            }
+        }
+        false
+    }
+}
+pub struct GlobFilter {
+    include: Option<Gitignore>,
+    exclude: Gitignore,
+}
+impl GlobFilter {
+    pub fn new(root: &Path, include: &[String], exclude: &[String]) -> Result<Self, String> {
+        let build = |globs: &[String]| -> Result<Gitignore, String> {
+            let mut builder = GitignoreBuilder::new(root);
+            for glob in globs {
+                builder.add_line(None, glob).map_err(|e| e.to_string())?;
+            }
+            builder.build().map_err(|e| e.to_string())
+        };
+        Ok(Self {
+            include: if include.is_empty() {
+                None
+            } else {
+                Some(build(include)?)
+            },
+            exclude: build(exclude)?,
+        })
+    }
+    pub fn matches(&self, path: &Path) -> bool {
+        if self
+            .exclude
+            .matched_path_or_any_parents(path, false)
+            .is_ignore()
+        {
+            return false;
+        }
+        self.include.as_ref().map_or(true, |include| {
+            include.matched_path_or_any_parents(path, false).is_ignore()
+        })
+    }
+}
+// Only watched roots are cached, since the watcher is what invalidates them.
+pub fn rules_for(root: &Path) -> Arc<IgnoreRules> {
+    if !watcher::is_watching(root) {
+        return Arc::new(IgnoreRules::new(root));
+    }
+    RULES
+        .lock()
+        .unwrap()
+        .entry(root.to_path_buf())
+        .or_insert_with(|| Arc::new(IgnoreRules::new(root)))
+        .clone()
+}
+// Drops cached rules for `root` and for any nested or enclosing root whose ignore files it covers.
+pub fn invalidate_rules(root: &Path) {
+    RULES
+        .lock()
+        .unwrap()
+        .retain(|cached, _| !cached.starts_with(root) && !root.starts_with(cached));
+}
+pub fn find_project_root(path: &Path) -> PathBuf {
+    path.ancestors()
+        .find(|dir| dir.join(".git").exists() || dir.join(".mide").is_dir())
+        .unwrap_or(path)
+        .to_path_buf()
+}
+pub fn walk_builder(root: &Path) -> WalkBuilder {
+    let rules = rules_for(root);
+    let mut builder = WalkBuilder::new(root);
+    builder
+        .hidden(false)
+        .parents(false)
+        .follow_links(false)
+        .require_git(false)
+        .git_ignore(true)
+        .git_exclude(true)
+        .git_global(true)
+        .filter_entry(move |entry| {
+            let is_dir = entry.file_type().is_some_and(|t| t.is_dir());
+            !rules.is_excluded(entry.path(), is_dir)
+        });
+    builder
+}
+// Walks `dir` inside `root` with the root's rules, so ignore files above `dir` still apply.
+pub fn walk_subtree(root: &Path, dir: &Path) -> impl Iterator<Item = PathBuf> {
+    let rules = rules_for(root);
+    let inside = dir.starts_with(root) && !rules.is_ignored(dir, true);
+    let mut builder = WalkBuilder::new(dir);
+    builder
+        .standard_filters(false)
+        .follow_links(false)
+        .filter_entry(move |entry| {
+            let is_dir = entry.file_type().is_some_and(|t| t.is_dir());
+            entry.depth() == 0 || !rules.is_ignored(entry.path(), is_dir)
+        });
+    builder
+        .build()
+        .filter(move |_| inside)
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_some_and(|t| t.is_file()))
+        .map(|entry| entry.into_path())
+}
+pub fn walk_files(root: &Path) -> impl Iterator<Item = PathBuf> {
+    walk_builder(root)
+        .build()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_some_and(|t| t.is_file()))
+        .map(|entry| entry.into_path())
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    fn project(files: &[(&str, &str)]) -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        for (path, content) in files {
+            let path = dir.path().join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        }
+        dir
+    }
+    #[test]
+    fn nested_gitignore_can_whitelist_a_parent_pattern() {
+        let dir = project(&[
+            (".gitignore", "*.log\ngen/\n"),
+            ("src/.gitignore", "!keep.log\n"),
+        ]);
+        let rules = IgnoreRules::new(dir.path());
+        assert!(rules.is_ignored(&dir.path().join("debug.log"), false));
+        assert!(rules.is_ignored(&dir.path().join("src/gen/out.rs"), false));
+        assert!(!rules.is_ignored(&dir.path().join("src/keep.log"), false));
+        assert!(!rules.is_ignored(&dir.path().join("src/main.rs"), false));
+    }
+    #[test]
+    fn settings_and_default_excludes_apply_to_whole_subtrees() {
+        let dir = project(&[(".mide/settings.json", r#"{"files.exclude": ["dist"]}"#)]);
+        let rules = IgnoreRules::new(dir.path());
+        assert!(rules.is_ignored(&dir.path().join("dist/app.js"), false));
+        assert!(rules.is_ignored(&dir.path().join("web/node_modules/x/index.js"), false));
+        assert!(!rules.is_ignored(&dir.path().join("web/index.js"), false));
+    }
+    #[test]
+    fn subtree_walks_apply_ignore_files_above_the_subtree() {
+        let dir = project(&[
+            (".gitignore", "*.log\n"),
+            (".mide/settings.json", r#"{"files.exclude": ["src/gen"]}"#),
+            ("src/main.rs", ""),
+            ("src/debug.log", ""),
+            ("src/gen/out.rs", ""),
+        ]);
+        let files: Vec<PathBuf> = walk_subtree(dir.path(), &dir.path().join("src")).collect();
+        assert_eq!(files, vec![dir.path().join("src/main.rs")]);
+        assert_eq!(
+            walk_subtree(dir.path(), &dir.path().join("src/gen")).count(),
+            0
+        );
+    }
+    #[test]
+    fn paths_outside_the_root_are_ignored() {
+        let dir = project(&[]);
+        let rules = IgnoreRules::new(&dir.path().join("project"));
+        assert!(rules.is_ignored(&dir.path().join("other/file.rs"), false));
+    }
+}
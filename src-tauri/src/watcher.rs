@@ -0,0 +1,285 @@
+use crate::error::FsError;
+use crate::models::FileNode;
+use crate::project_settings;
+use crate::search;
+use crate::symbols;
+use crate::todos;
+use crate::trigram;
+use crate::walker::{self, IgnoreRules};
+use crate::workspace;
+use notify_debouncer_full::notify::event::{ModifyKind, RemoveKind, RenameMode};
+use notify_debouncer_full::notify::{EventKind, RecommendedWatcher, RecursiveMode};
+use notify_debouncer_full::{new_debouncer, DebounceEventResult, Debouncer, RecommendedCache};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+const DEBOUNCE_TIMEOUT: Duration = Duration::from_millis(250);
+type ProjectDebouncer = Debouncer<RecommendedWatcher, RecommendedCache>;
+lazy_static::lazy_static! {
+    static ref WATCHERS: Mutex<HashMap<PathBuf, ProjectDebouncer>> = Mutex::new(HashMap::new());
+}
+#[derive(Serialize, Debug, Clone)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum FsChange {
+    Created { node: FileNode },
+    Modified { node: FileNode },
+    Deleted { node: FileNode },
+    Renamed { from: String, node: FileNode },
+}
+impl FsChange {
+    // Both ends of a rename, otherwise just the changed entry.
+    pub fn paths(&self) -> impl Iterator<Item = &str> {
+        let (from, node) = match self {
+            FsChange::Renamed { from, node } => (Some(from.as_str()), node),
+            FsChange::Created { node }
+            | FsChange::Modified { node }
+            | FsChange::Deleted { node } => (None, node),
+        };
+        from.into_iter().chain(Some(node.path.as_str()))
+    }
+}
+#[derive(Serialize, Debug, Clone)]
+pub struct FsChangedEvent {
+    pub root: String,
+    pub changes: Vec<FsChange>,
+}
+fn node_for(path: &Path, is_dir: Option<bool>) -> FileNode {
+    let is_dir = is_dir.unwrap_or_else(|| fs::metadata(path).map(|m| m.is_dir()).unwrap_or(false));
+    FileNode {
+        name: path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default(),
+        path: path.to_string_lossy().to_string(),
+        is_dir,
+        children: None,
+        ignored: false,
+    }
+}
+fn classify(kind: &EventKind, paths: &[PathBuf]) -> Vec<FsChange> {
+    let mut changes = Vec::new();
+    match kind {
+        EventKind::Create(_) => {
+            for path in paths.iter().filter(|p| p.exists()) {
+                changes.push(FsChange::Created {
+                    node: node_for(path, None),
+                });
+            }
+        }
+        EventKind::Modify(ModifyKind::Name(RenameMode::Both)) if paths.len() == 2 => {
+            changes.push(FsChange::Renamed {
+                from: paths[0].to_string_lossy().to_string(),
+                node: node_for(&paths[1], None),
+            });
+        }
+        EventKind::Modify(ModifyKind::Name(RenameMode::From)) => {
+            for path in paths {
+                changes.push(FsChange::Deleted {
+                    node: node_for(path, Some(false)),
+                });
+            }
+        }
+        EventKind::Modify(ModifyKind::Name(_)) => {
+            for path in paths.iter().filter(|p| p.exists()) {
+                changes.push(FsChange::Created {
+                    node: node_for(path, None),
+                });
+            }
+        }
+        EventKind::Modify(ModifyKind::Metadata(_)) => {}
+        EventKind::Modify(_) => {
+            for path in paths.iter().filter(|p| p.is_file()) {
+                changes.push(FsChange::Modified {
+                    node: node_for(path, Some(false)),
+                });
+            }
+        }
+        EventKind::Remove(remove_kind) => {
+            let is_dir = matches!(remove_kind, RemoveKind::Folder);
+            for path in paths {
+                changes.push(FsChange::Deleted {
+                    node: node_for(path, Some(is_dir)),
+                });
+            }
+        }
+        _ => {}
+    }
+    changes
+}
+fn change_path(change: &FsChange) -> (&str, bool) {
+    match change {
+        FsChange::Created { node }
+        | FsChange::Modified { node }
+        | FsChange::Deleted { node }
+        | FsChange::Renamed { node, .. } => (&node.path, node.is_dir),
+    }
+}
+pub fn is_watching(root: &Path) -> bool {
+    WATCHERS.lock().unwrap().contains_key(root)
+}
+#[tauri::command]
+pub fn watch_project(app: AppHandle, path: String) -> Result<(), FsError> {
+    let root = workspace::resolve(&path)?;
+    if !root.is_dir() {
+        return Err("Invalid directory".into());
+    }
+    let mut watchers = WATCHERS.lock().unwrap();
+    if watchers.contains_key(&root) {
+        return Ok(());
+    }
+    trigram::ensure_loaded(&root);
+    let handler_root = root.clone();
+    let repo_exclude = root.join(".git").join("info").join("exclude");
+    let mut rules = IgnoreRules::new(&root);
+    let mut debouncer = new_debouncer(
+        DEBOUNCE_TIMEOUT,
+        None,
+        move |result: DebounceEventResult| {
+            let events = match result {
+                Ok(events) => events,
+                Err(errors) => {
+                    for e in errors {
+                        eprintln!("File watcher error in {:?}: {}", handler_root, e);
+                    }
+                    return;
+                }
+            };
+            let mut changes = Vec::new();
+            let mut rules_changed = false;
+            for event in events {
+                if event.paths.iter().any(|p| {
+                    p.file_name().is_some_and(|n| n == ".gitignore")
+                        || *p == repo_exclude
+                        || *p == project_settings::settings_path(&handler_root)
+                }) {
+                    rules = IgnoreRules::new(&handler_root);
+                    walker::invalidate_rules(&handler_root);
+                    rules_changed = true;
+                }
+                for change in classify(&event.kind, &event.paths) {
+                    let (path, is_dir) = change_path(&change);
+                    if !rules.is_ignored(Path::new(path), is_dir) {
+                        changes.push(change);
+                    }
+                }
+            }
+            if rules_changed
+                || changes
+                    .iter()
+                    .any(|c| !matches!(c, FsChange::Modified { .. }))
+            {
+                search::invalidate_file_list(&handler_root);
+            }
+            if changes.is_empty() {
+                return;
+            }
+            trigram::apply_changes(&handler_root, &changes);
+            symbols::apply_changes(&handler_root, &changes);
+            todos::apply_changes(&handler_root, &changes);
+            let _ = app.emit(
+                "fs-changed",
+                FsChangedEvent {
+                    root: handler_root.to_string_lossy().to_string(),
+                    changes,
+                },
+            );
+        },
+    )
+    .map_err(|e| e.to_string())?;
+    debouncer
+        .watch(&root, RecursiveMode::Recursive)
+        .map_err(|e| e.to_string())?;
+    watchers.insert(root, debouncer);
+    Ok(())
+}
+#[tauri::command]
+pub fn unwatch_project(path: String) -> Result<(), String> {
+    let root = fs::canonicalize(&path).unwrap_or_else(|_| PathBuf::from(&path));
+    WATCHERS.lock().unwrap().remove(&root);
+    search::invalidate_file_list(&root);
+    walker::invalidate_rules(&root);
+    trigram::unload(&root);
+    symbols::unload(&root);
+    todos::unload(&root);
+    Ok(())
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use notify_debouncer_full::notify::event::{CreateKind, DataChange, MetadataKind};
+    #[test]
+    fn creations_keep_only_paths_that_still_exist() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("a.txt");
+        fs::write(&file, "x").unwrap();
+        let changes = classify(
+            &EventKind::Create(CreateKind::Any),
+            &[file.clone(), dir.path().join("gone.txt")],
+        );
+        assert!(matches!(
+            changes.as_slice(),
+            [FsChange::Created { node }] if node.name == "a.txt" && !node.is_dir
+        ));
+    }
+    #[test]
+    fn paired_renames_report_both_paths() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("new.txt");
+        fs::write(&file, "x").unwrap();
+        let changes = classify(
+            &EventKind::Modify(ModifyKind::Name(RenameMode::Both)),
+            &[dir.path().join("old.txt"), file],
+        );
+        assert!(matches!(
+            changes.as_slice(),
+            [FsChange::Renamed { from, node }] if from.ends_with("old.txt") && node.name == "new.txt"
+        ));
+    }
+    #[test]
+    fn renames_touch_both_paths() {
+        let change = FsChange::Renamed {
+            from: "/p/old.txt".to_string(),
+            node: node_for(Path::new("/p/new.txt"), Some(false)),
+        };
+        assert_eq!(
+            change.paths().collect::<Vec<_>>(),
+            vec!["/p/old.txt", "/p/new.txt"]
+        );
+        let change = FsChange::Deleted {
+            node: node_for(Path::new("/p/gone"), Some(true)),
+        };
+        assert_eq!(change.paths().collect::<Vec<_>>(), vec!["/p/gone"]);
+    }
+    #[test]
+    fn content_changes_skip_directories_and_metadata() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("a.txt");
+        fs::write(&file, "x").unwrap();
+        let changes = classify(
+            &EventKind::Modify(ModifyKind::Data(DataChange::Content)),
+            &[file.clone(), dir.path().to_path_buf()],
+        );
+        assert!(matches!(changes.as_slice(), [FsChange::Modified { .. }]));
+        assert!(classify(
+            &EventKind::Modify(ModifyKind::Metadata(MetadataKind::Any)),
+            &[file]
+        )
+        .is_empty());
+    }
+    #[test]
+    fn removals_use_the_event_kind_for_directories() {
+        let dir = tempfile::tempdir().unwrap();
+        let changes = classify(
+            &EventKind::Remove(RemoveKind::Folder),
+            &[dir.path().join("gone")],
+        );
+        assert!(matches!(
+            changes.as_slice(),
+            [FsChange::Deleted { node }] if node.is_dir
+        ));
+    }
+}
@@ -0,0 +1,280 @@
+use crate::error::FsError;
+use crate::filesystem::write_private;
+use crate::storage;
+use crate::watcher;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::sync::RwLock;
+use tauri::AppHandle;
+use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
+#[derive(Default)]
+struct WorkspaceScope {
+    roots: Vec<PathBuf>,
+    approved: Vec<PathBuf>,
+}
+lazy_static::lazy_static! {
+    static ref SCOPE: RwLock<WorkspaceScope> = RwLock::new(WorkspaceScope::default());
+}
+fn outside(path: &Path) -> FsError {
+    FsError::OutsideWorkspace {
+        path: path.to_string_lossy().to_string(),
+    }
+}
+pub fn canonicalize(path: &Path) -> Result<PathBuf, FsError> {
+    if !path.is_absolute() {
+        return Err(outside(path));
+    }
+    // Paths that do not exist yet resolve through their closest existing ancestor.
+    let mut missing = Vec::new();
+    let mut existing = path;
+    loop {
+        match fs::canonicalize(existing) {
+            Ok(mut resolved) => {
+                for component in missing.iter().rev() {
+                    resolved.push(component);
+                }
+                return Ok(resolved);
+            }
+            Err(_) => match existing.components().next_back() {
+                Some(Component::Normal(name)) => {
+                    missing.push(name.to_os_string());
+                    existing = existing.parent().ok_or_else(|| outside(path))?;
+                }
+                Some(Component::CurDir) => {
+                    existing = existing.parent().ok_or_else(|| outside(path))?;
+                }
+                _ => return Err(outside(path)),
+            },
+        }
+    }
+}
+pub fn is_allowed(resolved: &Path) -> bool {
+    let scope = SCOPE.read().unwrap();
+    scope
+        .roots
+        .iter()
+        .chain(scope.approved.iter())
+        .any(|root| resolved.starts_with(root))
+}
+pub fn resolve(path: &str) -> Result<PathBuf, FsError> {
+    let resolved = canonicalize(Path::new(path))?;
+    if is_allowed(&resolved) {
+        Ok(resolved)
+    } else {
+        Err(outside(Path::new(path)))
+    }
+}
+// Resolves only the parent, so a trailing symlink names the link itself rather than its target.
+pub fn resolve_entry(path: &str) -> Result<PathBuf, FsError> {
+    let requested = Path::new(path);
+    let (parent, name) = match (requested.parent(), requested.file_name()) {
+        (Some(parent), Some(name)) => (parent, name),
+        _ => return resolve(path),
+    };
+    let resolved = canonicalize(parent)?.join(name);
+    if is_allowed(&resolved) {
+        Ok(resolved)
+    } else {
+        Err(outside(requested))
+    }
+}
+pub fn is_root(resolved: &Path) -> bool {
+    SCOPE.read().unwrap().roots.iter().any(|r| r == resolved)
+}
+pub fn roots() -> Vec<PathBuf> {
+    SCOPE.read().unwrap().roots.clone()
+}
+pub fn root_for(resolved: &Path) -> Option<PathBuf> {
+    SCOPE
+        .read()
+        .unwrap()
+        .roots
+        .iter()
+        .filter(|root| resolved.starts_with(root))
+        .max_by_key(|root| root.components().count())
+        .cloned()
+}
+// Folders the user has picked or confirmed before; reopening them does not ask again.
+fn trusted_path() -> PathBuf {
+    storage::data_dir().join("trusted-workspaces.json")
+}
+fn load_trusted() -> Vec<PathBuf> {
+    fs::read(trusted_path())
+        .ok()
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        .unwrap_or_default()
+}
+fn remember_trusted(root: &Path) {
+    let mut trusted = load_trusted();
+    if trusted.iter().any(|t| t == root) {
+        return;
+    }
+    trusted.push(root.to_path_buf());
+    let result = fs::create_dir_all(storage::data_dir()).and_then(|_| {
+        write_private(
+            &trusted_path(),
+            &serde_json::to_vec(&trusted).unwrap_or_default(),
+        )
+    });
+    if let Err(e) = result {
+        eprintln!("Failed to remember trusted workspace {:?}: {}", root, e);
+    }
+}
+fn add_root(root: PathBuf) -> Result<String, FsError> {
+    if !root.is_dir() {
+        return Err(FsError::Io {
+            message: "Invalid directory".to_string(),
+        });
+    }
+    let mut scope = SCOPE.write().unwrap();
+    if !scope.roots.contains(&root) {
+        scope.roots.push(root.clone());
+    }
+    Ok(root.to_string_lossy().to_string())
+}
+#[cfg(test)]
+pub(crate) fn open_for_test(path: &Path) -> PathBuf {
+    let root = fs::canonicalize(path).unwrap();
+    add_root(root.clone()).unwrap();
+    root
+}
+#[tauri::command]
+pub async fn pick_workspace(app: AppHandle) -> Result<Option<String>, FsError> {
+    let picked = match app.dialog().file().blocking_pick_folder() {
+        Some(picked) => picked.into_path().map_err(|e| e.to_string())?,
+        None => return Ok(None),
+    };
+    let root = fs::canonicalize(picked)?;
+    let path = add_root(root.clone())?;
+    remember_trusted(&root);
+    Ok(Some(path))
+}
+// Paths coming from the webview are only opened once the user has trusted them natively.
+#[tauri::command]
+pub async fn open_workspace(app: AppHandle, path: String) -> Result<Option<String>, FsError> {
+    let root = fs::canonicalize(&path)?;
+    if !load_trusted().contains(&root) {
+        let approved = app
+            .dialog()
+            .message(format!(
+                "Open this folder as a workspace?\n\n{}\n\nMIDE will be able to read and change every file in it.",
+                root.to_string_lossy()
+            ))
+            .title("Open folder?")
+            .kind(MessageDialogKind::Warning)
+            .buttons(MessageDialogButtons::OkCancelCustom(
+                "Open".to_string(),
+                "Cancel".to_string(),
+            ))
+            .blocking_show();
+        if !approved {
+            return Ok(None);
+        }
+    }
+    let opened = add_root(root.clone())?;
+    remember_trusted(&root);
+    Ok(Some(opened))
+}
+#[tauri::command]
+pub fn close_workspace(path: String) -> Result<(), FsError> {
+    let root = fs::canonicalize(&path)?;
+    SCOPE.write().unwrap().roots.retain(|r| *r != root);
+    watcher::unwatch_project(root.to_string_lossy().to_string())?;
+    Ok(())
+}
+#[tauri::command]
+pub fn list_workspace_roots() -> Vec<String> {
+    SCOPE
+        .read()
+        .unwrap()
+        .roots
+        .iter()
+        .map(|r| r.to_string_lossy().to_string())
+        .collect()
+}
+#[tauri::command]
+pub async fn request_path_access(app: AppHandle, path: String) -> Result<bool, FsError> {
+    let resolved = canonicalize(Path::new(&path))?;
+    if is_allowed(&resolved) {
+        return Ok(true);
+    }
+    let approved = app
+        .dialog()
+        .message(format!(
+            "A command wants to access a location outside the open workspace:\n\n{}\n\nAllow access for this session?",
+            resolved.to_string_lossy()
+        ))
+        .title("Allow file access?")
+        .kind(MessageDialogKind::Warning)
+        .buttons(MessageDialogButtons::OkCancelCustom(
+            "Allow".to_string(),
+            "Deny".to_string(),
+        ))
+        .blocking_show();
+    if approved {
+        SCOPE.write().unwrap().approved.push(resolved);
+    }
+    Ok(approved)
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    fn workspace() -> (tempfile::TempDir, PathBuf) {
+        let dir = tempfile::tempdir().unwrap();
+        let base = fs::canonicalize(dir.path()).unwrap();
+        fs::create_dir_all(base.join("project/src")).unwrap();
+        fs::create_dir_all(base.join("outside")).unwrap();
+        fs::write(base.join("outside/secret"), "token").unwrap();
+        let root = open_for_test(&base.join("project"));
+        (dir, root)
+    }
+    fn path(p: &Path) -> String {
+        p.to_string_lossy().to_string()
+    }
+    #[test]
+    fn parent_components_cannot_leave_the_workspace() {
+        let (_dir, root) = workspace();
+        assert_eq!(
+            resolve(&path(&root.join("src/../src"))).unwrap(),
+            root.join("src")
+        );
+        assert!(resolve(&path(&root.join("../outside/secret"))).is_err());
+        assert!(resolve(&path(&root.join("src/../../outside/new.txt"))).is_err());
+        assert!(resolve(&path(&root.join("missing/../../outside/secret"))).is_err());
+        assert!(resolve("src/main.rs").is_err());
+    }
+    #[test]
+    fn missing_leaves_resolve_through_their_existing_parent() {
+        let (_dir, root) = workspace();
+        assert_eq!(
+            resolve(&path(&root.join("src/new/deeper.rs"))).unwrap(),
+            root.join("src/new/deeper.rs")
+        );
+        assert_eq!(
+            canonicalize(&root.join("./src/./new.rs")).unwrap(),
+            root.join("src/new.rs")
+        );
+    }
+    #[cfg(unix)]
+    #[test]
+    fn symlinks_out_of_the_workspace_are_rejected() {
+        let (_dir, root) = workspace();
+        let outside = root.parent().unwrap().join("outside");
+        std::os::unix::fs::symlink(&outside, root.join("escape")).unwrap();
+        assert!(resolve(&path(&root.join("escape/secret"))).is_err());
+        assert!(resolve(&path(&root.join("escape/new.txt"))).is_err());
+        // The link itself lives in the workspace, so it can still be deleted or renamed.
+        assert_eq!(
+            resolve_entry(&path(&root.join("escape"))).unwrap(),
+            root.join("escape")
+        );
+        assert!(resolve_entry(&path(&root.join("escape/secret"))).is_err());
+    }
+    #[test]
+    fn workspace_roots_are_recognised() {
+        let (_dir, root) = workspace();
+        assert!(is_root(&root));
+        assert!(!is_root(&root.join("src")));
+        assert_eq!(root_for(&root.join("src/lib.rs")), Some(root.clone()));
+    }
+}
@@ -13,6 +13,9 @@ import {
   FolderPlus,
 } from "lucide-react";
 import { motion, AnimatePresence } from "motion/react";
+import { getLanguageFromPath } from "../lib/utils";
+import type { FileContent } from "../lib/types";
+import { invoke } from "@tauri-apps/api/core";
 interface Command {
   id: string;
   label: string;
@@ -20,6 +23,34 @@ interface Command {
   icon: React.ReactNode;
   action: () => void;
 }
+interface FileMatch {
+  path: string;
+  score: number;
+  relative_path: string;
+  positions: number[];
+  line: number | null;
+  column: number | null;
+}
+interface SymbolMatch {
+  name: string;
+  kind: string;
+  container: string | null;
+  path: string;
+  range: { line: number; start: number; end_line: number; end: number };
+  score: number;
+  positions: number[];
+}
+function highlight(text: string, positions: Set<number>, offset: number) {
+  return Array.from(text).map((char, i) =>
+    positions.has(i + offset) ? (
+      <span key={i} className="text-[#4fc1ff] font-semibold">
+        {char}
+      </span>
+    ) : (
+      char
+    )
+  );
+}
 export default function CommandPalette() {
   const {
     isCommandPaletteOpen,
@@ -27,6 +58,8 @@ export default function CommandPalette() {
     openProjectDialog,
     activeFile,
     openFiles,
+    openFile,
+    openLargeFile,
     setActiveFile,
     closeFile,
     projectPath,
@@ -38,19 +71,28 @@ export default function CommandPalette() {
   } = useEditorStore();
   const [query, setQuery] = useState("");
   const [selectedIndex, setSelectedIndex] = useState(0);
-  const [fileResults, setFileResults] = useState<
-    { path: string; score: number }[]
-  >([]);
+  const [fileResults, setFileResults] = useState<FileMatch[]>([]);
+  const [symbolResults, setSymbolResults] = useState<SymbolMatch[]>([]);
   const inputRef = useRef<HTMLInputElement>(null);
   useEffect(() => {
     const searchFiles = async () => {
       if (!projectPath || !query || query.startsWith(">")) {
         setFileResults([]);
+        setSymbolResults([]);
         return;
       }
       try {
-        const { invoke } = await import("@tauri-apps/api/core");
-        const results = await invoke<{ path: string; score: number }[]>(
+        if (query.startsWith("#")) {
+          const symbols = await invoke<SymbolMatch[]>("fuzzy_search_symbols", {
+            path: projectPath,
+            query: query.substring(1),
+          });
+          setFileResults([]);
+          setSymbolResults(symbols);
+          return;
+        }
+        setSymbolResults([]);
+        const results = await invoke<FileMatch[]>(
           "fuzzy_search_files",
           {
             path: projectPath,
@@ -65,6 +107,32 @@ export default function CommandPalette() {
     const debounce = setTimeout(searchFiles, 150);
     return () => clearTimeout(debounce);
   }, [query, projectPath]);
+  const openPath = async (path: string) => {
+    if (openFiles.some((f) => f.path === path)) {
+      setActiveFile(path);
+      return;
+    }
+    try {
+      const { content, is_binary, version } = await invoke<FileContent>("read_file_content", {
+        path,
+      });
+      openFile({
+        path,
+        name: path.split(/[/\\]/).pop() || path,
+        content,
+        language: getLanguageFromPath(path),
+        isDirty: false,
+        readOnly: is_binary,
+        version,
+      });
+    } catch (err: any) {
+      if (err?.kind === "too_large") {
+        openLargeFile(path);
+        return;
+      }
+      console.error("Error opening file:", err);
+    }
+  };
   const handleCreateTrigger = (type: "file" | "folder") => {
     if (!projectPath) return;
     let parentPath = projectPath;
@@ -155,6 +223,7 @@ export default function CommandPalette() {
     })),
   ];
   const isCommandMode = query.startsWith(">");
+  const isSymbolMode = query.startsWith("#");
   const filteredCommands = isCommandMode
     ? commands.filter((cmd) =>
         cmd.label
@@ -166,15 +235,42 @@ export default function CommandPalette() {
       );
   const displayItems = isCommandMode
     ? filteredCommands
+    : isSymbolMode
+    ? symbolResults.map((symbol) => ({
+        id: `symbol-${symbol.path}-${symbol.range.line}-${symbol.range.start}`,
+        label: highlight(symbol.name, new Set(symbol.positions), 0),
+        description: `${symbol.kind}${symbol.container ? ` in ${symbol.container}` : ""} · ${symbol.path.replace(projectPath || "", "")}:${symbol.range.line}`,
+        icon: <File size={16} />,
+        action: () => {
+          invoke("record_search", { path: projectPath, kind: "symbols", query: query.substring(1) }).catch(() => {});
+          openPath(symbol.path);
+        },
+      }))
     : [
         ...filteredCommands,
-        ...fileResults.map((file) => ({
-          id: `file-${file.path}`,
-          label: file.path.split(/[/\\]/).pop() || file.path,
-          description: file.path.replace(projectPath || "", ""),
-          icon: <File size={16} />,
-          action: () => setActiveFile(file.path),
-        })),
+        ...fileResults.map((file) => {
+          const positions = new Set(file.positions);
+          const nameStart = Array.from(file.relative_path).length -
+            Array.from(file.relative_path.split("/").pop() || "").length;
+          const location = file.line
+            ? `:${file.line}${file.column ? `:${file.column}` : ""}`
+            : "";
+          return {
+            id: `file-${file.path}`,
+            label: highlight(file.relative_path.slice(file.relative_path.lastIndexOf("/") + 1), positions, nameStart),
+            description: (
+              <>
+                {highlight(file.relative_path, positions, 0)}
+                {location}
+              </>
+            ),
+            icon: <File size={16} />,
+            action: () => {
+              invoke("record_search", { path: projectPath, kind: "files", query }).catch(() => {});
+              openPath(file.path);
+            },
+          };
+        }),
       ];
   useEffect(() => {
     const handleKeyDown = (e: KeyboardEvent) => {
@@ -243,7 +339,7 @@ export default function CommandPalette() {
             <Input
               ref={inputRef}
               className="flex-1 bg-transparent border-none text-white placeholder-gray-500 text-sm focus-visible:ring-0 h-auto p-0"
-              placeholder="Type a command or search for files..."
+              placeholder="Type a command, search for files or # for symbols..."
               value={query}
               onChange={(e) => {
                 setQuery(e.target.value);
@@ -1,7 +1,9 @@
 import { useEffect } from "react";
 import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
 import { useEditorStore } from "../lib/store";
 import { getLanguageFromPath } from "../lib/utils";
+import type { FileContent, FsChangedEvent, SavedTerminal } from "../lib/types";
 import { ArrowLeftRight, ArrowUpDown } from "lucide-react";
 import ActivityBar from "./ActivityBar";
 import Sidebar from "./Sidebar";
@@ -20,23 +22,61 @@ import { Button } from "./ui/button";
 export default function EditorLayout() {
   const {
     fileTree,
+    projectPath,
     openFiles,
     activeFile,
     activeView,
     openFile,
     setActiveFile,
     setActiveView,
-    markFileDirty,
     isSidebarCollapsed,
     toggleSidebar,
     splitEditorHorizontal,
     splitEditorVertical,
     splitDirection,
     updateFileContent,
+    updateOpenFile,
+    openLargeFile,
+    saveFile,
+    applyFsChanges,
     isBottomPanelVisible,
     toggleBottomPanel,
+    addSavedTerminal,
+    setBottomPanelVisible,
   } = useEditorStore();
   const activeFileObj = openFiles.find((f) => f.path === activeFile);
+  useEffect(() => {
+    const interval = setInterval(() => {
+      const { openFiles } = useEditorStore.getState();
+      for (const file of openFiles.filter((f) => f.isDirty)) {
+        invoke("backup_buffer", {
+          path: file.path,
+          content: file.content,
+          baseVersion: file.version ?? null,
+        }).catch((err) => console.error("Failed to back up buffer:", err));
+      }
+    }, 5000);
+    return () => clearInterval(interval);
+  }, []);
+  useEffect(() => {
+    if (!projectPath) return;
+    const unlisten = listen<FsChangedEvent>("fs-changed", (event) => {
+      applyFsChanges(event.payload);
+    });
+    return () => {
+      unlisten.then((u) => u());
+    };
+  }, [projectPath]);
+  useEffect(() => {
+    if (!projectPath) return;
+    invoke<SavedTerminal[]>("restore_terminal_sessions", { workspace: projectPath })
+      .then((saved) => {
+        if (saved.length === 0) return;
+        saved.forEach(addSavedTerminal);
+        setBottomPanelVisible(true);
+      })
+      .catch((err) => console.error("Failed to restore terminals:", err));
+  }, [projectPath]);
   const handleFileSelect = async (path: string) => {
     const existing = openFiles.find((f) => f.path === path);
     if (existing) {
@@ -52,9 +92,13 @@ export default function EditorLayout() {
       isDirty: false,
     });
     try {
-      const content = await invoke<string>("read_file_content", { path });
-      updateFileContent(path, content, false);
-    } catch (err) {
+      const { content, is_binary, version } = await invoke<FileContent>("read_file_content", { path });
+      updateOpenFile(path, { content, readOnly: is_binary, version });
+    } catch (err: any) {
+      if (err?.kind === "too_large") {
+        openLargeFile(path);
+        return;
+      }
       console.error("Error reading file:", err);
       updateFileContent(path, `// Error loading file: ${err}`, false);
     }
@@ -62,11 +106,7 @@ export default function EditorLayout() {
   const handleSave = async () => {
     if (!activeFileObj) return;
     try {
-      await invoke("save_file_content", {
-        path: activeFileObj.path,
-        content: activeFileObj.content,
-      });
-      markFileDirty(activeFileObj.path, false);
+      await saveFile(activeFileObj.path);
     } catch (err) {
       console.error("Failed to save:", err);
     }
@@ -0,0 +1,114 @@
+import { useEffect, useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { ChevronLeft, ChevronRight } from "lucide-react";
+import { useEditorStore } from "../lib/store";
+import type { LineIndexStatus, LineRange } from "../lib/types";
+import { Button } from "./ui/button";
+import { Input } from "./ui/input";
+const PAGE_SIZE = 1000;
+interface LargeFileViewProps {
+  path: string;
+  changedOnDisk?: boolean;
+}
+export default function LargeFileView({ path, changedOnDisk }: LargeFileViewProps) {
+  const { updateOpenFile } = useEditorStore();
+  const [startLine, setStartLine] = useState(0);
+  const [page, setPage] = useState<LineRange | null>(null);
+  const [status, setStatus] = useState<LineIndexStatus | null>(null);
+  const [goToLine, setGoToLine] = useState("");
+  const [error, setError] = useState<string | null>(null);
+  useEffect(() => {
+    setStartLine(0);
+    invoke("open_large_file", { path }).catch((err) => setError(String(err)));
+  }, [path]);
+  useEffect(() => {
+    invoke<LineRange>("read_file_lines", { path, startLine, count: PAGE_SIZE })
+      .then((range) => {
+        setPage(range);
+        setError(null);
+      })
+      .catch((err) => setError(String(err?.message ?? err)));
+    if (changedOnDisk) {
+      updateOpenFile(path, { changedOnDisk: false });
+    }
+  }, [path, startLine, changedOnDisk]);
+  useEffect(() => {
+    // The line index is built in the background; poll until it has seen the whole file.
+    let timer: ReturnType<typeof setTimeout>;
+    const poll = () => {
+      invoke<LineIndexStatus>("line_index_status", { path })
+        .then((s) => {
+          setStatus(s);
+          if (!s.complete) timer = setTimeout(poll, 500);
+        })
+        .catch(console.error);
+    };
+    poll();
+    return () => clearTimeout(timer);
+  }, [path, changedOnDisk]);
+  const totalLines = status?.complete ? status.lines_indexed : null;
+  const handleGoToLine = (e: React.FormEvent) => {
+    e.preventDefault();
+    const line = parseInt(goToLine, 10);
+    if (Number.isNaN(line) || line < 1) return;
+    setStartLine(Math.floor((line - 1) / PAGE_SIZE) * PAGE_SIZE);
+  };
+  return (
+    <div className="flex flex-col h-full bg-[#1e1e1e] text-[#cccccc]">
+      <div className="flex items-center gap-2 px-3 h-9 border-b border-[#333] text-xs">
+        <span className="text-yellow-500">Large file, read-only</span>
+        <span className="text-gray-500">
+          Lines {startLine + 1}–{startLine + (page?.lines.length ?? 0)}
+          {totalLines !== null
+            ? ` of ${totalLines}`
+            : status
+              ? ` (indexing ${Math.round((status.bytes_indexed / Math.max(status.total_size, 1)) * 100)}%)`
+              : ""}
+        </span>
+        <div className="flex-1" />
+        <form onSubmit={handleGoToLine}>
+          <Input
+            value={goToLine}
+            onChange={(e) => setGoToLine(e.target.value)}
+            placeholder="Go to line"
+            className="h-6 w-28 text-xs bg-[#3c3c3c] border-[#3c3c3c]"
+          />
+        </form>
+        <Button
+          variant="ghost"
+          size="icon"
+          className="h-6 w-6"
+          disabled={startLine === 0}
+          onClick={() => setStartLine(Math.max(0, startLine - PAGE_SIZE))}
+          title="Previous page"
+        >
+          <ChevronLeft size={14} />
+        </Button>
+        <Button
+          variant="ghost"
+          size="icon"
+          className="h-6 w-6"
+          disabled={!page || page.eof}
+          onClick={() => setStartLine(startLine + PAGE_SIZE)}
+          title="Next page"
+        >
+          <ChevronRight size={14} />
+        </Button>
+      </div>
+      {error ? (
+        <div className="p-4 text-sm text-red-400">{error}</div>
+      ) : (
+        <div className="flex-1 overflow-auto font-mono text-[13px] leading-5">
+          {page?.lines.map((line, i) => (
+            <div key={startLine + i} className="flex whitespace-pre">
+              <span className="w-16 shrink-0 pr-4 text-right text-[#858585] select-none">
+                {startLine + i + 1}
+              </span>
+              <span>{line}</span>
+            </div>
+          ))}
+        </div>
+      )}
+    </div>
+  );
+}
@@ -1,5 +1,12 @@
+import { useEffect, useState } from "react";
 import { useEditorStore } from "../lib/store";
-import { Loader2, X } from "lucide-react";
+import {
+  type FileContent,
+  type ReplaceOutcome,
+  type ReplacePreviewSet,
+  type SearchHistoryEntry,
+} from "../lib/types";
+import { Loader2, Pin, X } from "lucide-react";
 import { invoke } from "@tauri-apps/api/core";
 import { getLanguageFromPath } from "../lib/utils";
 import { Button } from "./ui/button";
@@ -9,20 +16,106 @@ export default function SearchView() {
     searchResults,
     isSearching,
     searchQuery,
+    searchOptions,
+    searchFilesScanned,
     performSearch,
+    cancelSearch,
     setSearchQuery,
+    setSearchOptions,
+    structuralSearch,
+    setStructuralSearch,
     openFile,
+    openLargeFile,
+    projectPath,
   } = useEditorStore();
+  const [history, setHistory] = useState<SearchHistoryEntry[]>([]);
+  const [replacement, setReplacement] = useState("");
+  const [preview, setPreview] = useState<ReplacePreviewSet | null>(null);
+  const [undoToken, setUndoToken] = useState<string | null>(null);
+  const loadHistory = async () => {
+    if (!projectPath) return;
+    try {
+      const entries = await invoke<SearchHistoryEntry[]>("list_search_history", {
+        path: projectPath,
+      });
+      setHistory(entries.filter((e) => e.kind === "text" || e.kind === "structural"));
+    } catch (err) {
+      console.error("Failed to load search history:", err);
+    }
+  };
+  useEffect(() => {
+    if (!isSearching) loadHistory();
+  }, [projectPath, isSearching]);
+  const rerun = (entry: SearchHistoryEntry) => {
+    setStructuralSearch(entry.kind === "structural");
+    if (entry.options) setSearchOptions(entry.options);
+    setSearchQuery(entry.query);
+    performSearch(entry.query);
+  };
+  const togglePin = async (entry: SearchHistoryEntry) => {
+    await invoke("pin_search", { path: projectPath, id: entry.id, pinned: !entry.pinned }).catch(console.error);
+    loadHistory();
+  };
+  const removeEntry = async (entry: SearchHistoryEntry) => {
+    await invoke("delete_search", { path: projectPath, id: entry.id }).catch(console.error);
+    loadHistory();
+  };
+  const toggles = [
+    { key: "case_sensitive", label: "Aa", title: "Match Case" },
+    { key: "whole_word", label: "ab", title: "Match Whole Word" },
+    { key: "regex", label: ".*", title: "Use Regular Expression" },
+  ] as const;
   const handleSearch = (e: React.FormEvent) => {
     e.preventDefault();
     performSearch(searchQuery);
   };
   const clearSearch = () => {
     setSearchQuery("");
+    setPreview(null);
+    cancelSearch();
+  };
+  const previewReplace = async () => {
+    if (!projectPath || !searchQuery) return;
+    try {
+      setPreview(
+        await invoke<ReplacePreviewSet>("preview_replace", {
+          path: projectPath,
+          query: searchQuery,
+          replacement,
+          options: searchOptions,
+        })
+      );
+    } catch (err) {
+      console.error("Replace preview failed:", err);
+    }
+  };
+  const applyReplace = async () => {
+    if (!preview) return;
+    try {
+      const outcome = await invoke<ReplaceOutcome>("replace_in_files", {
+        query: searchQuery,
+        replacement,
+        options: searchOptions,
+        files: preview.files.map(({ file, version }) => ({ file, version })),
+      });
+      setUndoToken(outcome.undo_token);
+      setPreview(null);
+    } catch (err) {
+      console.error("Replace failed:", err);
+    }
+  };
+  const undoReplace = async () => {
+    if (!undoToken) return;
+    try {
+      await invoke("undo_replace", { undoToken });
+      setUndoToken(null);
+    } catch (err) {
+      console.error("Undo replace failed:", err);
+    }
   };
   const handleResultClick = async (result: any) => {
     try {
-      const content = await invoke<string>("read_file_content", {
+      const { content, is_binary, version } = await invoke<FileContent>("read_file_content", {
         path: result.file,
       });
       const name = result.file.split(/[/\\]/).pop() || result.file;
@@ -32,8 +125,14 @@ export default function SearchView() {
         content,
         language: getLanguageFromPath(result.file),
         isDirty: false,
+        readOnly: is_binary,
+        version,
       });
-    } catch (err) {
+    } catch (err: any) {
+      if (err?.kind === "too_large") {
+        openLargeFile(result.file);
+        return;
+      }
       console.error("Error opening file:", err);
     }
   };
@@ -62,17 +161,160 @@ export default function SearchView() {
             </Button>
           )}
           {isSearching && (
-            <Loader2
-              className="absolute right-2 top-1.5 animate-spin text-gray-400"
-              size={14}
-            />
+            <Button
+              variant="ghost"
+              size="icon"
+              title="Stop search"
+              className="absolute right-1 top-0.5 h-6 w-6 text-gray-400 hover:text-white cursor-pointer hover:bg-transparent"
+              onClick={cancelSearch}
+            >
+              <Loader2 className="animate-spin" size={14} />
+            </Button>
           )}
         </form>
+        {!structuralSearch && (
+          <div className="flex gap-1 mt-2">
+            <Input
+              type="text"
+              value={replacement}
+              onChange={(e) => setReplacement(e.target.value)}
+              placeholder="Replace"
+              className="flex-1 bg-[#3c3c3c] border-[#555] text-white placeholder:text-gray-500 h-8 text-sm"
+            />
+            <Button
+              variant="ghost"
+              title="Preview Replace"
+              onClick={previewReplace}
+              className="h-8 px-2 text-xs text-gray-300 cursor-pointer"
+            >
+              Preview
+            </Button>
+          </div>
+        )}
+        <div className="flex gap-1 mt-2">
+          {toggles.map(({ key, label, title }) => (
+            <Button
+              key={key}
+              variant="ghost"
+              size="icon"
+              title={title}
+              onClick={() => setSearchOptions({ [key]: !searchOptions[key] })}
+              className={`h-6 w-7 text-[11px] font-mono cursor-pointer ${searchOptions[key] ? "bg-[#094771] text-white" : "text-gray-400"}`}
+            >
+              {label}
+            </Button>
+          ))}
+          <Button
+            variant="ghost"
+            size="icon"
+            title="Structural Search ($VAR and $$$VARS match code nodes)"
+            onClick={() => setStructuralSearch(!structuralSearch)}
+            className={`h-6 w-7 text-[11px] font-mono cursor-pointer ${structuralSearch ? "bg-[#094771] text-white" : "text-gray-400"}`}
+          >
+            {"{}"}
+          </Button>
+        </div>
       </div>
       <div className="flex-1 overflow-y-auto scrollbar-thin scrollbar-thumb-[#424242] scrollbar-track-transparent">
-        {searchResults.length > 0 && (
+        {undoToken && !preview && (
+          <div className="px-4 pb-2 flex items-center justify-between text-xs text-gray-400">
+            Replaced.
+            <Button
+              variant="ghost"
+              onClick={undoReplace}
+              className="h-6 px-2 text-xs text-gray-300 cursor-pointer"
+            >
+              Undo
+            </Button>
+          </div>
+        )}
+        {preview && (
+          <div className="px-4 pb-2 text-xs">
+            <div className="flex items-center justify-between text-gray-400">
+              {preview.files.length} files will change
+              <div className="flex gap-1">
+                <Button
+                  variant="ghost"
+                  onClick={() => setPreview(null)}
+                  className="h-6 px-2 text-xs text-gray-300 cursor-pointer"
+                >
+                  Cancel
+                </Button>
+                <Button
+                  variant="ghost"
+                  disabled={preview.files.length === 0}
+                  onClick={applyReplace}
+                  className="h-6 px-2 text-xs bg-[#094771] text-white cursor-pointer"
+                >
+                  Replace All
+                </Button>
+              </div>
+            </div>
+            {preview.truncated && (
+              <div className="mt-1 text-yellow-500">
+                More files match than can be previewed. Only the files listed are replaced; narrow the
+                search to reach the rest.
+              </div>
+            )}
+            {preview.files.map((file) => (
+              <div
+                key={file.file}
+                title={file.unified}
+                className="flex justify-between py-1 border-b border-[#2a2d2e]"
+              >
+                <span className="truncate text-blue-400">{file.file.split(/[/\\]/).pop()}</span>
+                <span className="text-[10px] text-gray-500">
+                  {file.replacements} · +{file.insertions} −{file.deletions}
+                </span>
+              </div>
+            ))}
+          </div>
+        )}
+        {(searchResults.length > 0 || isSearching) && (
           <div className="px-4 pb-2 text-xs text-gray-400">
             {searchResults.length} results found
+            {isSearching && ` (${searchFilesScanned} files scanned)`}
+          </div>
+        )}
+        {!searchQuery && !isSearching && searchResults.length === 0 && history.length > 0 && (
+          <div className="px-2 pb-2">
+            <div className="px-2 pb-1 text-[11px] uppercase tracking-wide text-gray-500">
+              Recent Searches
+            </div>
+            {history.map((entry) => (
+              <div
+                key={entry.id}
+                onClick={() => rerun(entry)}
+                className="flex items-center gap-1 px-2 py-1 rounded hover:bg-[#2a2d2e] cursor-pointer group text-xs"
+              >
+                <span className="flex-1 truncate font-mono" title={entry.query}>
+                  {entry.name || entry.query}
+                </span>
+                {entry.kind === "structural" && (
+                  <span className="text-[10px] text-gray-500 font-mono">{"{}"}</span>
+                )}
+                <button
+                  title={entry.pinned ? "Unpin" : "Pin"}
+                  onClick={(e) => {
+                    e.stopPropagation();
+                    togglePin(entry);
+                  }}
+                  className={`${entry.pinned ? "text-white" : "text-gray-500 opacity-0 group-hover:opacity-100"} hover:text-white cursor-pointer`}
+                >
+                  <Pin size={12} />
+                </button>
+                <button
+                  title="Remove"
+                  onClick={(e) => {
+                    e.stopPropagation();
+                    removeEntry(entry);
+                  }}
+                  className="text-gray-500 opacity-0 group-hover:opacity-100 hover:text-white cursor-pointer"
+                >
+                  <X size={12} />
+                </button>
+              </div>
+            ))}
           </div>
         )}
         {searchResults.map((result, i) => (
@@ -91,7 +333,7 @@ export default function SearchView() {
               <span className="text-[10px] text-gray-500">:{result.line}</span>
             </div>
             <div className="text-xs text-gray-300 truncate font-mono opacity-80">
-              {result.content}
+              {result.content.trim()}
             </div>
           </div>
         ))}
@@ -96,6 +96,30 @@ export default function SettingsView() {
               </div>
             </div>
           </section>
+          {/* Terminal Section */}
+          <section>
+            <h2 className="text-sm font-bold uppercase tracking-wider text-blue-400 mb-4">
+              Terminal
+            </h2>
+            <div className="space-y-4">
+              <div className="flex items-center gap-3">
+                <Checkbox
+                  id="terminalRawOutput"
+                  checked={settings.terminalRawOutput}
+                  onCheckedChange={(checked) =>
+                    handleChange("terminalRawOutput", checked)
+                  }
+                  className="w-4 h-4 border-gray-600 data-[state=checked]:bg-blue-600 data-[state=checked]:border-blue-600"
+                />
+                <label
+                  htmlFor="terminalRawOutput"
+                  className="text-sm font-medium select-none cursor-pointer"
+                >
+                  Send raw terminal output (applies to new terminals)
+                </label>
+              </div>
+            </div>
+          </section>
           {/* Window Section */}
           <section>
             <h2 className="text-sm font-bold uppercase tracking-wider text-blue-400 mb-4">
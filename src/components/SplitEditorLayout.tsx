@@ -1,9 +1,9 @@
 import { useEditorStore } from "../lib/store";
 import CodeEditor, { type CodeEditorHandle } from "./ui/CodeEditor";
+import LargeFileView from "./LargeFileView";
 import FindReplaceWidget, { type FindOptions } from "./FindReplaceWidget";
 import { Button } from "./ui/button";
 import { X, ArrowLeftRight, ArrowUpDown, XCircle, Save } from "lucide-react";
-import { invoke } from "@tauri-apps/api/core";
 import {
   Dialog,
   DialogContent,
@@ -31,7 +31,7 @@ function EditorPane({ groupId }: EditorPaneProps) {
     setGroupActiveFile,
     closeFile,
     updateFileContent,
-    markFileDirty,
+    saveFile,
     splitDirection,
     isFindWidgetOpen,
     isFindReplaceMode,
@@ -94,11 +94,7 @@ function EditorPane({ groupId }: EditorPaneProps) {
   const handleSave = async () => {
     if (!activeFileObj) return;
     try {
-      await invoke("save_file_content", {
-        path: activeFileObj.path,
-        content: activeFileObj.content,
-      });
-      markFileDirty(activeFileObj.path, false);
+      await saveFile(activeFileObj.path);
     } catch (err) {
       console.error("Failed to save:", err);
     }
@@ -115,17 +111,15 @@ function EditorPane({ groupId }: EditorPaneProps) {
   const handleConfirmClose = async (saveFirst: boolean) => {
     if (!fileToClose) return;
     if (saveFirst) {
-      const file = openFiles.find((f) => f.path === fileToClose);
-      if (file) {
-        try {
-          await invoke("save_file_content", {
-            path: file.path,
-            content: file.content,
-          });
-          markFileDirty(file.path, false);
-        } catch (err) {
-          console.error("Failed to save:", err);
-        }
+      // Keep the tab open when the save fails or the user backs out of overwriting disk changes.
+      const saved = await saveFile(fileToClose).catch((err) => {
+        console.error("Failed to save:", err);
+        return false;
+      });
+      if (!saved) {
+        setIsCloseDialogOpen(false);
+        setFileToClose(null);
+        return;
       }
     }
     closeFile(fileToClose);
@@ -160,7 +154,12 @@ function EditorPane({ groupId }: EditorPaneProps) {
                 }
             `}
             >
-              <span className="truncate flex-1">{file.name}</span>
+              <span
+                className={`truncate flex-1 ${file.changedOnDisk ? "text-yellow-500" : ""}`}
+                title={file.changedOnDisk ? "Changed on disk" : undefined}
+              >
+                {file.name}
+              </span>
               <div className="flex items-center justify-center w-5 h-5 relative">
                 {file.isDirty && (
                   <div className="w-2 h-2 rounded-full bg-white absolute group-hover:opacity-0 transition-opacity" />
@@ -190,7 +189,7 @@ function EditorPane({ groupId }: EditorPaneProps) {
                 e.stopPropagation();
                 handleSave();
               }}
-              disabled={!activeFileObj.isDirty}
+              disabled={!activeFileObj.isDirty || activeFileObj.readOnly}
               className={`h-7 px-3 flex items-center gap-2 text-xs transition-colors ${activeFileObj.isDirty
                   ? "text-white hover:bg-[#094771] hover:text-white"
                   : "text-gray-600 cursor-not-allowed"
@@ -224,12 +223,19 @@ function EditorPane({ groupId }: EditorPaneProps) {
           />
         )}
         {/* Always keep editor mounted but hidden when no file is active to improve performance */}
-        <div className={`h-full ${activeFileObj ? "" : "hidden"}`}>
+        {activeFileObj?.largeFile && (
+          <LargeFileView
+            path={activeFileObj.path}
+            changedOnDisk={activeFileObj.changedOnDisk}
+          />
+        )}
+        <div className={`h-full ${activeFileObj && !activeFileObj.largeFile ? "" : "hidden"}`}>
           <CodeEditor
             ref={editorRef}
             code={activeFileObj?.content || ""}
             language={activeFileObj?.language || "plaintext"}
             filePath={activeFileObj?.path || ""}
+            readOnly={activeFileObj?.readOnly}
             onChange={(value) => {
               if (value !== undefined && activeFileObj) {
                 handleContentChange(activeFileObj.path, value);
@@ -1,5 +1,6 @@
 import { useEffect, useRef, useState } from "react";
 import { useEditorStore } from "../lib/store";
+import { useSettingsStore } from "../lib/settingsStore";
 import {
   X,
   Plus,
@@ -17,6 +18,20 @@ import { invoke } from "@tauri-apps/api/core";
 import { listen } from "@tauri-apps/api/event";
 import XtermTerminal from "./ui/XtermTerminal";
 import type { XtermTerminalHandle } from "./ui/XtermTerminal";
+import type { PtySessionInfo, TerminalProfile } from "../lib/types";
+interface PtyData {
+  seq: number;
+  data: string;
+}
+interface PtyExit {
+  code: number;
+  signal: string | null;
+}
+// Restored scrollback may end inside a full-screen program; leave the alternate screen and turn off
+// mouse reporting, bracketed paste and other modes before the fresh shell starts writing.
+const RESET_MODES =
+  "\x1b[?1049l\x1b[?1000l\x1b[?1002l\x1b[?1003l\x1b[?1006l\x1b[?2004l\x1b[!p\x1b[?25h\x1b[0m";
+const decodeBase64 = (data: string) => Uint8Array.from(atob(data), (c) => c.charCodeAt(0));
 interface VirtualEnv {
   path: string;
   type: "venv" | "virtualenv" | "conda";
@@ -31,31 +46,124 @@ export default function TerminalView() {
     setActiveTerminal,
     activateVenvInTerminal,
     setTerminalRunning,
+    restoreTerminal,
   } = useEditorStore();
   const [detectedVenvs, setDetectedVenvs] = useState<VirtualEnv[]>([]);
   const [showVenvMenu, setShowVenvMenu] = useState(false);
+  const [profiles, setProfiles] = useState<TerminalProfile[]>([]);
+  const [showProfileMenu, setShowProfileMenu] = useState(false);
   const terminalRefs = useRef<{ [key: string]: XtermTerminalHandle | null }>(
     {}
   );
   const initializedRefs = useRef<Set<string>>(new Set());
+  // Sessions already running in the backend when this view mounted, e.g. after a webview reload.
+  const runningRef = useRef<Promise<PtySessionInfo[]> | null>(null);
+  const runningSessions = () => {
+    if (!runningRef.current) {
+      runningRef.current = invoke<PtySessionInfo[]>("list_pty_sessions").catch((err) => {
+        console.error("Failed to list terminal sessions:", err);
+        return [];
+      });
+    }
+    return runningRef.current;
+  };
+  const lastSeqRefs = useRef<Record<string, number>>({});
+  // Output that arrives while a session's backlog is still being fetched.
+  const pendingRefs = useRef<Record<string, PtyData[]>>({});
+  const unlistenRefs = useRef<Record<string, (() => void)[]>>({});
+  // Sessions spawned in binary mode send base64-encoded bytes instead of text.
+  const binaryRefs = useRef<Record<string, boolean>>({});
+  const writeOutput = (id: string, data: string) => {
+    terminalRefs.current[id]?.write(binaryRefs.current[id] ? decodeBase64(data) : data);
+  };
   const activeTerminal = terminals.find((t) => t.id === activeTerminalId);
   useEffect(() => {
     if (projectPath && terminals.length > 0) {
       detectVirtualEnvironments();
     }
   }, [projectPath, terminals.length]);
+  useEffect(() => {
+    invoke<{ profiles: TerminalProfile[]; default_profile: string | null }>(
+      "list_terminal_profiles",
+      { cwd: projectPath || null }
+    )
+      .then((result) => setProfiles(result.profiles))
+      .catch(console.error);
+  }, [projectPath]);
+  useEffect(() => {
+    runningSessions().then((sessions) => sessions.forEach(restoreTerminal));
+    return () => {
+      Object.values(unlistenRefs.current).forEach((fns) => fns.forEach((u) => u()));
+      unlistenRefs.current = {};
+    };
+  }, []);
+  const subscribe = async (id: string) => {
+    const unlistenData = await listen<PtyData>(`term-data-${id}`, (event) => {
+      const pending = pendingRefs.current[id];
+      if (pending) {
+        pending.push(event.payload);
+        return;
+      }
+      if (event.payload.seq <= (lastSeqRefs.current[id] ?? 0)) return;
+      lastSeqRefs.current[id] = event.payload.seq;
+      writeOutput(id, event.payload.data);
+    });
+    const unlistenExit = await listen<PtyExit>(`term-exit-${id}`, (event) => {
+      const { code, signal } = event.payload;
+      terminalRefs.current[id]?.writeln(
+        signal
+          ? `\r\nProcess terminated by ${signal}`
+          : `\r\nProcess exited with code ${code}`
+      );
+      setTerminalRunning(id, false);
+    });
+    unlistenRefs.current[id] = [unlistenData, unlistenExit];
+  };
+  const reattach = async (id: string) => {
+    pendingRefs.current[id] = [];
+    try {
+      const attachment = await invoke<{ seq: number; backlog: string }>("attach_pty", { id });
+      writeOutput(id, attachment.backlog);
+      lastSeqRefs.current[id] = attachment.seq;
+    } finally {
+      const pending = pendingRefs.current[id] || [];
+      delete pendingRefs.current[id];
+      for (const chunk of pending) {
+        if (chunk.seq <= (lastSeqRefs.current[id] ?? 0)) continue;
+        lastSeqRefs.current[id] = chunk.seq;
+        writeOutput(id, chunk.data);
+      }
+    }
+  };
   useEffect(() => {
     const manageTerminals = async () => {
       for (const terminal of terminals) {
         if (!initializedRefs.current.has(terminal.id)) {
           initializedRefs.current.add(terminal.id);
           try {
-            // Note: If we reload the window, we might lose backend sessions if they aren't persisted.
+            const running = await runningSessions();
+            const session = running.find((s) => s.id === terminal.id);
+            binaryRefs.current[terminal.id] = session
+              ? session.binary
+              : useSettingsStore.getState().settings.terminalRawOutput;
+            await subscribe(terminal.id);
+            if (session) {
+              await reattach(terminal.id);
+              continue;
+            }
+            if (terminal.restoredOutput) {
+              const restoredAt = new Date(terminal.restoredAt ?? Date.now()).toLocaleString();
+              terminalRefs.current[terminal.id]?.write(
+                `${terminal.restoredOutput}${RESET_MODES}\r\n\x1b[90m── Restored session from ${restoredAt} ──\x1b[0m\r\n`
+              );
+            }
             await invoke("spawn_pty", {
               id: terminal.id,
               rows: 24, // Initial guess, will be resized
               cols: 80,
               cwd: terminal.cwd || projectPath || undefined,
+              profile: terminal.profileId,
+              binary: binaryRefs.current[terminal.id],
             });
             setTerminalRunning(terminal.id, true);
           } catch (err) {
@@ -67,30 +175,6 @@ export default function TerminalView() {
     };
     manageTerminals();
   }, [terminals, projectPath]);
-  const listenersRef = useRef<Set<string>>(new Set());
-  useEffect(() => {
-    const unlisteners: (() => void)[] = [];
-    const setupListeners = async () => {
-      for (const terminal of terminals) {
-        if (listenersRef.current.has(terminal.id)) continue;
-        listenersRef.current.add(terminal.id);
-        const unlistenData = await listen<string>(`term-data-${terminal.id}`, (event) => {
-          terminalRefs.current[terminal.id]?.write(event.payload);
-        });
-        unlisteners.push(unlistenData);
-        const unlistenExit = await listen<number | null>(`term-exit-${terminal.id}`, (event) => {
-          terminalRefs.current[terminal.id]?.writeln(`\r\nProcess exited with code ${event.payload}`);
-          setTerminalRunning(terminal.id, false);
-        });
-        unlisteners.push(unlistenExit);
-      }
-    };
-    setupListeners();
-    return () => {
-      unlisteners.forEach(u => u());
-      // Note: we don't clear listenersRef here to assume persistence.
-    };
-  }, [terminals]);
   const detectVirtualEnvironments = async () => {
     if (!projectPath) return;
     try {
@@ -123,10 +207,14 @@ export default function TerminalView() {
       console.error(e);
     }
   };
-  const handleNewTerminal = () => {
-    addTerminal(projectPath || undefined);
+  const handleNewTerminal = (profile?: TerminalProfile) => {
+    addTerminal(projectPath || undefined, profile?.name, profile?.id);
+    setShowProfileMenu(false);
   };
   const handleCloseTerminal = async (id: string) => {
+    if (terminals.find((t) => t.id === id)?.isRunning) {
+      invoke("kill_pty", { id }).catch(console.error);
+    }
     removeTerminal(id);
     if (terminalRefs.current[id]) {
       delete terminalRefs.current[id];
@@ -134,9 +222,10 @@ export default function TerminalView() {
     if (initializedRefs.current.has(id)) {
       initializedRefs.current.delete(id);
     }
-    if (listenersRef.current.has(id)) {
-      listenersRef.current.delete(id);
-    }
+    unlistenRefs.current[id]?.forEach((u) => u());
+    delete unlistenRefs.current[id];
+    delete binaryRefs.current[id];
+    delete lastSeqRefs.current[id];
   };
   const handleClearTerminal = () => {
     if (activeTerminalId) {
@@ -144,7 +233,11 @@ export default function TerminalView() {
     }
   };
   const handleSplitTerminal = () => {
-    addTerminal(activeTerminal?.cwd || projectPath || undefined);
+    addTerminal(
+      activeTerminal?.cwd || projectPath || undefined,
+      undefined,
+      activeTerminal?.profileId
+    );
   };
   const handleResize = (id: string, rows: number, cols: number) => {
     invoke("resize_pty", { id, rows, cols }).catch(console.error);
@@ -204,12 +297,40 @@ export default function TerminalView() {
             <Button
               variant="ghost"
               size="icon"
-              onClick={handleNewTerminal}
+              onClick={() => handleNewTerminal()}
               className="h-8 w-8 p-1 hover:bg-[#2d2d2d] rounded text-gray-400 hover:text-white shrink-0"
               title="New Terminal"
             >
               <Plus size={16} />
             </Button>
+            {profiles.length > 1 && (
+              <div className="relative shrink-0">
+                <Button
+                  variant="ghost"
+                  size="icon"
+                  onClick={() => setShowProfileMenu(!showProfileMenu)}
+                  className="h-8 w-5 p-0 hover:bg-[#2d2d2d] rounded text-gray-400 hover:text-white"
+                  title="New Terminal with Profile"
+                >
+                  <ChevronDown size={12} />
+                </Button>
+                {showProfileMenu && (
+                  <div className="fixed mt-1 bg-[#2d2d2d] border border-[#444] rounded shadow-lg z-50 min-w-40">
+                    {profiles.map((profile) => (
+                      <button
+                        key={profile.id}
+                        onClick={() => handleNewTerminal(profile)}
+                        className="w-full px-3 py-2 text-xs text-left hover:bg-[#333] text-gray-300 hover:text-white flex items-center gap-2"
+                        title={profile.shell}
+                      >
+                        <TerminalIcon size={12} />
+                        <span className="flex-1 truncate">{profile.name}</span>
+                      </button>
+                    ))}
+                  </div>
+                )}
+              </div>
+            )}
           </div>
           {/* Terminal Toolbar */}
           <div className="flex items-center justify-between px-3 py-1.5 bg-[#252526] border-b border-[#333]">
@@ -318,7 +439,7 @@ export default function TerminalView() {
               Create a new terminal to get started
             </p>
             <Button
-              onClick={handleNewTerminal}
+              onClick={() => handleNewTerminal()}
               className="bg-blue-600 hover:bg-blue-500 text-white"
               size="default"
             >
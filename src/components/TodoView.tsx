@@ -1,6 +1,7 @@
 import { useState, useEffect } from "react";
 import { invoke } from "@tauri-apps/api/core";
 import { useEditorStore } from "../lib/store";
+import type { FileContent } from "../lib/types";
 import { ScrollArea } from "./ui/scroll-area";
 import { Button } from "./ui/button";
 import { Badge } from "./ui/badge";
@@ -10,27 +11,54 @@ import {
   AlertCircle,
   FileText,
   ChevronRight,
+  CircleDot,
+  FileDown,
 } from "lucide-react";
 interface TodoItem {
   file: string;
   line: number;
-  type: "TODO" | "FIXME" | "HACK" | "NOTE" | "BUG";
+  column: number;
+  type: string;
   text: string;
+  owner: string | null;
+  issue: string | null;
+  priority: number;
+  author: string | null;
+  commit: string | null;
+  committed_at: number | null;
+}
+type TodoSort = "priority" | "age" | "file";
+interface TodoTag {
+  name: string;
+  priority: number;
+  color: string | null;
 }
 export default function TodoView() {
-  const { projectPath, setActiveFile, openFile } = useEditorStore();
+  const { projectPath, setActiveFile, openFile, openLargeFile } = useEditorStore();
   const [todos, setTodos] = useState<TodoItem[]>([]);
+  const [tags, setTags] = useState<TodoTag[]>([]);
   const [isLoading, setIsLoading] = useState(false);
   const [filter, setFilter] = useState<string | null>(null);
+  const [sort, setSort] = useState<TodoSort>("priority");
+  const [blame, setBlame] = useState(false);
+  const [author, setAuthor] = useState("");
+  const [minAgeDays, setMinAgeDays] = useState("");
   const fetchTodos = async () => {
     if (!projectPath) return;
     setIsLoading(true);
     try {
-      const result = await invoke<string>("search_todos", {
+      const minAge = parseInt(minAgeDays, 10);
+      const result = await invoke<TodoItem[]>("search_todos", {
         path: projectPath,
+        options: {
+          blame,
+          sort,
+          authors: author.trim() ? [author.trim()] : [],
+          min_age_days: Number.isNaN(minAge) ? null : minAge,
+        },
       });
-      const parsed = JSON.parse(result);
-      setTodos(parsed);
+      setTodos(result);
+      setTags(await invoke<TodoTag[]>("list_todo_tags", { path: projectPath }));
     } catch (err) {
       console.error("Failed to fetch TODOs:", err);
       setTodos([]);
@@ -40,10 +68,10 @@ export default function TodoView() {
   };
   useEffect(() => {
     fetchTodos();
-  }, [projectPath]);
+  }, [projectPath, sort, blame]);
   const handleTodoClick = async (todo: TodoItem) => {
     try {
-      const content = await invoke<string>("read_file_content", {
+      const { content, is_binary, version } = await invoke<FileContent>("read_file_content", {
         path: todo.file,
       });
       const name = todo.file.split(/[/\\]/).pop() || todo.file;
@@ -54,13 +82,50 @@ export default function TodoView() {
         content,
         language,
         isDirty: false,
+        readOnly: is_binary,
+        version,
       });
       // TODO: Navigate to line number
       setActiveFile(todo.file);
-    } catch (err) {
+    } catch (err: any) {
+      if (err?.kind === "too_large") {
+        openLargeFile(todo.file);
+        return;
+      }
       console.error("Failed to open file:", err);
     }
   };
+  const exportTodos = async (format: "markdown" | "csv") => {
+    if (!projectPath) return;
+    try {
+      const report = await invoke<string>("export_todos", {
+        path: projectPath,
+        format,
+        options: { tags: filter ? [filter] : [], sort, blame },
+      });
+      await navigator.clipboard.writeText(report);
+    } catch (err) {
+      console.error("Failed to export TODOs:", err);
+    }
+  };
+  const promoteTodo = async (e: React.MouseEvent, todo: TodoItem) => {
+    e.stopPropagation();
+    try {
+      const promoted = await invoke<{ issue: string; item: TodoItem }>(
+        "promote_todo_to_issue",
+        { file: todo.file, line: todo.line }
+      );
+      setTodos((prev) =>
+        prev.map((t) =>
+          t.file === todo.file && t.line === todo.line
+            ? { ...t, issue: promoted.issue }
+            : t
+        )
+      );
+    } catch (err) {
+      console.error("Failed to create issue:", err);
+    }
+  };
   const getLanguage = (path: string): string => {
     const ext = path.split(".").pop()?.toLowerCase();
     const langMap: Record<string, string> = {
@@ -81,32 +146,21 @@ export default function TodoView() {
     };
     return langMap[ext || ""] || "plaintext";
   };
-  const getTypeColor = (type: string) => {
-    switch (type) {
-      case "TODO":
-        return "bg-blue-500/20 text-blue-400";
-      case "FIXME":
-        return "bg-red-500/20 text-red-400";
-      case "HACK":
-        return "bg-yellow-500/20 text-yellow-400";
-      case "BUG":
-        return "bg-red-500/20 text-red-400";
-      case "NOTE":
-        return "bg-green-500/20 text-green-400";
-      default:
-        return "bg-gray-500/20 text-gray-400";
-    }
+  const getTypeStyle = (type: string) => {
+    const color = tags.find((t) => t.name === type)?.color || "#9ca3af";
+    return { color, backgroundColor: `${color}33` };
   };
-  const getTypeIcon = (type: string) => {
-    switch (type) {
-      case "TODO":
-        return <CheckCircle size={14} />;
-      case "FIXME":
-      case "BUG":
-        return <AlertCircle size={14} />;
-      default:
-        return <FileText size={14} />;
-    }
+  const formatAge = (committedAt: number | null) => {
+    if (committedAt === null) return "uncommitted";
+    const days = Math.floor((Date.now() - committedAt) / 86_400_000);
+    if (days < 1) return "today";
+    if (days < 60) return `${days}d ago`;
+    return `${Math.floor(days / 30)}mo ago`;
+  };
+  const getTypeIcon = (todo: TodoItem) => {
+    if (todo.priority === 0) return <AlertCircle size={14} />;
+    if (todo.priority === 1) return <CheckCircle size={14} />;
+    return <FileText size={14} />;
   };
   const filteredTodos = filter ? todos.filter((t) => t.type === filter) : todos;
   const typeCounts = todos.reduce((acc, todo) => {
@@ -128,15 +182,70 @@ export default function TodoView() {
         <span className="text-xs font-bold text-gray-400 uppercase">
           TODO / Tasks
         </span>
-        <Button
-          variant="ghost"
-          size="icon"
-          onClick={fetchTodos}
-          className="h-6 w-6 text-gray-400 hover:text-white"
-          title="Refresh"
+        <div className="flex items-center gap-1">
+          <Button
+            variant="ghost"
+            size="icon"
+            onClick={() => exportTodos("markdown")}
+            className="h-6 w-6 text-gray-400 hover:text-white"
+            title="Copy as Markdown checklist"
+          >
+            <FileDown size={14} />
+          </Button>
+          <Button
+            variant="ghost"
+            size="icon"
+            onClick={() => exportTodos("csv")}
+            className="h-6 px-1 w-auto text-[10px] text-gray-400 hover:text-white"
+            title="Copy as CSV"
+          >
+            CSV
+          </Button>
+          <Button
+            variant="ghost"
+            size="icon"
+            onClick={fetchTodos}
+            className="h-6 w-6 text-gray-400 hover:text-white"
+            title="Refresh"
+          >
+            <RefreshCw size={14} className={isLoading ? "animate-spin" : ""} />
+          </Button>
+        </div>
+      </div>
+      {/* Sort & Blame Filters */}
+      <div className="flex gap-2 px-2 pt-2 items-center text-xs">
+        <select
+          value={sort}
+          onChange={(e) => setSort(e.target.value as TodoSort)}
+          className="bg-[#333] text-gray-300 rounded px-1 py-0.5"
+          title="Sort by"
         >
-          <RefreshCw size={14} className={isLoading ? "animate-spin" : ""} />
-        </Button>
+          <option value="priority">Priority</option>
+          <option value="age">Oldest first</option>
+          <option value="file">File</option>
+        </select>
+        <label className="flex items-center gap-1 text-gray-400">
+          <input
+            type="checkbox"
+            checked={blame}
+            onChange={(e) => setBlame(e.target.checked)}
+          />
+          Blame
+        </label>
+        <input
+          value={author}
+          onChange={(e) => setAuthor(e.target.value)}
+          onKeyDown={(e) => e.key === "Enter" && fetchTodos()}
+          placeholder="Author"
+          className="bg-[#333] text-gray-300 rounded px-1 py-0.5 w-20 min-w-0"
+        />
+        <input
+          value={minAgeDays}
+          onChange={(e) => setMinAgeDays(e.target.value.replace(/\D/g, ""))}
+          onKeyDown={(e) => e.key === "Enter" && fetchTodos()}
+          placeholder="Min days"
+          className="bg-[#333] text-gray-300 rounded px-1 py-0.5 w-16 min-w-0"
+        />
       </div>
       {/* Filter Badges */}
       <div className="flex gap-2 p-2 border-b border-[#333] flex-wrap">
@@ -150,17 +259,16 @@ export default function TodoView() {
         >
           All ({todos.length})
         </Badge>
-        {["TODO", "FIXME", "HACK", "BUG", "NOTE"].map((type) => {
+        {tags.map(({ name: type }) => {
           const count = typeCounts[type] || 0;
           if (count === 0) return null;
           return (
             <Badge
               key={type}
               onClick={() => setFilter(type)}
+              style={filter === type ? getTypeStyle(type) : undefined}
               className={`cursor-pointer text-xs ${
-                filter === type
-                  ? getTypeColor(type)
-                  : "bg-[#333] text-gray-400 hover:bg-[#444]"
+                filter === type ? "" : "bg-[#333] text-gray-400 hover:bg-[#444]"
               }`}
             >
               {type} ({count})
@@ -191,15 +299,14 @@ export default function TodoView() {
                 className="px-4 py-2 hover:bg-[#2a2d2e] cursor-pointer border-b border-[#1e1e1e] group"
               >
                 <div className="flex items-start gap-2">
-                  <div className={`mt-0.5 ${getTypeColor(todo.type)}`}>
-                    {getTypeIcon(todo.type)}
+                  <div className="mt-0.5" style={{ color: getTypeStyle(todo.type).color }}>
+                    {getTypeIcon(todo)}
                   </div>
                   <div className="flex-1 min-w-0">
                     <div className="flex items-center gap-2 mb-1">
                       <Badge
-                        className={`text-[10px] h-4 px-1 ${getTypeColor(
-                          todo.type
-                        )}`}
+                        style={getTypeStyle(todo.type)}
+                        className="text-[10px] h-4 px-1"
                       >
                         {todo.type}
                       </Badge>
@@ -210,10 +317,26 @@ export default function TodoView() {
                     <p className="text-sm text-gray-300">{todo.text}</p>
                     <div className="flex items-center gap-1 mt-1 text-xs text-gray-500">
                       <span>Line {todo.line}</span>
+                      {todo.owner && <span>· @{todo.owner}</span>}
+                      {todo.issue && <span>· #{todo.issue}</span>}
+                      {(todo.author || todo.committed_at !== null) && (
+                        <span title={todo.commit ?? undefined}>
+                          · {todo.author ?? "You"}, {formatAge(todo.committed_at)}
+                        </span>
+                      )}
                       <ChevronRight
                         size={12}
                         className="opacity-0 group-hover:opacity-100"
                       />
+                      {!todo.issue && (
+                        <button
+                          onClick={(e) => promoteTodo(e, todo)}
+                          className="ml-auto opacity-0 group-hover:opacity-100 hover:text-white"
+                          title="Promote to GitHub issue"
+                        >
+                          <CircleDot size={12} />
+                        </button>
+                      )}
                     </div>
                   </div>
                 </div>
@@ -35,12 +35,13 @@ interface CodeEditorProps {
   language?: string;
   filePath?: string;
   onChange?: (value: string | undefined) => void;
+  readOnly?: boolean;
 }
 export interface CodeEditorHandle {
   getEditor: () => any;
 }
 const CodeEditor = forwardRef<CodeEditorHandle, CodeEditorProps>(
-  ({ code, language = "javascript", filePath, onChange }, ref) => {
+  ({ code, language = "javascript", filePath, onChange, readOnly = false }, ref) => {
     const { settings } = useSettingsStore();
     const editorRef = useRef<any>(null);
     const [isLoading, setIsLoading] = useState(true);
@@ -182,6 +183,7 @@ const CodeEditor = forwardRef<CodeEditorHandle, CodeEditorProps>(
             </div>
           }
           options={{
+            readOnly,
             minimap: { enabled: settings.minimap },
             fontSize: settings.fontSize,
             fontFamily: settings.fontFamily,
@@ -38,6 +38,7 @@ export interface FileNode {
   path: string;
   is_dir: boolean;
   children?: FileNode[];
+  ignored?: boolean;
 }
 interface FileTreeProps {
   node: FileNode;
@@ -88,6 +89,8 @@ const FileTreeNode = ({ node, onSelect, level = 0 }: FileTreeProps) => {
         try {
           const loadedChildren = await invoke<FileNode[]>("read_dir", {
             path: node.path,
+            root: projectPath,
+            showIgnored: true,
           });
           setChildren(loadedChildren);
         } catch (err) {
@@ -248,7 +251,7 @@ const FileTreeNode = ({ node, onSelect, level = 0 }: FileTreeProps) => {
             ) : (
               <FileCode size={16} className="text-yellow-500 shrink-0" />
             )}
-            <span className="truncate">{node.name}</span>
+            <span className={`truncate${node.ignored ? " opacity-50" : ""}`}>{node.name}</span>
           </div>
         </ContextMenuTrigger>
         <ContextMenuContent className="w-56 bg-[#252526] border-[#454545] text-gray-300">
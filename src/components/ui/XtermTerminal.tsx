@@ -4,7 +4,7 @@ import { FitAddon } from "xterm-addon-fit";
 import { invoke } from "@tauri-apps/api/core";
 import "xterm/css/xterm.css";
 export interface XtermTerminalHandle {
-  write: (data: string) => void;
+  write: (data: string | Uint8Array) => void;
   writeln: (data: string) => void;
   clear: () => void;
   fit: () => void;
@@ -23,7 +23,7 @@ const XtermTerminal = forwardRef<XtermTerminalHandle, XtermTerminalProps>(
     const fitAddonRef = useRef<FitAddon | null>(null);
     const isDisposed = useRef(false);
     useImperativeHandle(ref, () => ({
-      write: (data: string) => {
+      write: (data: string | Uint8Array) => {
         if (terminalRef.current && !isDisposed.current) {
           terminalRef.current.write(data);
         }
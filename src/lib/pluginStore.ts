@@ -3,6 +3,7 @@ import { invoke } from "@tauri-apps/api/core";
 import { homeDir, join } from '@tauri-apps/api/path';
 // import { exists, mkdir, BaseDirectory } from '@tauri-apps/plugin-fs';
 import { useEditorStore } from './store';
+import type { FileContent, FileVersion } from './types';
 type EventCallback = (...args: any[]) => void;
 class PluginEventEmitter {
     private events: Map<string, Set<EventCallback>> = new Map();
@@ -35,6 +36,8 @@ class PluginEventEmitter {
     }
 }
 export const pluginEvents = new PluginEventEmitter();
+// Versions seen by plugin reads, so a plugin write cannot silently clobber edits made since.
+const pluginReadVersions = new Map<string, FileVersion>();
 export interface PluginManifest {
     id: string;
     name: string;
@@ -275,10 +278,23 @@ export const usePluginStore = create<PluginState>((set, get) => ({
                 return editorState.projectPath;
             },
             readFile: async (path: string) => {
-                return await invoke<string>('read_file_content', { path });
+                const { content, version } = await invoke<FileContent>('read_file_content', { path });
+                pluginReadVersions.set(path, version);
+                return content;
             },
             writeFile: async (path: string, content: string) => {
-                await invoke('save_file_content', { path, content });
+                const editorState = useEditorStore.getState();
+                const open = editorState.openFiles.find(f => f.path === path);
+                const expected = pluginReadVersions.get(path) ?? open?.version ?? null;
+                const version = await invoke<FileVersion>('save_file_content', {
+                    path,
+                    content,
+                    options: { expected_version: expected },
+                });
+                pluginReadVersions.set(path, version);
+                if (open && !open.isDirty) {
+                    editorState.updateOpenFile(path, { content, version });
+                }
                 pluginEvents.emit('file:save', path);
             },
             getOpenFiles: () => {
@@ -9,6 +9,8 @@ export interface EditorSettings {
     lineNumbers: 'on' | 'off';
     tabSize: number;
     autoSave: boolean;
+    // Ask the backend for raw PTY bytes (base64) and let xterm decode them.
+    terminalRawOutput: boolean;
 }
 interface SettingsState {
     settings: EditorSettings;
@@ -26,6 +28,7 @@ export const useSettingsStore = create<SettingsState>()(
                 lineNumbers: 'on',
                 tabSize: 2,
                 autoSave: false,
+                terminalRawOutput: false,
             },
             updateSettings: (newSettings) =>
                 set((state) => ({
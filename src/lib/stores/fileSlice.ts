@@ -1,5 +1,5 @@
 import type { StateCreator } from 'zustand';
-import { type FileSlice, type EditorState } from '../types';
+import { type FileSlice, type EditorState, type FileContent, type FileVersion, type FsChange } from '../types';
 import { type FileNode } from '../../components/ui/FileTree';
 
 export const createFileSlice: StateCreator<EditorState, [], [], FileSlice> = (set, get) => ({
@@ -9,6 +9,11 @@ export const createFileSlice: StateCreator<EditorState, [], [], FileSlice> = (se
     setFileTree: (tree) => set({ fileTree: tree }),
     openFile: (file) => set((state) => {
         const existing = state.openFiles.find((f) => f.path === file.path);
+        if (!existing) {
+            import('@tauri-apps/api/core')
+                .then(({ invoke }) => invoke('record_file_open', { path: file.path }))
+                .catch(() => {});
+        }
         const newFiles = existing
             ? state.openFiles.map(f => f.path === file.path ? { ...f, ...file } : f)
             : [...state.openFiles, file];
@@ -27,6 +32,18 @@ export const createFileSlice: StateCreator<EditorState, [], [], FileSlice> = (se
         };
     }),
     closeFile: (path) => set((state) => {
+        const closing = state.openFiles.find((f) => f.path === path);
+        // Closing a dirty buffer discards it, so its crash-recovery backup has to go too.
+        if (closing?.isDirty) {
+            import('@tauri-apps/api/core')
+                .then(({ invoke }) => invoke('discard_buffer_backup', { path }))
+                .catch(console.error);
+        }
+        if (closing?.largeFile) {
+            import('@tauri-apps/api/core')
+                .then(({ invoke }) => invoke('close_large_file', { path }))
+                .catch(console.error);
+        }
         const newFiles = state.openFiles.filter((f) => f.path !== path);
 
         // Use a Set to track processed groups to update
@@ -66,6 +83,20 @@ export const createFileSlice: StateCreator<EditorState, [], [], FileSlice> = (se
             f.path === path ? { ...f, content, isDirty: markDirty ? true : f.isDirty } : f
         ),
     })),
+    updateOpenFile: (path, changes) => set((state) => ({
+        openFiles: state.openFiles.map((f) =>
+            f.path === path ? { ...f, ...changes } : f
+        ),
+    })),
+    openLargeFile: (path) => get().openFile({
+        path,
+        name: path.split(/[/\\]/).pop() || path,
+        content: '',
+        language: 'plaintext',
+        isDirty: false,
+        readOnly: true,
+        largeFile: true,
+    }),
     markFileDirty: (path, isDirty) => set((state) => ({
         openFiles: state.openFiles.map((f) =>
             f.path === path ? { ...f, isDirty } : f
@@ -89,4 +120,86 @@ export const createFileSlice: StateCreator<EditorState, [], [], FileSlice> = (se
             }
         }
     },
+    saveFile: async (path) => {
+        const file = get().openFiles.find((f) => f.path === path);
+        if (!file || file.readOnly) return false;
+        const { invoke } = await import('@tauri-apps/api/core');
+        try {
+            const version = await invoke<FileVersion>('save_file_content', {
+                path,
+                content: file.content,
+                options: { expected_version: file.version ?? null },
+            });
+            set((state) => ({
+                openFiles: state.openFiles.map((f) =>
+                    f.path === path
+                        ? { ...f, version, isDirty: f.content !== file.content, changedOnDisk: false }
+                        : f
+                ),
+            }));
+            return true;
+        } catch (err: any) {
+            if (err?.kind === 'deleted') {
+                const { ask } = await import('@tauri-apps/plugin-dialog');
+                const recreate = await ask(
+                    `${file.name} was deleted on disk since it was opened. Save it again?`,
+                    { title: 'File deleted on disk', kind: 'warning', okLabel: 'Save', cancelLabel: 'Keep Editing' }
+                );
+                if (!recreate) return false;
+                get().updateOpenFile(path, { version: undefined });
+                return get().saveFile(path);
+            }
+            if (err?.kind !== 'stale') throw err;
+            // Someone else (a replace-all, a TODO promotion, another editor) wrote the file since it was read.
+            const current: FileContent = err.current;
+            const { ask } = await import('@tauri-apps/plugin-dialog');
+            const overwrite = await ask(
+                `${file.name} was changed on disk since it was opened. Overwrite it with your changes?`,
+                { title: 'File changed on disk', kind: 'warning', okLabel: 'Overwrite', cancelLabel: 'Keep Editing' }
+            );
+            if (!overwrite) return false;
+            get().updateOpenFile(path, { version: current.version });
+            return get().saveFile(path);
+        }
+    },
+    applyFsChanges: async ({ root, changes }) => {
+        const { projectPath, refreshTree, renameFile, updateOpenFile } = get();
+        if (root !== projectPath) return;
+        if (changes.some((c: FsChange) => c.kind !== 'modified')) {
+            refreshTree();
+        }
+        const { invoke } = await import('@tauri-apps/api/core');
+        for (const change of changes) {
+            if (change.kind === 'renamed') {
+                renameFile(change.from, change.node.path);
+                continue;
+            }
+            const file = get().openFiles.find((f) => f.path === change.node.path);
+            if (!file) continue;
+            // Large files are paged from disk, so flagging the change is enough for the view to re-read.
+            if (change.kind === 'deleted' || file.largeFile) {
+                updateOpenFile(file.path, { changedOnDisk: true });
+                continue;
+            }
+            try {
+                const current = await invoke<FileContent>('read_file_content', { path: file.path });
+                // Our own saves come back through the watcher with the version we already hold.
+                if (current.version.hash === file.version?.hash) continue;
+                const latest = get().openFiles.find((f) => f.path === file.path);
+                if (!latest) continue;
+                if (latest.isDirty) {
+                    updateOpenFile(file.path, { changedOnDisk: true });
+                } else {
+                    updateOpenFile(file.path, {
+                        content: current.content,
+                        version: current.version,
+                        readOnly: current.is_binary,
+                        changedOnDisk: false,
+                    });
+                }
+            } catch (e) {
+                console.error("Failed to reload changed file", e);
+            }
+        }
+    },
 });
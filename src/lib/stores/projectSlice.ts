@@ -1,5 +1,6 @@
 import type { StateCreator } from 'zustand';
-import { type ProjectSlice, type EditorState } from '../types';
+import { type ProjectSlice, type EditorState, type FileVersion } from '../types';
+import { getLanguageFromPath } from '../utils';
 
 export const createProjectSlice: StateCreator<EditorState, [], [], ProjectSlice> = (set, get) => ({
     projectPath: null,
@@ -21,22 +22,56 @@ export const createProjectSlice: StateCreator<EditorState, [], [], ProjectSlice>
     setCreationState: (state) => set({ creationState: state }),
     openProjectDialog: async () => {
         try {
-            const { open } = await import('@tauri-apps/plugin-dialog');
-            const selected = await open({
-                directory: true,
-                multiple: false,
-            });
-            if (selected && typeof selected === 'string') {
+            const { invoke } = await import('@tauri-apps/api/core');
+            // The backend shows the native picker so only folders the user chose become workspaces.
+            const selected = await invoke<string | null>('pick_workspace');
+            if (selected) {
                 get().openProjectByPath(selected);
             }
         } catch (err) {
             console.error("Failed to open project dialog:", err);
         }
     },
-    openProjectByPath: async (path: string) => {
-        const { setProjectPath, addRecentProject, refreshTree } = get();
+    openProjectByPath: async (requestedPath: string) => {
+        const { projectPath, setProjectPath, addRecentProject, refreshTree } = get();
+        const { invoke } = await import('@tauri-apps/api/core');
+        const path = await invoke<string | null>('open_workspace', { path: requestedPath });
+        if (!path) return;
+        if (projectPath && projectPath !== path) {
+            invoke('close_workspace', { path: projectPath }).catch(console.error);
+        }
+        invoke('watch_project', { path }).catch((err) => console.error("Failed to watch project:", err));
         setProjectPath(path);
         addRecentProject(path);
         await refreshTree();
+        try {
+            const recovered = await invoke<{ path: string; content: string; base_version: FileVersion | null; disk_changed: boolean }[]>('list_recoverable_buffers', { workspace: path });
+            for (const buffer of recovered) {
+                const name = buffer.path.split(/[/\\]/).pop() || buffer.path;
+                if (buffer.disk_changed) {
+                    // Restoring would show the old text as unsaved edits on top of a newer file.
+                    const { ask } = await import('@tauri-apps/plugin-dialog');
+                    const restore = await ask(
+                        `${name} was changed on disk after your unsaved edits were backed up. Restore the edits anyway?`,
+                        { title: 'Unsaved changes recovered', kind: 'warning', okLabel: 'Restore', cancelLabel: 'Discard' }
+                    );
+                    if (!restore) {
+                        invoke('discard_buffer_backup', { path: buffer.path }).catch(console.error);
+                        continue;
+                    }
+                }
+                get().openFile({
+                    path: buffer.path,
+                    name,
+                    content: buffer.content,
+                    language: getLanguageFromPath(buffer.path),
+                    isDirty: true,
+                    version: buffer.base_version ?? undefined,
+                    changedOnDisk: buffer.disk_changed,
+                });
+            }
+        } catch (err) {
+            console.error("Failed to restore unsaved buffers:", err);
+        }
     },
 });
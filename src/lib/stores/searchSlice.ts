@@ -1,5 +1,5 @@
 import type { StateCreator } from 'zustand';
-import { type SearchResult, type SearchSlice, type EditorState } from '../types';
+import { type SearchBatch, type SearchSlice, type EditorState, type StructuralMatch } from '../types';
 
 export const createSearchSlice: StateCreator<EditorState, [], [], SearchSlice> = (set, get) => ({
     isFindWidgetOpen: false,
@@ -7,29 +7,81 @@ export const createSearchSlice: StateCreator<EditorState, [], [], SearchSlice> =
     searchResults: [],
     isSearching: false,
     searchQuery: "",
+    searchOptions: {},
+    currentSearchId: null,
+    searchFilesScanned: 0,
+    structuralSearch: false,
     setFindWidgetOpen: (isOpen) => set({ isFindWidgetOpen: isOpen }),
     setFindReplaceMode: (isReplace) => set({ isFindReplaceMode: isReplace }),
     setSearchQuery: (query) => set({ searchQuery: query }),
+    setSearchOptions: (options) => set((state) => ({ searchOptions: { ...state.searchOptions, ...options } })),
+    setStructuralSearch: (enabled) => set({ structuralSearch: enabled }),
     performSearch: async (query) => {
-        if (!query.trim()) {
+        const { invoke } = await import('@tauri-apps/api/core');
+        const { listen } = await import('@tauri-apps/api/event');
+        const { projectPath, searchOptions, structuralSearch } = get();
+        if (!query.trim() || !projectPath) {
+            await get().cancelSearch();
             set({ searchResults: [] });
             return;
         }
-        set({ isSearching: true });
-        try {
-            const { invoke } = await import('@tauri-apps/api/core');
-            const { projectPath } = get();
-            if (projectPath) {
-                const results = await invoke<SearchResult[]>("search_in_files", {
+        // Recorded here, when the user runs a query, rather than by every backend search.
+        invoke("record_search", {
+            path: projectPath,
+            kind: structuralSearch ? "structural" : "text",
+            query,
+            options: searchOptions,
+            structural: structuralSearch ? { pattern: query } : null,
+        }).catch(() => {});
+        if (structuralSearch) {
+            await get().cancelSearch();
+            set({ searchResults: [], isSearching: true });
+            try {
+                const results = await invoke<StructuralMatch[]>("structural_search", {
                     path: projectPath,
-                    query
+                    query: { pattern: query },
+                    options: searchOptions,
                 });
                 set({ searchResults: results });
+            } catch (error) {
+                console.error("Structural search failed:", error);
+            } finally {
+                set({ isSearching: false });
+            }
+            return;
+        }
+        const searchId = crypto.randomUUID();
+        set({ searchResults: [], isSearching: true, currentSearchId: searchId, searchFilesScanned: 0 });
+        const unlisten = await listen<SearchBatch>("search-results", (event) => {
+            const batch = event.payload;
+            if (batch.search_id !== searchId) return;
+            if (get().currentSearchId === searchId) {
+                set((state) => ({
+                    searchResults: batch.results.length ? [...state.searchResults, ...batch.results] : state.searchResults,
+                    searchFilesScanned: batch.files_scanned,
+                    isSearching: !batch.done,
+                }));
             }
+            if (batch.done) unlisten();
+        });
+        try {
+            await invoke("start_search", {
+                searchId,
+                path: projectPath,
+                query,
+                options: searchOptions,
+            });
         } catch (error) {
             console.error("Search failed:", error);
-        } finally {
+            unlisten();
             set({ isSearching: false });
         }
     },
+    cancelSearch: async () => {
+        const { currentSearchId } = get();
+        if (!currentSearchId) return;
+        const { invoke } = await import('@tauri-apps/api/core');
+        set({ currentSearchId: null, isSearching: false });
+        await invoke("cancel_search", { searchId: currentSearchId }).catch(console.error);
+    },
 });
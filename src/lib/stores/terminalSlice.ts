@@ -6,7 +6,7 @@ export const createTerminalSlice: StateCreator<EditorState, [], [], TerminalSlic
     activeTerminalId: null,
     isTerminalOpen: false,
     isBottomPanelVisible: false,
-    addTerminal: (cwd?: string, name?: string) => set((state) => {
+    addTerminal: (cwd?: string, name?: string, profileId?: string) => set((state) => {
         const id = `terminal-${Date.now()}`;
         const terminalName = name || `Terminal ${state.terminals.length + 1}`;
         const workingDir = cwd || state.projectPath || '~';
@@ -18,12 +18,49 @@ export const createTerminalSlice: StateCreator<EditorState, [], [], TerminalSlic
             isActive: true, // Auto-activate new terminals
             venvActivated: false,
             isRunning: false,
+            profileId,
         };
         return {
             terminals: [...state.terminals.map(t => ({ ...t, isActive: false })), newTerminal],
             activeTerminalId: id,
         };
     }),
+    restoreTerminal: (session) => set((state) => {
+        if (state.terminals.some(t => t.id === session.id)) return {};
+        const restored: TerminalInstance = {
+            id: session.id,
+            name: session.shell.split(/[/\\]/).pop() || `Terminal ${state.terminals.length + 1}`,
+            output: [],
+            cwd: session.cwd || state.projectPath || '~',
+            isActive: true,
+            venvActivated: false,
+            isRunning: true,
+            profileId: session.profile,
+        };
+        return {
+            terminals: [...state.terminals.map(t => ({ ...t, isActive: false })), restored],
+            activeTerminalId: session.id,
+        };
+    }),
+    addSavedTerminal: (saved) => set((state) => {
+        if (state.terminals.some(t => t.id === saved.id)) return {};
+        const restored: TerminalInstance = {
+            id: saved.id,
+            name: saved.profile || `Terminal ${state.terminals.length + 1}`,
+            output: [],
+            cwd: saved.cwd || state.projectPath || '~',
+            isActive: true,
+            venvActivated: false,
+            isRunning: false,
+            profileId: saved.profile,
+            restoredOutput: saved.scrollback,
+            restoredAt: saved.saved_at,
+        };
+        return {
+            terminals: [...state.terminals.map(t => ({ ...t, isActive: false })), restored],
+            activeTerminalId: saved.id,
+        };
+    }),
     removeTerminal: (id) => set((state) => {
         const newTerminals = state.terminals.filter(t => t.id !== id);
         let newActiveId = state.activeTerminalId;
@@ -6,12 +6,123 @@ export interface OpenFile {
     content: string;
     language: string;
     isDirty: boolean;
+    readOnly?: boolean;
+    version?: FileVersion;
+    changedOnDisk?: boolean;
+    largeFile?: boolean;
+}
+
+export interface LineRange {
+    start_line: number;
+    lines: string[];
+    eof: boolean;
+}
+
+export interface LineIndexStatus {
+    lines_indexed: number;
+    bytes_indexed: number;
+    total_size: number;
+    complete: boolean;
+}
+
+export type FsChange =
+    | { kind: 'created'; node: FileNode }
+    | { kind: 'modified'; node: FileNode }
+    | { kind: 'deleted'; node: FileNode }
+    | { kind: 'renamed'; from: string; node: FileNode };
+
+export interface FsChangedEvent {
+    root: string;
+    changes: FsChange[];
+}
+
+export interface FileVersion {
+    mtime: number;
+    size: number;
+    hash: string;
+}
+
+export interface FileContent {
+    content: string;
+    version: FileVersion;
+    encoding: string;
+    has_bom: boolean;
+    line_ending: 'lf' | 'crlf' | 'mixed';
+    is_binary: boolean;
+}
+
+export interface MatchRange {
+    line: number;
+    start: number;
+    end_line: number;
+    end: number;
 }
 
 export interface SearchResult {
     file: string;
     line: number;
     content: string;
+    matches?: MatchRange[];
+    before?: string[];
+    after?: string[];
+}
+
+export interface StructuralMatch extends SearchResult {
+    captures: Record<string, { text: string; range: MatchRange }>;
+    replacement: string | null;
+}
+
+export interface ReplacePreview {
+    file: string;
+    version: FileVersion;
+    replacements: number;
+    unified: string;
+    insertions: number;
+    deletions: number;
+}
+
+export interface ReplacePreviewSet {
+    files: ReplacePreview[];
+    // More files would change than the preview lists; only listed files are replaced.
+    truncated: boolean;
+}
+
+export interface ReplaceOutcome {
+    undo_token: string;
+    files: string[];
+    replacements: number;
+}
+
+export interface SearchHistoryEntry {
+    id: string;
+    kind: "text" | "structural" | "files" | "symbols";
+    query: string;
+    options: SearchOptions | null;
+    name: string | null;
+    pinned: boolean;
+    last_used: number;
+    use_count: number;
+}
+
+export interface SearchBatch {
+    search_id: string;
+    results: SearchResult[];
+    files_scanned: number;
+    match_count: number;
+    done: boolean;
+    cancelled: boolean;
+}
+
+export interface SearchOptions {
+    regex?: boolean;
+    case_sensitive?: boolean;
+    whole_word?: boolean;
+    multiline?: boolean;
+    include?: string[];
+    exclude?: string[];
+    max_file_size?: number;
+    context_lines?: number;
+    max_results?: number;
 }
 
 export interface RunConfiguration {
@@ -36,6 +147,39 @@ export interface TerminalInstance {
     venvActivated: boolean;
     venvPath?: string;
     isRunning: boolean;
+    profileId?: string;
+    // Scrollback from the previous launch, shown above the new shell's output.
+    restoredOutput?: string;
+    restoredAt?: number;
+}
+
+export interface SavedTerminal {
+    id: string;
+    profile: string;
+    cwd: string | null;
+    started_at: number;
+    saved_at: number;
+    scrollback: string;
+}
+
+export interface PtySessionInfo {
+    id: string;
+    pid: number | null;
+    shell: string;
+    profile: string;
+    cwd: string | null;
+    started_at: number;
+    binary: boolean;
+}
+
+export interface TerminalProfile {
+    id: string;
+    name: string;
+    shell: string;
+    args: string[];
+    env: Record<string, string | null>;
+    cwd: string | null;
+    login: boolean;
 }
 
 export type SplitDirection = 'none' | 'horizontal' | 'vertical';
@@ -47,7 +191,9 @@ export interface TerminalSlice {
     activeTerminalId: string | null;
     isTerminalOpen: boolean;
     isBottomPanelVisible: boolean;
-    addTerminal: (cwd?: string, name?: string) => void;
+    addTerminal: (cwd?: string, name?: string, profileId?: string) => void;
+    restoreTerminal: (session: PtySessionInfo) => void;
+    addSavedTerminal: (saved: SavedTerminal) => void;
     removeTerminal: (id: string) => void;
     setActiveTerminal: (id: string) => void;
     appendToTerminal: (id: string, line: string) => void;
@@ -69,9 +215,13 @@ export interface FileSlice {
     closeFile: (path: string) => void;
     setActiveFile: (path: string) => void;
     updateFileContent: (path: string, content: string, markDirty?: boolean) => void;
+    updateOpenFile: (path: string, changes: Partial<OpenFile>) => void;
+    openLargeFile: (path: string) => void;
     markFileDirty: (path: string, isDirty: boolean) => void;
     renameFile: (oldPath: string, newPath: string) => void;
     refreshTree: () => Promise<void>;
+    saveFile: (path: string) => Promise<boolean>;
+    applyFsChanges: (event: FsChangedEvent) => Promise<void>;
 }
 
 export interface LayoutSlice {
@@ -98,10 +248,17 @@ export interface SearchSlice {
     searchResults: SearchResult[];
     isSearching: boolean;
     searchQuery: string;
+    searchOptions: SearchOptions;
+    currentSearchId: string | null;
+    searchFilesScanned: number;
+    structuralSearch: boolean;
     setFindWidgetOpen: (isOpen: boolean) => void;
     setFindReplaceMode: (isReplace: boolean) => void;
     setSearchQuery: (query: string) => void;
+    setSearchOptions: (options: Partial<SearchOptions>) => void;
+    setStructuralSearch: (enabled: boolean) => void;
     performSearch: (query: string) => Promise<void>;
+    cancelSearch: () => Promise<void>;
 }
 
 export interface ProjectSlice {